@@ -0,0 +1,354 @@
+use std::collections::HashMap;
+
+use meshtastic::protobufs;
+use meshtastic::ts::specta::{self, Type};
+use serde::{Deserialize, Serialize};
+
+/// Telemetry values that alert rules can be defined on
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum TelemetryMetric {
+    BatteryLevel,
+    Voltage,
+    ChannelUtilization,
+    AirUtilTx,
+    Temperature,
+    RelativeHumidity,
+    BarometricPressure,
+    Pm25,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum AlertComparison {
+    Above,
+    Below,
+}
+
+/// A user-defined rule, without the identifier assigned when it is added
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertRuleDefinition {
+    pub name: String,
+    pub metric: TelemetryMetric,
+    pub comparison: AlertComparison,
+    pub threshold: f64,
+
+    /// Node the rule applies to, or any node if `None`
+    pub node_num: Option<u32>,
+
+    /// Seconds the threshold must be crossed before the alert fires
+    #[serde(default)]
+    pub sustain_secs: u32,
+
+    /// Margin past the threshold the value must return by before the alert
+    /// clears, which prevents values around the threshold from flapping
+    #[serde(default)]
+    pub hysteresis: f64,
+
+    /// Minimum seconds between two alerts of the rule for the same node
+    #[serde(default)]
+    pub cooldown_secs: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertRule {
+    pub id: u32,
+    pub definition: AlertRuleDefinition,
+}
+
+impl AlertRuleDefinition {
+    fn is_breached(&self, value: f64) -> bool {
+        match self.comparison {
+            AlertComparison::Above => value > self.threshold,
+            AlertComparison::Below => value < self.threshold,
+        }
+    }
+
+    fn is_cleared(&self, value: f64) -> bool {
+        match self.comparison {
+            AlertComparison::Above => value <= self.threshold - self.hysteresis,
+            AlertComparison::Below => value >= self.threshold + self.hysteresis,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum AlertStatus {
+    Firing,
+    Resolved,
+}
+
+/// An alert that fired or resolved, emitted to clients
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct TelemetryAlert {
+    pub rule_id: u32,
+    pub rule_name: String,
+    pub node_num: u32,
+    pub metric: TelemetryMetric,
+    pub comparison: AlertComparison,
+    pub threshold: f64,
+    pub value: f64,
+    pub status: AlertStatus,
+
+    /// Time the alert changed status in seconds since epoch
+    pub timestamp: u32,
+}
+
+#[derive(Clone, Debug, Default)]
+struct RuleNodeState {
+    breached_since: Option<u32>,
+    active: bool,
+    last_fired: Option<u32>,
+}
+
+/// Evaluates alert rules against the telemetry received from each node
+#[derive(Clone, Debug, Default)]
+pub struct AlertEngine {
+    rules: Vec<AlertRule>,
+    next_rule_id: u32,
+    states: HashMap<(u32, u32), RuleNodeState>,
+}
+
+impl AlertEngine {
+    pub fn rules(&self) -> &[AlertRule] {
+        &self.rules
+    }
+
+    pub fn add_rule(&mut self, definition: AlertRuleDefinition) -> AlertRule {
+        self.next_rule_id += 1;
+
+        let rule = AlertRule {
+            id: self.next_rule_id,
+            definition,
+        };
+
+        self.rules.push(rule.clone());
+        rule
+    }
+
+    /// Removes a rule along with the state of its alerts.
+    /// Returns whether the rule existed.
+    pub fn remove_rule(&mut self, rule_id: u32) -> bool {
+        let rule_count = self.rules.len();
+
+        self.rules.retain(|rule| rule.id != rule_id);
+        self.states.retain(|(id, _), _| *id != rule_id);
+
+        self.rules.len() != rule_count
+    }
+
+    /// Evaluates every rule against a telemetry sample received from a node,
+    /// returning the alerts that fired or resolved
+    pub fn evaluate(
+        &mut self,
+        node_num: u32,
+        telemetry: &protobufs::Telemetry,
+        now: u32,
+    ) -> Vec<TelemetryAlert> {
+        let values = metric_values(telemetry);
+        let mut alerts = vec![];
+
+        for rule in self.rules.iter() {
+            let definition = &rule.definition;
+
+            if definition.node_num.is_some_and(|num| num != node_num) {
+                continue;
+            }
+
+            let Some(value) = values
+                .iter()
+                .find(|(metric, _)| *metric == definition.metric)
+                .map(|(_, value)| *value)
+            else {
+                continue;
+            };
+
+            let state = self.states.entry((rule.id, node_num)).or_default();
+
+            let status = if state.active {
+                if !definition.is_cleared(value) {
+                    continue;
+                }
+
+                state.active = false;
+                state.breached_since = None;
+                AlertStatus::Resolved
+            } else {
+                if !definition.is_breached(value) {
+                    state.breached_since = None;
+                    continue;
+                }
+
+                let breached_since = *state.breached_since.get_or_insert(now);
+                let sustained = now.saturating_sub(breached_since) >= definition.sustain_secs;
+                let cooling_down = state.last_fired.is_some_and(|last_fired| {
+                    now.saturating_sub(last_fired) < definition.cooldown_secs
+                });
+
+                if !sustained || cooling_down {
+                    continue;
+                }
+
+                state.active = true;
+                state.last_fired = Some(now);
+                AlertStatus::Firing
+            };
+
+            alerts.push(TelemetryAlert {
+                rule_id: rule.id,
+                rule_name: definition.name.clone(),
+                node_num,
+                metric: definition.metric,
+                comparison: definition.comparison,
+                threshold: definition.threshold,
+                value,
+                status,
+                timestamp: now,
+            });
+        }
+
+        alerts
+    }
+}
+
+/// Values of the metrics reported in a telemetry packet. Proto3 encodes
+/// unset fields as zero, so zero readings are treated as not reported.
+fn metric_values(telemetry: &protobufs::Telemetry) -> Vec<(TelemetryMetric, f64)> {
+    let values = match telemetry.variant.as_ref() {
+        Some(protobufs::telemetry::Variant::DeviceMetrics(metrics)) => vec![
+            (TelemetryMetric::BatteryLevel, metrics.battery_level.into()),
+            (TelemetryMetric::Voltage, metrics.voltage.into()),
+            (
+                TelemetryMetric::ChannelUtilization,
+                metrics.channel_utilization.into(),
+            ),
+            (TelemetryMetric::AirUtilTx, metrics.air_util_tx.into()),
+        ],
+        Some(protobufs::telemetry::Variant::EnvironmentMetrics(metrics)) => vec![
+            (TelemetryMetric::Temperature, metrics.temperature.into()),
+            (
+                TelemetryMetric::RelativeHumidity,
+                metrics.relative_humidity.into(),
+            ),
+            (
+                TelemetryMetric::BarometricPressure,
+                metrics.barometric_pressure.into(),
+            ),
+        ],
+        Some(protobufs::telemetry::Variant::AirQualityMetrics(metrics)) => {
+            vec![(TelemetryMetric::Pm25, metrics.pm25_standard.into())]
+        }
+        _ => vec![],
+    };
+
+    values
+        .into_iter()
+        .filter(|(_, value)| *value != 0.0)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn channel_utilization(value: f32) -> protobufs::Telemetry {
+        protobufs::Telemetry {
+            variant: Some(protobufs::telemetry::Variant::DeviceMetrics(
+                protobufs::DeviceMetrics {
+                    channel_utilization: value,
+                    ..Default::default()
+                },
+            )),
+            ..Default::default()
+        }
+    }
+
+    fn statuses(alerts: Vec<TelemetryAlert>) -> Vec<AlertStatus> {
+        alerts.into_iter().map(|alert| alert.status).collect()
+    }
+
+    #[test]
+    fn fires_once_sustained_and_resolves_past_hysteresis() {
+        let mut engine = AlertEngine::default();
+        engine.add_rule(AlertRuleDefinition {
+            name: "Busy channel".into(),
+            metric: TelemetryMetric::ChannelUtilization,
+            comparison: AlertComparison::Above,
+            threshold: 40.0,
+            node_num: None,
+            sustain_secs: 300,
+            hysteresis: 5.0,
+            cooldown_secs: 0,
+        });
+
+        assert!(engine.evaluate(1, &channel_utilization(45.0), 0).is_empty());
+        assert!(engine
+            .evaluate(1, &channel_utilization(50.0), 200)
+            .is_empty());
+
+        let alerts = engine.evaluate(1, &channel_utilization(48.0), 300);
+        assert_eq!(statuses(alerts), vec![AlertStatus::Firing]);
+
+        // Still above the threshold minus the hysteresis
+        assert!(engine
+            .evaluate(1, &channel_utilization(38.0), 400)
+            .is_empty());
+
+        let alerts = engine.evaluate(1, &channel_utilization(34.0), 500);
+        assert_eq!(statuses(alerts), vec![AlertStatus::Resolved]);
+    }
+
+    #[test]
+    fn waits_for_cooldown_and_matches_node() {
+        let mut engine = AlertEngine::default();
+        engine.add_rule(AlertRuleDefinition {
+            name: "Busy channel on node 2".into(),
+            metric: TelemetryMetric::ChannelUtilization,
+            comparison: AlertComparison::Above,
+            threshold: 40.0,
+            node_num: Some(2),
+            sustain_secs: 0,
+            hysteresis: 0.0,
+            cooldown_secs: 600,
+        });
+
+        assert!(engine.evaluate(1, &channel_utilization(90.0), 0).is_empty());
+
+        let alerts = engine.evaluate(2, &channel_utilization(90.0), 0);
+        assert_eq!(statuses(alerts), vec![AlertStatus::Firing]);
+
+        let alerts = engine.evaluate(2, &channel_utilization(10.0), 100);
+        assert_eq!(statuses(alerts), vec![AlertStatus::Resolved]);
+
+        assert!(engine
+            .evaluate(2, &channel_utilization(90.0), 200)
+            .is_empty());
+
+        let alerts = engine.evaluate(2, &channel_utilization(90.0), 600);
+        assert_eq!(statuses(alerts), vec![AlertStatus::Firing]);
+    }
+
+    #[test]
+    fn ignores_unset_readings() {
+        let mut engine = AlertEngine::default();
+        engine.add_rule(AlertRuleDefinition {
+            name: "Quiet channel".into(),
+            metric: TelemetryMetric::ChannelUtilization,
+            comparison: AlertComparison::Below,
+            threshold: 5.0,
+            node_num: None,
+            sustain_secs: 0,
+            hysteresis: 0.0,
+            cooldown_secs: 0,
+        });
+
+        assert!(engine.evaluate(1, &channel_utilization(0.0), 0).is_empty());
+
+        let alerts = engine.evaluate(1, &channel_utilization(2.0), 100);
+        assert_eq!(statuses(alerts), vec![AlertStatus::Firing]);
+    }
+}
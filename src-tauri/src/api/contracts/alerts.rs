@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use crate::api::primitives::alerts::{AlertRule, AlertRuleDefinition};
+
+// Add an alert rule, which applies to the telemetry received by every connected radio
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct AddAlertRuleRequest {
+    pub rule: AlertRuleDefinition,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct AddAlertRuleResponse {
+    pub rule: AlertRule,
+}
+
+// Remove an alert rule
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoveAlertRuleRequest {
+    pub rule_id: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoveAlertRuleResponse {} // Empty
+
+// List alert rules
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ListAlertRulesRequest {} // Empty
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ListAlertRulesResponse {
+    pub rules: Vec<AlertRule>,
+}
@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use crate::state::DeviceKey;
+
+// Start capturing packets received from a device
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct StartPacketCaptureRequest {
+    pub device_key: DeviceKey,
+    pub file_path: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct StartPacketCaptureResponse {} // Empty
+
+// Stop capturing packets received from a device
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct StopPacketCaptureRequest {
+    pub device_key: DeviceKey,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct StopPacketCaptureResponse {} // Empty
@@ -82,6 +82,23 @@ pub struct ConnectToTcpPortRequest {
 #[serde(rename_all = "camelCase")]
 pub struct ConnectToTcpPortResponse {} // Empty
 
+// Connect to a packet capture, replaying it as if it were a radio
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectToReplayRequest {
+    pub file_path: String,
+    /// Playback speed relative to the original capture, from 0.01 to 1000, or
+    /// zero to replay without delay
+    pub speed: Option<f64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectToReplayResponse {
+    pub device_key: DeviceKey,
+}
+
 // Drop connection to device
 
 #[derive(Clone, Debug, Serialize, Deserialize, Type)]
@@ -1,7 +1,13 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use specta::Type;
 
-use crate::api::primitives::graph::MeshGraph;
+use crate::api::primitives::graph::{
+    GraphExportFormat, GraphSnapshot, GraphTimeoutPolicy, MeshGraph, ObservedTopology,
+    RoutePrediction, TopologyDiff,
+};
+use crate::ipc::APMincutStringResults;
+use crate::state::DeviceKey;
 
 // Get graph state
 
@@ -18,13 +24,23 @@ pub struct GetGraphStateResponse {
 
 // Initialize timeout handler
 
+/// Starts the graph cleaner if it isn't running. Passed fields update the
+/// timeout policy, including that of a running cleaner.
 #[derive(Clone, Debug, Serialize, Deserialize, Type)]
 #[serde(rename_all = "camelCase")]
-pub struct InitializeTimeoutHandlerRequest {} // Empty
+pub struct InitializeTimeoutHandlerRequest {
+    pub node_timeout_secs: Option<u64>,
+    pub edge_timeout_secs: Option<u64>,
+    pub broadcast_interval_multiplier: Option<f64>,
+    pub clean_period_secs: Option<u64>,
+}
 
 #[derive(Clone, Debug, Serialize, Deserialize, Type)]
 #[serde(rename_all = "camelCase")]
-pub struct InitializeTimeoutHandlerResponse {} // Empty
+pub struct InitializeTimeoutHandlerResponse {
+    /// The timeout policy now in effect
+    pub policy: GraphTimeoutPolicy,
+}
 
 // Stop timeout handler
 
@@ -35,3 +51,132 @@ pub struct StopTimeoutHandlerRequest {} // Empty
 #[derive(Clone, Debug, Serialize, Deserialize, Type)]
 #[serde(rename_all = "camelCase")]
 pub struct StopTimeoutHandlerResponse {} // Empty
+
+// Run network analysis on the graph
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct RunGraphAnalysisRequest {} // Empty
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct RunGraphAnalysisResponse {
+    pub results: APMincutStringResults,
+}
+
+// Find the most reliable path between two nodes
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct GetBestPathRequest {
+    pub from: u32,
+    pub to: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct GetBestPathResponse {
+    /// `None` if no path between the nodes is known
+    pub route: Option<RoutePrediction>,
+}
+
+// Estimate the quality of a known route
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct GetRouteQualityRequest {
+    /// Node numbers from the source to the destination, inclusive
+    pub path: Vec<u32>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct GetRouteQualityResponse {
+    /// `None` if any link along the route isn't known
+    pub route: Option<RoutePrediction>,
+}
+
+// Export the graph to a file
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportGraphRequest {
+    pub format: GraphExportFormat,
+    pub file_path: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportGraphResponse {
+    pub node_count: u32,
+    pub edge_count: u32,
+}
+
+// List the times at which graph snapshots were recorded
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ListGraphSnapshotsRequest {} // Empty
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ListGraphSnapshotsResponse {
+    pub timestamps: Vec<DateTime<Utc>>,
+}
+
+// Get the graph as it was at a point in time
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct GetGraphSnapshotRequest {
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct GetGraphSnapshotResponse {
+    /// `None` if no snapshot was recorded at or before the timestamp
+    pub snapshot: Option<GraphSnapshot>,
+}
+
+// Compare the graph at two points in time
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct GetTopologyDiffRequest {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct GetTopologyDiffResponse {
+    pub diff: TopologyDiff,
+}
+
+// Get the graph merged from all connected radios
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct GetObservedTopologyRequest {
+    /// Only include nodes and links observed by this radio
+    pub observer: Option<DeviceKey>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct GetObservedTopologyResponse {
+    pub topology: ObservedTopology,
+}
+
+// Set whether unknown neighbors are inserted as inferred nodes
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct SetInferNeighborNodesRequest {
+    pub enabled: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct SetInferNeighborNodesResponse {} // Empty
@@ -43,3 +43,38 @@ pub struct DeleteWaypointRequest {
 #[derive(Clone, Debug, Serialize, Deserialize, Type)]
 #[serde(rename_all = "camelCase")]
 pub struct DeleteWaypointResponse {} // Empty
+
+// Send traceroute
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct SendTracerouteRequest {
+    pub device_key: DeviceKey,
+    pub destination: u32,
+    pub channel: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct SendTracerouteResponse {} // Empty
+
+// Request message history from a store-and-forward router
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestStoreForwardHistoryRequest {
+    pub device_key: DeviceKey,
+    /// Router to request history from, defaults to the most recently heard router
+    pub server: Option<u32>,
+    pub channel: u32,
+    /// Minutes of history to request, defaults to the router's configured window
+    pub window_minutes: Option<u32>,
+    /// Maximum number of messages to replay, defaults to the router's configured maximum
+    pub max_messages: Option<u32>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestStoreForwardHistoryResponse {
+    pub server: u32,
+}
@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use crate::api::primitives::mqtt::MqttBridgeConfig;
+
+// Start MQTT bridge
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct StartMqttBridgeRequest {
+    pub config: MqttBridgeConfig,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct StartMqttBridgeResponse {} // Empty
+
+// Stop MQTT bridge
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct StopMqttBridgeRequest {} // Empty
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct StopMqttBridgeResponse {} // Empty
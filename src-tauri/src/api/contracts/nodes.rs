@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use crate::{api::primitives::nodes::NodeDatabaseFormat, state::DeviceKey};
+
+// Export heard nodes to a file
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportNodesRequest {
+    pub device_key: DeviceKey,
+    pub format: NodeDatabaseFormat,
+    pub file_path: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportNodesResponse {
+    pub node_count: u32,
+}
+
+// Import node annotations from a roster file
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportNodeRosterRequest {
+    pub device_key: DeviceKey,
+    pub format: NodeDatabaseFormat,
+    pub file_path: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportNodeRosterResponse {
+    /// Number of nodes annotated by the roster
+    pub annotation_count: u32,
+}
@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use crate::{
+    api::primitives::range_test::RangeTestExportFormat, device::RangeTestRecord, state::DeviceKey,
+};
+
+// Get range test records
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct GetRangeTestRecordsRequest {
+    pub device_key: DeviceKey,
+    /// Only return records received after this time in seconds since epoch
+    pub since: Option<u32>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct GetRangeTestRecordsResponse {
+    /// Records in the order they were received
+    pub records: Vec<RangeTestRecord>,
+}
+
+// Export range test records to a file
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportRangeTestRequest {
+    pub device_key: DeviceKey,
+    pub format: RangeTestExportFormat,
+    pub file_path: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportRangeTestResponse {
+    /// Number of records written, which excludes records without a sender
+    /// position when exporting GeoJSON
+    pub record_count: u32,
+}
+
+// Clear range test records
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ClearRangeTestRequest {
+    pub device_key: DeviceKey,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ClearRangeTestResponse {} // Empty
@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use crate::{
+    api::primitives::telemetry::{NodeTelemetryHistory, TelemetryRetentionPolicy},
+    state::DeviceKey,
+};
+
+// Get the telemetry history of a node
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct GetNodeTelemetryHistoryRequest {
+    pub device_key: DeviceKey,
+    pub node_num: u32,
+    /// Start of the time range in seconds since epoch, defaults to the oldest sample
+    pub start: Option<u32>,
+    /// End of the time range in seconds since epoch, defaults to the latest sample
+    pub end: Option<u32>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct GetNodeTelemetryHistoryResponse {
+    pub history: NodeTelemetryHistory,
+}
+
+// Set how much telemetry history is kept
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct SetTelemetryRetentionRequest {
+    pub device_key: DeviceKey,
+    /// Fields that are omitted keep their current value
+    pub max_samples: Option<u32>,
+    pub full_resolution_secs: Option<u32>,
+    pub downsample_interval_secs: Option<u32>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct SetTelemetryRetentionResponse {
+    pub policy: TelemetryRetentionPolicy,
+}
@@ -0,0 +1,2 @@
+// Re-export alert rule types from the alerts module
+pub use crate::alerts::{AlertRule, AlertRuleDefinition};
@@ -1,2 +1,25 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
 // Re-export the MeshGraph type from the graph module
 pub use crate::graph::ds::graph::MeshGraph;
+
+// Re-export topology history types from the graph module
+pub use crate::graph::ds::history::{GraphSnapshot, TopologyDiff};
+
+// Re-export per-observer topology types from the graph module
+pub use crate::graph::ds::observations::ObservedTopology;
+
+// Re-export the cleaner's timeout policy from the graph module
+pub use crate::graph::ds::timeout_policy::GraphTimeoutPolicy;
+
+// Re-export route types from the graph algorithms module
+pub use crate::graph::algorithms::best_path::{RouteLink, RoutePrediction};
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum GraphExportFormat {
+    GraphMl,
+    Dot,
+    GeoJson,
+}
@@ -1,4 +1,9 @@
+pub mod alerts;
 pub mod connections;
 pub mod graph;
 pub mod mesh;
+pub mod mqtt;
+pub mod nodes;
 pub mod radio;
+pub mod range_test;
+pub mod telemetry;
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+pub const DEFAULT_MQTT_PORT: u16 = 1883;
+pub const DEFAULT_MQTT_ROOT_TOPIC: &str = "msh/US";
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct MqttBridgeConfig {
+    pub host: String,
+    #[serde(default = "default_port")]
+    pub port: u16,
+    pub username: Option<String>,
+    pub password: Option<String>,
+
+    /// Client identifier presented to the broker, randomly generated if unset
+    pub client_id: Option<String>,
+
+    /// Root of the topic tree, including the region (e.g., "msh/US")
+    #[serde(default = "default_root_topic")]
+    pub root_topic: String,
+
+    /// Whether to send messages published to the downlink topic through the radio
+    #[serde(default)]
+    pub downlink_enabled: bool,
+}
+
+fn default_port() -> u16 {
+    DEFAULT_MQTT_PORT
+}
+
+fn default_root_topic() -> String {
+    DEFAULT_MQTT_ROOT_TOPIC.into()
+}
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+// Re-export the roster annotation type from the device module
+pub use crate::device::roster::NodeAnnotation;
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum NodeDatabaseFormat {
+    Csv,
+    Json,
+}
@@ -0,0 +1,2 @@
+// Re-export telemetry retention types from the device module
+pub use crate::device::retention::{NodeTelemetryHistory, TelemetryRetentionPolicy};
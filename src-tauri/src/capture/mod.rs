@@ -0,0 +1,275 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use bytes::{Buf, BufMut};
+use log::{debug, warn};
+use meshtastic::protobufs;
+use meshtastic::Message;
+
+use crate::packet_api::MeshPacketApi;
+
+/// Identifies capture files and their format version
+const CAPTURE_MAGIC: &[u8; 8] = b"MSHCAP01";
+
+pub const CAPTURE_FILE_EXTENSION: &str = "mshcap";
+
+/// Prefixes the device keys of replayed captures, which have no radio behind them
+pub const REPLAY_DEVICE_KEY_PREFIX: &str = "replay:";
+
+/// A packet received from a radio and the time it was received at
+#[derive(Clone, Debug, PartialEq)]
+pub struct CaptureRecord {
+    /// Milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+    pub packet: protobufs::FromRadio,
+}
+
+/// Records `FromRadio` packets to a capture file. Each record is the receive
+/// time as a little-endian `u64` of milliseconds since the Unix epoch,
+/// followed by the length-delimited protobuf encoding of the packet.
+pub struct CaptureWriter {
+    path: PathBuf,
+    writer: BufWriter<File>,
+}
+
+impl CaptureWriter {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        debug!("Creating packet capture at {:?}", path);
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(CAPTURE_MAGIC)?;
+        writer.flush()?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            writer,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn write(&mut self, record: &CaptureRecord) -> io::Result<()> {
+        let mut encoded = Vec::with_capacity(8 + record.packet.encoded_len() + 10);
+        encoded.put_u64_le(record.timestamp_ms);
+        record
+            .packet
+            .encode_length_delimited(&mut encoded)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        // Flush every record so that captures survive crashes, which is when they're needed
+        self.writer.write_all(&encoded)?;
+        self.writer.flush()
+    }
+}
+
+/// Name of the file a connection is captured to when captures are
+/// enabled for all connections, unique to the connection and start time
+pub fn capture_file_name(device_key: &str, started_at: chrono::DateTime<chrono::Utc>) -> String {
+    // Device keys are port names, Bluetooth names or socket addresses,
+    // none of which are guaranteed to be valid file names
+    let sanitized_key: String = device_key
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+
+    format!(
+        "{}-{}.{}",
+        sanitized_key,
+        started_at.format("%Y%m%dT%H%M%SZ"),
+        CAPTURE_FILE_EXTENSION
+    )
+}
+
+/// Reads all records from a capture file. A truncated final record (e.g., from
+/// a crash mid-write) is dropped rather than failing the whole capture.
+pub fn read_capture(path: &Path) -> io::Result<Vec<CaptureRecord>> {
+    let contents = std::fs::read(path)?;
+    parse_capture(&contents)
+}
+
+fn parse_capture(contents: &[u8]) -> io::Result<Vec<CaptureRecord>> {
+    let mut remaining = contents
+        .strip_prefix(CAPTURE_MAGIC.as_slice())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Not a packet capture file"))?;
+
+    let mut records = vec![];
+
+    while remaining.has_remaining() {
+        if remaining.remaining() < 8 {
+            warn!("Dropping truncated capture record");
+            break;
+        }
+
+        let timestamp_ms = remaining.get_u64_le();
+
+        match protobufs::FromRadio::decode_length_delimited(&mut remaining) {
+            Ok(packet) => records.push(CaptureRecord {
+                timestamp_ms,
+                packet,
+            }),
+            Err(e) => {
+                warn!("Dropping malformed capture record: {}", e);
+                break;
+            }
+        }
+    }
+
+    Ok(records)
+}
+
+/// Slowest playback speed accepted for replays, other than zero
+pub const MIN_REPLAY_SPEED: f64 = 0.01;
+
+/// Fastest playback speed accepted for replays
+pub const MAX_REPLAY_SPEED: f64 = 1000.0;
+
+/// Whether replays can be played back at the passed speed
+pub fn is_valid_replay_speed(speed: f64) -> bool {
+    speed == 0.0 || (MIN_REPLAY_SPEED..=MAX_REPLAY_SPEED).contains(&speed)
+}
+
+/// Returns how long to wait before replaying a record received `elapsed_ms`
+/// after the previous one. A speed of zero replays without any delay, and
+/// delays too long to represent saturate.
+pub fn replay_delay(elapsed_ms: u64, speed: f64) -> Duration {
+    if speed <= 0.0 || !speed.is_finite() {
+        return Duration::ZERO;
+    }
+
+    Duration::try_from_secs_f64(elapsed_ms as f64 / 1000.0 / speed).unwrap_or(Duration::MAX)
+}
+
+/// Feeds captured packets through a packet API without any delay, returning
+/// the number of packets that were handled successfully. Only the device state
+/// is updated, packets aren't published or bridged like those of a live radio.
+pub fn replay_into(
+    packet_api: &mut MeshPacketApi,
+    records: impl IntoIterator<Item = CaptureRecord>,
+) -> usize {
+    use meshtastic::packet::PacketRouter;
+
+    let mut handled = 0;
+
+    for record in records {
+        match packet_api.handle_packet_from_radio(record.packet) {
+            Ok(_) => handled += 1,
+            Err(e) => debug!("Replayed packet not handled: {}", e),
+        }
+    }
+
+    handled
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::device::MeshDevice;
+    use crate::graph::ds::graph::MeshGraph;
+    use crate::ipc::events::BroadcastEventDispatcher;
+
+    fn node_info_packet(node_num: u32) -> protobufs::FromRadio {
+        protobufs::FromRadio {
+            payload_variant: Some(protobufs::from_radio::PayloadVariant::NodeInfo(
+                protobufs::NodeInfo {
+                    num: node_num,
+                    ..Default::default()
+                },
+            )),
+            ..Default::default()
+        }
+    }
+
+    fn encode_capture(records: &[CaptureRecord]) -> Vec<u8> {
+        let mut contents = CAPTURE_MAGIC.to_vec();
+
+        for record in records {
+            contents.put_u64_le(record.timestamp_ms);
+            record
+                .packet
+                .encode_length_delimited(&mut contents)
+                .unwrap();
+        }
+
+        contents
+    }
+
+    #[test]
+    fn capture_round_trips_and_drops_truncated_records() {
+        let records = vec![
+            CaptureRecord {
+                timestamp_ms: 1_000,
+                packet: node_info_packet(1),
+            },
+            CaptureRecord {
+                timestamp_ms: 2_500,
+                packet: node_info_packet(2),
+            },
+        ];
+
+        let mut contents = encode_capture(&records);
+        assert_eq!(parse_capture(&contents).unwrap(), records);
+
+        contents.truncate(contents.len() - 2);
+        assert_eq!(parse_capture(&contents).unwrap(), records[..1].to_vec());
+
+        assert!(parse_capture(b"not a capture").is_err());
+    }
+
+    #[test]
+    fn capture_file_name_is_sanitized() {
+        let started_at = chrono::DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+
+        assert_eq!(
+            capture_file_name("/dev/ttyUSB0", started_at),
+            "_dev_ttyUSB0-20231114T221320Z.mshcap"
+        );
+    }
+
+    #[test]
+    fn replay_delay_scales_with_speed() {
+        assert_eq!(replay_delay(1_000, 1.0), Duration::from_secs(1));
+        assert_eq!(replay_delay(1_000, 4.0), Duration::from_millis(250));
+        assert_eq!(replay_delay(1_000, 0.0), Duration::ZERO);
+        assert_eq!(replay_delay(1_000, 1e-300), Duration::MAX);
+
+        assert!(is_valid_replay_speed(0.0));
+        assert!(!is_valid_replay_speed(1e-300));
+        assert!(!is_valid_replay_speed(f64::NAN));
+        assert!(!is_valid_replay_speed(f64::INFINITY));
+    }
+
+    #[test]
+    fn replayed_captures_populate_device() {
+        let records = vec![
+            CaptureRecord {
+                timestamp_ms: 0,
+                packet: node_info_packet(0x11),
+            },
+            CaptureRecord {
+                timestamp_ms: 10,
+                packet: node_info_packet(0x22),
+            },
+        ];
+
+        let mut packet_api = MeshPacketApi::new(
+            Arc::new(BroadcastEventDispatcher::new(16)),
+            "replay".into(),
+            MeshDevice::new(),
+            Arc::new(Mutex::new(MeshGraph::new())),
+            None,
+        );
+
+        let contents = encode_capture(&records);
+        let handled = replay_into(&mut packet_api, parse_capture(&contents).unwrap());
+
+        assert_eq!(handled, 2);
+        assert!(packet_api.device.nodes.contains_key(&0x11));
+        assert!(packet_api.device.nodes.contains_key(&0x22));
+    }
+}
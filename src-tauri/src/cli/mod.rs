@@ -5,12 +5,27 @@ use crate::state;
 pub fn handle_cli_matches(
     app: &mut tauri::App,
     inital_autoconnect_state: &mut state::autoconnect::AutoConnectState,
+    http_address: &mut Option<String>,
+    api_token: &mut Option<String>,
 ) -> Result<(), String> {
     use tauri_plugin_cli::CliExt;
     match app.cli().matches() {
         Ok(matches) => {
             let args = matches.args;
 
+            // Check if user has requested the HTTP API, which is served alongside the UI
+            if let Some(http_arg) = args.get("http") {
+                if let serde_json::Value::String(address) = http_arg.value.clone() {
+                    *http_address = Some(address);
+                }
+            }
+
+            if let Some(token_arg) = args.get("api-token") {
+                if let serde_json::Value::String(token) = token_arg.value.clone() {
+                    *api_token = Some(token);
+                }
+            }
+
             // Check if user has specified a port name to automatically connect to
             // If so, store it for future connection attempts
             if let Some(port_arg) = args.get("port") {
@@ -0,0 +1,331 @@
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+use crate::api::contracts::connections::{
+    ConnectToBluetoothRequest, ConnectToReplayRequest, ConnectToSerialPortRequest,
+    ConnectToTcpPortRequest,
+};
+use crate::api::primitives::mqtt::{MqttBridgeConfig, DEFAULT_MQTT_PORT, DEFAULT_MQTT_ROOT_TOPIC};
+
+pub const DEFAULT_LISTEN_ADDRESS: &str = "127.0.0.1:4404";
+
+pub const USAGE: &str = "\
+Runs the Meshtastic network management client without a window.
+
+USAGE:
+    meshtastic-daemon [OPTIONS]
+
+OPTIONS:
+    -c, --config <FILE>       JSON configuration file, overridden by any other options
+    -l, --listen <ADDRESS>    Address to serve commands and events on [default: 127.0.0.1:4404]
+        --http <ADDRESS>      Address to serve the HTTP and WebSocket API on (requires the
+                              \"http-api\" feature)
+        --api-token <TOKEN>   Token clients must authenticate with, read from
+                              MESHTASTIC_API_TOKEN or generated if unset
+        --allow-origin <URL>  Web origin allowed to call the HTTP API (repeatable)
+    -d, --data-dir <DIR>      Directory to persist device history in
+        --capture-dir <DIR>   Directory to capture every connection's packets in
+    -s, --serial <PORT>       Connect to a serial port on startup (repeatable)
+    -b, --baud-rate <RATE>    Baud rate used for serial connections
+    -t, --tcp <ADDRESS>       Connect to a TCP address on startup (repeatable)
+        --ble <NAME>          Connect to a Bluetooth device on startup (repeatable)
+        --replay <FILE>       Replay a packet capture as a connection (repeatable)
+        --replay-speed <X>    Playback speed of replays, 0 replays without delay [default: 1]
+        --no-graph-cleaning   Don't remove timed out nodes from the mesh graph
+        --mqtt <HOST[:PORT]>  Bridge decoded packets to an MQTT broker
+        --mqtt-root <TOPIC>   Root of the MQTT topic tree [default: msh/US]
+        --mqtt-downlink       Send messages published to the MQTT downlink topic
+    -h, --help                Print this message
+";
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum DaemonConnection {
+    Serial(ConnectToSerialPortRequest),
+    Tcp(ConnectToTcpPortRequest),
+    Bluetooth(ConnectToBluetoothRequest),
+    Replay(ConnectToReplayRequest),
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DaemonConfig {
+    /// Address the command and event socket listens on
+    pub listen_address: String,
+
+    /// Address the HTTP and WebSocket API listens on, disabled if unset
+    pub http_address: Option<String>,
+
+    /// Token clients must authenticate with, generated on startup if unset
+    pub api_token: Option<String>,
+
+    /// Web origins allowed to call the HTTP API from a browser
+    pub allowed_origins: Vec<String>,
+
+    /// Directory device history is persisted in, history is disabled if unset
+    pub data_dir: Option<PathBuf>,
+
+    /// Directory the packets of every connection are captured in, disabled if unset
+    pub capture_dir: Option<PathBuf>,
+
+    /// Connections opened when the daemon starts
+    pub connections: Vec<DaemonConnection>,
+
+    /// Whether to periodically remove timed out nodes from the mesh graph
+    pub clean_graph: bool,
+
+    /// Broker that decoded packets are bridged to, disabled if unset
+    pub mqtt: Option<MqttBridgeConfig>,
+}
+
+impl Default for DaemonConfig {
+    fn default() -> Self {
+        Self {
+            listen_address: DEFAULT_LISTEN_ADDRESS.into(),
+            http_address: None,
+            api_token: None,
+            allowed_origins: vec![],
+            data_dir: None,
+            capture_dir: None,
+            connections: vec![],
+            clean_graph: true,
+            mqtt: None,
+        }
+    }
+}
+
+/// The outcome of parsing the daemon's command line arguments
+pub enum DaemonArgs {
+    Run(DaemonConfig),
+    Help,
+}
+
+impl DaemonConfig {
+    pub fn from_file(path: &PathBuf) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read config file {:?}: {}", path, e))?;
+
+        serde_json::from_str(&contents)
+            .map_err(|e| format!("Failed to parse config file {:?}: {}", path, e))
+    }
+
+    /// Parses command line arguments (excluding the binary name), loading
+    /// the config file first if one is passed so other arguments override it
+    pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> Result<DaemonArgs, String> {
+        let args: Vec<String> = args.into_iter().collect();
+
+        let mut config = match config_file_arg(&args)? {
+            Some(path) => Self::from_file(&path)?,
+            None => Self::default(),
+        };
+
+        let mut baud_rate = None;
+        let mut replay_speed = None;
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| {
+                args.next()
+                    .ok_or_else(|| format!("Missing value for argument \"{}\"", name))
+            };
+
+            match arg.as_str() {
+                "-c" | "--config" => {
+                    // Already loaded above
+                    value(&arg)?;
+                }
+                "-l" | "--listen" => config.listen_address = value(&arg)?,
+                "--http" => config.http_address = Some(value(&arg)?),
+                "--api-token" => config.api_token = Some(value(&arg)?),
+                "--allow-origin" => config.allowed_origins.push(value(&arg)?),
+                "-d" | "--data-dir" => config.data_dir = Some(value(&arg)?.into()),
+                "--capture-dir" => config.capture_dir = Some(value(&arg)?.into()),
+                "-s" | "--serial" => {
+                    config
+                        .connections
+                        .push(DaemonConnection::Serial(ConnectToSerialPortRequest {
+                            port_name: value(&arg)?,
+                            baud_rate: None,
+                            dtr: None,
+                            rts: None,
+                        }));
+                }
+                "-b" | "--baud-rate" => {
+                    let rate = value(&arg)?;
+                    baud_rate = Some(
+                        rate.parse::<u32>()
+                            .map_err(|e| format!("Invalid baud rate \"{}\": {}", rate, e))?,
+                    );
+                }
+                "-t" | "--tcp" => {
+                    config
+                        .connections
+                        .push(DaemonConnection::Tcp(ConnectToTcpPortRequest {
+                            address: value(&arg)?,
+                        }));
+                }
+                "--ble" => {
+                    config.connections.push(DaemonConnection::Bluetooth(
+                        ConnectToBluetoothRequest {
+                            bluetooth_name: value(&arg)?,
+                        },
+                    ));
+                }
+                "--replay" => {
+                    config
+                        .connections
+                        .push(DaemonConnection::Replay(ConnectToReplayRequest {
+                            file_path: value(&arg)?,
+                            speed: None,
+                        }));
+                }
+                "--replay-speed" => {
+                    let speed = value(&arg)?;
+                    replay_speed = Some(
+                        speed
+                            .parse::<f64>()
+                            .map_err(|e| format!("Invalid replay speed \"{}\": {}", speed, e))?,
+                    );
+                }
+                "--no-graph-cleaning" => config.clean_graph = false,
+                "--mqtt" => {
+                    let (host, port) = parse_host_port(&value(&arg)?)?;
+                    let mqtt = mqtt_config(&mut config);
+                    mqtt.host = host;
+                    mqtt.port = port.unwrap_or(mqtt.port);
+                }
+                "--mqtt-root" => mqtt_config(&mut config).root_topic = value(&arg)?,
+                "--mqtt-downlink" => mqtt_config(&mut config).downlink_enabled = true,
+                "-h" | "--help" => return Ok(DaemonArgs::Help),
+                _ => return Err(format!("Unknown argument \"{}\"\n\n{}", arg, USAGE)),
+            }
+        }
+
+        // Apply the baud rate and replay speed to connections that don't specify their own
+        for connection in config.connections.iter_mut() {
+            match connection {
+                DaemonConnection::Serial(serial) => {
+                    serial.baud_rate = serial.baud_rate.or(baud_rate);
+                }
+                DaemonConnection::Replay(replay) => {
+                    replay.speed = replay.speed.or(replay_speed);
+                }
+                _ => {}
+            }
+        }
+
+        if config
+            .mqtt
+            .as_ref()
+            .is_some_and(|mqtt| mqtt.host.is_empty())
+        {
+            return Err("MQTT options require a broker passed via \"--mqtt\"".into());
+        }
+
+        Ok(DaemonArgs::Run(config))
+    }
+}
+
+/// Returns the MQTT bridge configuration, creating a default one if needed
+fn mqtt_config(config: &mut DaemonConfig) -> &mut MqttBridgeConfig {
+    config.mqtt.get_or_insert_with(|| MqttBridgeConfig {
+        host: String::new(),
+        port: DEFAULT_MQTT_PORT,
+        username: None,
+        password: None,
+        client_id: None,
+        root_topic: DEFAULT_MQTT_ROOT_TOPIC.into(),
+        downlink_enabled: false,
+    })
+}
+
+fn parse_host_port(address: &str) -> Result<(String, Option<u16>), String> {
+    match address.rsplit_once(':') {
+        Some((host, port)) => {
+            let port = port
+                .parse::<u16>()
+                .map_err(|e| format!("Invalid port in \"{}\": {}", address, e))?;
+            Ok((host.into(), Some(port)))
+        }
+        None => Ok((address.into(), None)),
+    }
+}
+
+fn config_file_arg(args: &[String]) -> Result<Option<PathBuf>, String> {
+    match args.iter().position(|a| a == "-c" || a == "--config") {
+        Some(index) => args
+            .get(index + 1)
+            .map(|path| Some(path.into()))
+            .ok_or_else(|| "Missing value for argument \"--config\"".into()),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> DaemonConfig {
+        match DaemonConfig::from_args(args.iter().map(|a| a.to_string())) {
+            Ok(DaemonArgs::Run(config)) => config,
+            Ok(DaemonArgs::Help) => panic!("Expected config, got help"),
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    #[test]
+    fn defaults_without_arguments() {
+        let config = parse(&[]);
+        assert_eq!(config.listen_address, DEFAULT_LISTEN_ADDRESS);
+        assert!(config.connections.is_empty());
+        assert!(config.clean_graph);
+    }
+
+    #[test]
+    fn baud_rate_applies_to_serial_connections() {
+        let config = parse(&[
+            "--serial",
+            "/dev/ttyUSB0",
+            "-b",
+            "9600",
+            "--tcp",
+            "10.0.0.2",
+        ]);
+        assert_eq!(config.connections.len(), 2);
+
+        match &config.connections[0] {
+            DaemonConnection::Serial(serial) => assert_eq!(serial.baud_rate, Some(9600)),
+            _ => panic!("Expected serial connection"),
+        }
+    }
+
+    #[test]
+    fn replay_speed_applies_to_replays() {
+        let config = parse(&["--replay", "mesh.mshcap", "--replay-speed", "0"]);
+
+        match &config.connections[0] {
+            DaemonConnection::Replay(replay) => {
+                assert_eq!(replay.file_path, "mesh.mshcap");
+                assert_eq!(replay.speed, Some(0.0));
+            }
+            _ => panic!("Expected replay connection"),
+        }
+    }
+
+    #[test]
+    fn parses_mqtt_broker_address() {
+        let config = parse(&["--mqtt", "localhost:1884", "--mqtt-downlink"]);
+        let mqtt = config.mqtt.expect("Expected MQTT config");
+
+        assert_eq!(mqtt.host, "localhost");
+        assert_eq!(mqtt.port, 1884);
+        assert_eq!(mqtt.root_topic, DEFAULT_MQTT_ROOT_TOPIC);
+        assert!(mqtt.downlink_enabled);
+    }
+
+    #[test]
+    fn rejects_unknown_arguments() {
+        assert!(DaemonConfig::from_args(vec!["--bogus".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,174 @@
+use std::sync::Arc;
+
+use log::{error, info, warn};
+
+use crate::api::contracts::graph::InitializeTimeoutHandlerRequest;
+use crate::api::contracts::mqtt::StartMqttBridgeRequest;
+use crate::domains::connections::{
+    handle_connect_to_bluetooth, handle_connect_to_replay, handle_connect_to_serial_port,
+    handle_connect_to_tcp_port,
+};
+use crate::domains::graph::handle_initialize_timeout_handler;
+use crate::domains::mqtt::handle_start_mqtt_bridge;
+use crate::ipc::auth::ApiAuth;
+use crate::ipc::context::IpcContext;
+use crate::ipc::events::BroadcastEventDispatcher;
+use crate::ipc::socket::serve_socket;
+use crate::ipc::spawn_http_api;
+use crate::ipc::CommandError;
+use crate::state;
+
+use self::config::{DaemonArgs, DaemonConfig, DaemonConnection, USAGE};
+
+pub mod config;
+
+/// Number of events buffered for each socket client before it is considered lagging
+const EVENT_BUFFER_CAPACITY: usize = 1024;
+
+/// Runs the client without a Tauri window, serving the same commands and
+/// events over a local socket. Configured via command line arguments.
+pub fn run() {
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .init();
+
+    let config = match DaemonConfig::from_args(std::env::args().skip(1)) {
+        Ok(DaemonArgs::Run(config)) => config,
+        Ok(DaemonArgs::Help) => {
+            print!("{}", USAGE);
+            return;
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+
+    if let Err(e) = tauri::async_runtime::block_on(run_daemon(config)) {
+        error!("Daemon stopped: {}", e);
+        std::process::exit(1);
+    }
+}
+
+async fn run_daemon(config: DaemonConfig) -> Result<(), String> {
+    let dispatcher = BroadcastEventDispatcher::new(EVENT_BUFFER_CAPACITY);
+    let auth = ApiAuth::from_config(config.api_token.clone());
+
+    // Printed once rather than logged, so that the token isn't persisted
+    if let Some(token) = auth.generated_token() {
+        println!("No API token configured, generated token \"{}\"", token);
+    }
+
+    let context = IpcContext {
+        events: state::events::EventsState::new(Arc::new(dispatcher.clone())),
+        autoconnect: state::autoconnect::AutoConnectState::new(),
+        mesh_devices: state::mesh_devices::MeshDevicesState::new(
+            config.data_dir.clone(),
+            config.capture_dir.clone(),
+        ),
+        radio_connections: state::radio_connections::RadioConnectionsState::new(),
+        mesh_graph: state::graph::GraphState::new(),
+        mqtt_bridge: state::mqtt::MqttBridgeState::new(),
+    };
+
+    if config.clean_graph {
+        handle_initialize_timeout_handler(
+            InitializeTimeoutHandlerRequest {
+                node_timeout_secs: None,
+                edge_timeout_secs: None,
+                broadcast_interval_multiplier: None,
+                clean_period_secs: None,
+            },
+            &context.events,
+            &context.mesh_graph,
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+    }
+
+    if let Some(mqtt) = config.mqtt.clone() {
+        handle_start_mqtt_bridge(
+            StartMqttBridgeRequest { config: mqtt },
+            &context.events,
+            &context.mesh_devices,
+            &context.radio_connections,
+            &context.mqtt_bridge,
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+    }
+
+    for connection in config.connections.iter() {
+        // A radio that is unavailable on startup shouldn't prevent the others from connecting
+        if let Err(e) = open_connection(&context, connection.clone()).await {
+            warn!("Failed to open connection {:?}: {}", connection, e);
+        }
+    }
+
+    if let Some(http_address) = config.http_address.clone() {
+        spawn_http_api(
+            http_address,
+            context.clone(),
+            dispatcher.clone(),
+            auth.clone()
+                .with_allowed_origins(config.allowed_origins.clone()),
+        )?;
+    }
+
+    info!("Daemon started");
+
+    serve_socket(&config.listen_address, context, dispatcher, auth)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+async fn open_connection(
+    context: &IpcContext,
+    connection: DaemonConnection,
+) -> Result<(), CommandError> {
+    info!("Opening connection {:?}", connection);
+
+    match connection {
+        DaemonConnection::Serial(request) => {
+            handle_connect_to_serial_port(
+                request,
+                &context.events,
+                &context.mesh_devices,
+                &context.radio_connections,
+                &context.mesh_graph,
+            )
+            .await?;
+        }
+        DaemonConnection::Tcp(request) => {
+            handle_connect_to_tcp_port(
+                request,
+                &context.events,
+                &context.mesh_devices,
+                &context.radio_connections,
+                &context.mesh_graph,
+            )
+            .await?;
+        }
+        DaemonConnection::Bluetooth(request) => {
+            handle_connect_to_bluetooth(
+                request,
+                &context.events,
+                &context.mesh_devices,
+                &context.radio_connections,
+                &context.mesh_graph,
+            )
+            .await?;
+        }
+        DaemonConnection::Replay(request) => {
+            handle_connect_to_replay(
+                request,
+                &context.events,
+                &context.mesh_devices,
+                &context.mesh_graph,
+            )
+            .await?;
+        }
+    }
+
+    Ok(())
+}
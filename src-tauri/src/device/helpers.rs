@@ -1,7 +1,10 @@
 use rand::{distributions::Standard, prelude::Distribution, Rng};
 use std::time::UNIX_EPOCH;
 
-use super::MeshDevice;
+use super::{MeshDevice, TracerouteHop};
+
+/// Sentinel value used by the firmware for hops that did not report an SNR
+const TRACEROUTE_UNKNOWN_SNR: i32 = i8::MIN as i32;
 
 pub fn get_current_time_u32() -> u32 {
     std::time::SystemTime::now()
@@ -12,6 +15,26 @@ pub fn get_current_time_u32() -> u32 {
         .expect("Could not convert u128 to u32")
 }
 
+pub fn get_current_time_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Could not get time since unix epoch")
+        .as_millis()
+        .try_into()
+        .expect("Could not convert u128 to u64")
+}
+
+/// Formats a node number as a node id, the way the firmware and other
+/// Meshtastic clients show it
+pub fn format_node_id(node_num: u32) -> String {
+    format!("!{:08x}", node_num)
+}
+
+/// Parses the sequence number out of a range test payload, sent by the firmware as "seq <n>"
+pub fn parse_range_test_sequence(payload: &str) -> Option<u32> {
+    payload.trim().strip_prefix("seq ")?.trim().parse().ok()
+}
+
 pub fn generate_rand_id<T>() -> T
 where
     Standard: Distribution<T>,
@@ -38,6 +61,45 @@ pub fn get_channel_name(device: &mut MeshDevice, channel_id: &u32) -> Option<Str
     Some(db_channel_settings.name.clone())
 }
 
+/// Returns the name the firmware uses for a channel in MQTT topics. Unnamed
+/// channels are named after the radio's modem preset (e.g., "LongFast").
+pub fn get_channel_topic_name(device: &MeshDevice, channel_id: &u32) -> String {
+    let channel_name = device
+        .channels
+        .get(channel_id)
+        .and_then(|c| c.config.settings.as_ref())
+        .map(|s| s.name.clone())
+        .filter(|name| !name.is_empty());
+
+    channel_name.unwrap_or_else(|| {
+        let preset_name = device
+            .config
+            .lora
+            .as_ref()
+            .map(|lora| lora.modem_preset().as_str_name())
+            .unwrap_or("LONG_FAST");
+
+        format_modem_preset_name(preset_name)
+    })
+}
+
+/// Converts a protobuf modem preset name (e.g., "LONG_FAST") into
+/// the display name used by the firmware (e.g., "LongFast")
+pub fn format_modem_preset_name(preset_name: &str) -> String {
+    preset_name
+        .split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => {
+                    first.to_ascii_uppercase().to_string() + &chars.as_str().to_lowercase()
+                }
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
 /// Converts a mesh location field (e.g., latitude) from
 /// its mesh integer representation to a float.
 ///
@@ -68,6 +130,43 @@ pub fn convert_location_field_to_protos(field: f32) -> i32 {
     (field * 1e7).floor() as i32
 }
 
+/// Builds an ordered list of traceroute hops from a `RouteDiscovery` route.
+///
+/// # Arguments
+///
+/// * `start` - The node the route starts at.
+/// * `intermediate` - The relaying nodes reported in the route, in order.
+/// * `end` - The node the route ends at.
+/// * `snrs` - The SNR each subsequent hop heard its predecessor at, in quarter dB.
+///
+/// # Returns
+///
+/// * `Vec<TracerouteHop>` - The hops from `start` to `end`, inclusive.
+///
+pub fn build_traceroute_hops(
+    start: u32,
+    intermediate: &[u32],
+    end: u32,
+    snrs: &[i32],
+) -> Vec<TracerouteHop> {
+    let node_nums = std::iter::once(start)
+        .chain(intermediate.iter().copied())
+        .chain(std::iter::once(end));
+
+    node_nums
+        .enumerate()
+        .map(|(index, node_num)| {
+            let snr = index
+                .checked_sub(1)
+                .and_then(|snr_index| snrs.get(snr_index))
+                .filter(|snr| **snr != TRACEROUTE_UNKNOWN_SNR)
+                .map(|snr| *snr as f32 / 4.0);
+
+            TracerouteHop { node_num, snr }
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -85,4 +184,34 @@ mod tests {
         let mesh_lat = convert_location_field_to_protos(lat);
         assert_eq!(mesh_lat, 27_030_000);
     }
+
+    #[test]
+    fn test_format_modem_preset_name() {
+        assert_eq!(format_modem_preset_name("LONG_FAST"), "LongFast");
+        assert_eq!(format_modem_preset_name("VERY_LONG_SLOW"), "VeryLongSlow");
+    }
+
+    #[test]
+    fn test_format_node_id() {
+        assert_eq!(format_node_id(42), "!0000002a");
+        assert_eq!(format_node_id(0xdeadbeef), "!deadbeef");
+    }
+
+    #[test]
+    fn test_parse_range_test_sequence() {
+        assert_eq!(parse_range_test_sequence("seq 42"), Some(42));
+        assert_eq!(parse_range_test_sequence("seq 7\n"), Some(7));
+        assert_eq!(parse_range_test_sequence("hello"), None);
+    }
+
+    #[test]
+    fn test_build_traceroute_hops() {
+        let hops = build_traceroute_hops(1, &[2, 3], 4, &[40, TRACEROUTE_UNKNOWN_SNR, -10]);
+
+        let node_nums: Vec<u32> = hops.iter().map(|h| h.node_num).collect();
+        assert_eq!(node_nums, vec![1, 2, 3, 4]);
+
+        let snrs: Vec<Option<f32>> = hops.iter().map(|h| h.snr).collect();
+        assert_eq!(snrs, vec![None, Some(10.0), None, Some(-2.5)]);
+    }
 }
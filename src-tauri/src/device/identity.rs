@@ -0,0 +1,147 @@
+use log::debug;
+use meshtastic::protobufs;
+
+use super::helpers::get_current_time_u32;
+use super::{LastHeardMetadata, MeshDevice, MeshNode};
+
+impl MeshNode {
+    /// Updates what is known about the node from a packet it sent
+    fn record_packet(&mut self, packet: &protobufs::MeshPacket, now: u32) {
+        self.first_seen.get_or_insert(now);
+        self.packet_count = self.packet_count.saturating_add(1);
+        self.via_mqtt = packet.via_mqtt;
+
+        // Packets without a hop start are from firmware that doesn't report
+        // hops, so a previously known hop count may no longer be accurate
+        self.hops_away =
+            (packet.hop_start != 0).then(|| packet.hop_start.saturating_sub(packet.hop_limit));
+
+        self.last_heard = Some(LastHeardMetadata {
+            timestamp: now,
+            snr: packet.rx_snr,
+            channel: packet.channel,
+        });
+    }
+}
+
+impl MeshDevice {
+    /// Returns the node with the passed number. Every node is created through
+    /// here, so that nodes are always keyed and labelled with the same number.
+    pub fn node_mut(&mut self, node_num: u32) -> &mut MeshNode {
+        self.nodes.entry(node_num).or_insert_with(|| {
+            debug!("Inserting new node with id {}", node_num);
+
+            MeshNode {
+                first_seen: Some(get_current_time_u32()),
+                ..MeshNode::new(node_num)
+            }
+        })
+    }
+
+    /// Records a packet received from a node, whatever its contents. Called
+    /// for every received packet before it is handled by its port.
+    pub fn record_node_packet(&mut self, packet: &protobufs::MeshPacket) {
+        let now = get_current_time_u32();
+        self.node_mut(packet.from).record_packet(packet, now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::device::UserPacket;
+
+    #[test]
+    fn creates_unknown_senders_with_their_own_number() {
+        let mut device = MeshDevice::new();
+        device.my_node_info.my_node_num = 1;
+
+        let packet = protobufs::MeshPacket {
+            from: 2,
+            hop_start: 3,
+            hop_limit: 1,
+            rx_snr: 5.5,
+            ..Default::default()
+        };
+
+        device.record_node_packet(&packet);
+        device.add_user(UserPacket {
+            packet: packet.clone(),
+            data: protobufs::User {
+                long_name: "Relay".into(),
+                ..Default::default()
+            },
+        });
+        device.record_node_packet(&packet);
+
+        assert_eq!(device.nodes.len(), 1);
+
+        let node = &device.nodes[&2];
+        assert_eq!(node.node_num, 2);
+        assert_eq!(node.packet_count, 2);
+        assert_eq!(node.hops_away, Some(2));
+        assert!(node.first_seen.is_some());
+        assert_eq!(node.last_heard.as_ref().map(|l| l.snr), Some(5.5));
+        assert_eq!(
+            node.user.as_ref().map(|u| u.long_name.as_str()),
+            Some("Relay")
+        );
+    }
+
+    #[test]
+    fn merges_node_info_reported_by_radio() {
+        let mut device = MeshDevice::new();
+
+        device.record_node_packet(&protobufs::MeshPacket {
+            from: 2,
+            hop_start: 3,
+            hop_limit: 3,
+            ..Default::default()
+        });
+        let heard_at = device.nodes[&2].last_heard.as_ref().unwrap().timestamp;
+
+        // An older entry from the radio's node database doesn't replace it
+        device
+            .node_mut(2)
+            .update_from_node_info(protobufs::NodeInfo {
+                num: 2,
+                last_heard: heard_at - 60,
+                hops_away: Some(4),
+                via_mqtt: true,
+                ..Default::default()
+            });
+
+        let node = &device.nodes[&2];
+        assert_eq!(
+            node.last_heard.as_ref().map(|l| l.timestamp),
+            Some(heard_at)
+        );
+        assert_eq!(node.hops_away, Some(0));
+        assert!(!node.via_mqtt);
+
+        device
+            .node_mut(2)
+            .update_from_node_info(protobufs::NodeInfo {
+                num: 2,
+                last_heard: heard_at + 60,
+                hops_away: Some(4),
+                via_mqtt: true,
+                ..Default::default()
+            });
+
+        let node = &device.nodes[&2];
+        assert_eq!(
+            node.last_heard.as_ref().map(|l| l.timestamp),
+            Some(heard_at + 60)
+        );
+        assert_eq!(node.hops_away, Some(4));
+        assert!(node.via_mqtt);
+
+        // Firmware that doesn't report hops leaves the hop count unknown
+        device.record_node_packet(&protobufs::MeshPacket {
+            from: 2,
+            ..Default::default()
+        });
+        assert_eq!(device.nodes[&2].hops_away, None);
+    }
+}
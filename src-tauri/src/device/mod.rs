@@ -3,28 +3,43 @@
 use meshtastic::protobufs;
 use meshtastic::ts::specta::{self, Type};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 
 use self::helpers::{
-    convert_location_field_to_protos, generate_rand_id, get_current_time_u32,
-    normalize_location_field,
+    build_traceroute_hops, convert_location_field_to_protos, generate_rand_id,
+    get_current_time_u32, normalize_location_field,
 };
+use self::retention::TelemetryRetentionPolicy;
+use self::roster::NodeAnnotation;
 
 pub mod helpers;
+pub mod identity;
+pub mod retention;
+pub mod roster;
 pub mod state;
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Type)]
 #[serde(rename_all = "camelCase")]
 pub enum SerialDeviceStatus {
-    Restarting,   // unused
+    Restarting,   // device reported a reboot, connection will be re-established
     Disconnected, // no attempt or failure to connect
     Connecting,   // connection initialized, not yet configured
-    Reconnecting, // unused
+    Reconnecting, // connection lost, retrying with backoff
     Connected,    // successful serial connection and device configuration, UI notified
     Configuring,  // configuration in process
     Configured,   // configured but UI not yet notified
 }
 
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum MqttProxyStatus {
+    #[default]
+    Disabled, // radio isn't configured to use the client as its MQTT proxy
+    Connecting,   // proxy started, not yet connected to the broker
+    Connected,    // relaying messages between the radio and the broker
+    Disconnected, // lost connection to the broker, retrying
+}
+
 impl Default for SerialDeviceStatus {
     fn default() -> Self {
         SerialDeviceStatus::Disconnected
@@ -48,6 +63,12 @@ pub struct MeshNodeDeviceMetrics {
     // channel: u32,
 }
 
+impl MeshNodeDeviceMetrics {
+    pub fn metrics(&self) -> &protobufs::DeviceMetrics {
+        &self.metrics
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, Type)]
 #[serde(rename_all = "camelCase")]
 pub struct MeshNodeEnvironmentMetrics {
@@ -57,6 +78,38 @@ pub struct MeshNodeEnvironmentMetrics {
     // channel: u32,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct MeshNodeAirQualityMetrics {
+    metrics: protobufs::AirQualityMetrics,
+    timestamp: u32,
+    snr: f32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct MeshNodePowerMetrics {
+    metrics: protobufs::PowerMetrics,
+    timestamp: u32,
+    snr: f32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct MeshNodeLocalStats {
+    metrics: protobufs::LocalStats,
+    timestamp: u32,
+    snr: f32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct MeshNodeHealthMetrics {
+    metrics: protobufs::HealthMetrics,
+    timestamp: u32,
+    snr: f32,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, Type)]
 #[serde(rename_all = "camelCase")]
 pub struct MeshNodePositionMetrics {
@@ -80,9 +133,32 @@ pub struct MeshNode {
     pub node_num: u32,
     pub last_heard: Option<LastHeardMetadata>,
     pub user: Option<protobufs::User>,
-    pub device_metrics: Vec<MeshNodeDeviceMetrics>,
-    pub environment_metrics: Vec<MeshNodeEnvironmentMetrics>,
-    pub position_metrics: Vec<NormalizedPosition>,
+    pub device_metrics: VecDeque<MeshNodeDeviceMetrics>,
+    pub environment_metrics: VecDeque<MeshNodeEnvironmentMetrics>,
+    pub position_metrics: VecDeque<NormalizedPosition>,
+
+    // Defaulted so that nodes recorded before these were tracked still load
+    #[serde(default)]
+    pub air_quality_metrics: VecDeque<MeshNodeAirQualityMetrics>,
+    #[serde(default)]
+    pub power_metrics: VecDeque<MeshNodePowerMetrics>,
+    #[serde(default)]
+    pub local_stats: VecDeque<MeshNodeLocalStats>,
+    #[serde(default)]
+    pub health_metrics: VecDeque<MeshNodeHealthMetrics>,
+
+    /// Time the node was first seen in seconds since epoch
+    #[serde(default)]
+    pub first_seen: Option<u32>,
+    /// Number of hops the latest packet from the node took, if reported
+    #[serde(default)]
+    pub hops_away: Option<u32>,
+    /// Whether the latest packet from the node arrived over MQTT
+    #[serde(default)]
+    pub via_mqtt: bool,
+    /// Number of packets received from the node
+    #[serde(default)]
+    pub packet_count: u32,
 }
 
 impl MeshNode {
@@ -91,25 +167,47 @@ impl MeshNode {
             node_num,
             last_heard: None,
             user: None,
-            device_metrics: Vec::new(),
-            environment_metrics: Vec::new(),
-            position_metrics: Vec::new(),
+            device_metrics: VecDeque::new(),
+            environment_metrics: VecDeque::new(),
+            position_metrics: VecDeque::new(),
+            air_quality_metrics: VecDeque::new(),
+            power_metrics: VecDeque::new(),
+            local_stats: VecDeque::new(),
+            health_metrics: VecDeque::new(),
+            first_seen: None,
+            hops_away: None,
+            via_mqtt: false,
+            packet_count: 0,
         }
     }
 
     pub fn update_from_node_info(&mut self, node_info: protobufs::NodeInfo) {
-        self.last_heard = Some(LastHeardMetadata {
-            timestamp: get_current_time_u32(),
-            snr: node_info.snr,
-            channel: node_info.channel,
+        // The radio reports when it last heard the node, or zero if it never
+        // has, which may be older than a packet we've since received from it
+        let is_newer = self.last_heard.as_ref().map_or(true, |last_heard| {
+            last_heard.timestamp <= node_info.last_heard
         });
 
+        if node_info.last_heard != 0 && is_newer {
+            self.last_heard = Some(LastHeardMetadata {
+                timestamp: node_info.last_heard,
+                snr: node_info.snr,
+                channel: node_info.channel,
+            });
+
+            self.via_mqtt = node_info.via_mqtt;
+
+            if let Some(hops_away) = node_info.hops_away {
+                self.hops_away = Some(hops_away);
+            }
+        }
+
         if let Some(user) = node_info.user {
             self.user = Some(user);
         }
 
         if let Some(device_metrics) = node_info.device_metrics {
-            self.device_metrics.push(MeshNodeDeviceMetrics {
+            self.device_metrics.push_back(MeshNodeDeviceMetrics {
                 metrics: device_metrics,
                 timestamp: get_current_time_u32(),
                 snr: node_info.snr,
@@ -117,7 +215,10 @@ impl MeshNode {
         }
 
         if let Some(position) = node_info.position {
-            self.position_metrics.push(position.into());
+            self.position_metrics.push_back(NormalizedPosition {
+                received_at: get_current_time_u32(),
+                ..position.into()
+            });
         }
     }
 }
@@ -175,6 +276,8 @@ pub struct NormalizedPosition {
     pub sensor_id: u32,
     pub next_update: u32, // secs
     pub seq_number: u32,
+    #[serde(default)] // missing from history written before it was recorded
+    pub received_at: u32, // secs, when this device received the position
 }
 
 impl From<protobufs::Position> for NormalizedPosition {
@@ -204,6 +307,7 @@ impl From<protobufs::Position> for NormalizedPosition {
             sensor_id: position.sensor_id,
             next_update: position.next_update,
             seq_number: position.seq_number,
+            received_at: 0,
         }
     }
 }
@@ -249,6 +353,132 @@ pub struct WaypointPacket {
     pub data: NormalizedWaypoint,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct TracerouteHop {
+    /// The node number of this hop
+    pub node_num: u32,
+
+    /// SNR (dB) at which this hop heard the previous hop in the route.
+    /// `None` for the first hop, or if the hop did not report an SNR
+    pub snr: Option<f32>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct TracerouteResult {
+    /// The node the traceroute was sent to
+    pub destination: u32,
+
+    /// Time the traceroute reply was received in seconds since epoch
+    pub timestamp: u32,
+
+    /// Ordered hops from this device to the destination, inclusive
+    pub route: Vec<TracerouteHop>,
+
+    /// Ordered hops from the destination back to this device, inclusive.
+    /// Empty if the destination firmware does not report the return route
+    pub route_back: Vec<TracerouteHop>,
+}
+
+impl TracerouteResult {
+    pub fn from_route_discovery(
+        origin: u32,
+        destination: u32,
+        route_discovery: protobufs::RouteDiscovery,
+    ) -> Self {
+        let route = build_traceroute_hops(
+            origin,
+            &route_discovery.route,
+            destination,
+            &route_discovery.snr_towards,
+        );
+
+        let route_back =
+            if route_discovery.route_back.is_empty() && route_discovery.snr_back.is_empty() {
+                vec![]
+            } else {
+                build_traceroute_hops(
+                    destination,
+                    &route_discovery.route_back,
+                    origin,
+                    &route_discovery.snr_back,
+                )
+            };
+
+        Self {
+            destination,
+            timestamp: get_current_time_u32(),
+            route,
+            route_back,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct RangeTestRecord {
+    /// Time the packet was received in seconds since epoch
+    pub timestamp: u32,
+
+    /// The node number of the node running the range test
+    pub from: u32,
+
+    /// Sequence number sent by the range test module
+    pub sequence: u32,
+
+    pub snr: f32,
+    pub rssi: i32,
+
+    /// Number of hops the packet took, if the sender reported its hop start
+    pub hops_away: Option<u32>,
+
+    /// Last known position of the sender when the packet was received
+    pub sender_position: Option<NormalizedPosition>,
+
+    /// Last known position of this device when the packet was received
+    pub receiver_position: Option<NormalizedPosition>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct StoreForwardServer {
+    /// The node number of the store-and-forward router
+    pub node_num: u32,
+
+    /// Time the router was last heard from in seconds since epoch
+    pub last_heard: u32,
+
+    /// Time of the last heartbeat in seconds since epoch, if the router sends heartbeats
+    pub last_heartbeat: Option<u32>,
+
+    /// Seconds between heartbeats, as advertised by the router
+    pub heartbeat_period: Option<u32>,
+
+    /// Whether the router advertised itself as a secondary router
+    pub secondary: bool,
+
+    /// The latest statistics reported by the router
+    pub stats: Option<protobufs::store_and_forward::Statistics>,
+
+    /// Number of messages the router announced it would replay for the last history request
+    pub announced_history_messages: u32,
+}
+
+impl StoreForwardServer {
+    pub fn new(node_num: u32) -> Self {
+        Self {
+            node_num,
+            last_heard: get_current_time_u32(),
+            last_heartbeat: None,
+            heartbeat_period: None,
+            secondary: false,
+            stats: None,
+            announced_history_messages: 0,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, Type)]
 #[serde(rename_all = "camelCase")]
 pub struct NormalizedWaypoint {
@@ -333,6 +563,16 @@ pub struct ChannelMessageWithState {
     pub state: ChannelMessageState,
 }
 
+impl ChannelMessageWithState {
+    /// The id of the mesh packet that carried this message
+    pub fn packet_id(&self) -> u32 {
+        match &self.payload {
+            ChannelMessagePayload::Text(t) => t.packet.id,
+            ChannelMessagePayload::Waypoint(w) => w.packet.id,
+        }
+    }
+}
+
 // TODO can't deserialize `SerialConnection`
 #[derive(Clone, Debug, Default, Serialize, Type)]
 #[serde(rename_all = "camelCase")]
@@ -347,9 +587,17 @@ pub struct MeshDevice {
     pub nodes: HashMap<u32, MeshNode>, // network devices this device has communicated with
     pub region_unset: bool,         // flag for whether device has an unset LoRa region
     pub device_metrics: protobufs::DeviceMetrics, // information about functioning of device (e.g. battery level)
+    pub local_stats: Option<protobufs::LocalStats>, // latest traffic counters reported by the device itself
     pub waypoints: HashMap<u32, NormalizedWaypoint>, // updatable GPS positions managed by this device
     pub neighbors: HashMap<u32, NeighborInfoPacket>, //updated packets from each node containing their neighbors
+    pub traceroutes: HashMap<u32, TracerouteResult>, // latest traceroute result to each destination node
+    pub store_forward_servers: HashMap<u32, StoreForwardServer>, // store-and-forward routers heard on the mesh
+    #[serde(skip)] // can grow large, so it's queried rather than sent with every device update
+    pub range_test_records: VecDeque<RangeTestRecord>, // range test packets received during coverage surveys
     pub config_in_progress: bool, // flag for whether the user has started a configuration transaction
+    pub mqtt_proxy_status: MqttProxyStatus, // state of the broker connection when proxying the radio's MQTT traffic
+    pub telemetry_retention: TelemetryRetentionPolicy, // how much telemetry history is kept for each node
+    pub roster: HashMap<u32, NodeAnnotation>, // local annotations of nodes, kept apart from what the nodes report
 }
 
 impl MeshDevice {
@@ -0,0 +1,386 @@
+use std::collections::VecDeque;
+
+use meshtastic::protobufs;
+use meshtastic::ts::specta::{self, Type};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    MeshNode, MeshNodeAirQualityMetrics, MeshNodeDeviceMetrics, MeshNodeEnvironmentMetrics,
+    MeshNodeHealthMetrics, MeshNodeLocalStats, MeshNodePowerMetrics, NormalizedPosition,
+};
+
+pub const DEFAULT_MAX_TELEMETRY_SAMPLES: u32 = 500;
+pub const DEFAULT_FULL_RESOLUTION_SECS: u32 = 60 * 60;
+pub const DEFAULT_DOWNSAMPLE_INTERVAL_SECS: u32 = 5 * 60;
+
+/// Controls how much telemetry history is kept for each node
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct TelemetryRetentionPolicy {
+    /// Number of samples kept per series before the oldest are dropped
+    pub max_samples: u32,
+
+    /// Samples received within this many seconds are kept at full resolution
+    pub full_resolution_secs: u32,
+
+    /// Older samples are merged into one sample per interval, zero disables downsampling
+    pub downsample_interval_secs: u32,
+}
+
+impl Default for TelemetryRetentionPolicy {
+    fn default() -> Self {
+        Self {
+            max_samples: DEFAULT_MAX_TELEMETRY_SAMPLES,
+            full_resolution_secs: DEFAULT_FULL_RESOLUTION_SECS,
+            downsample_interval_secs: DEFAULT_DOWNSAMPLE_INTERVAL_SECS,
+        }
+    }
+}
+
+/// A sample of a node's telemetry history
+pub trait TelemetrySample: Sized {
+    /// Time the sample was taken in seconds since epoch
+    fn sampled_at(&self) -> u32;
+
+    /// Combines the samples of a downsampling interval, oldest first
+    fn merge(bucket: Vec<Self>) -> Self;
+}
+
+macro_rules! impl_averaged_sample {
+    ($($sample:ty),* $(,)?) => {
+        $(
+            impl TelemetrySample for $sample {
+                fn sampled_at(&self) -> u32 {
+                    self.timestamp
+                }
+
+                fn merge(bucket: Vec<Self>) -> Self {
+                    let snr = bucket.iter().map(|s| s.snr).sum::<f32>() / bucket.len() as f32;
+                    let metrics = AveragedMetrics::average(
+                        &bucket.iter().map(|s| &s.metrics).collect::<Vec<_>>(),
+                    );
+                    let latest = bucket.into_iter().last().expect("Bucket is never empty");
+
+                    Self {
+                        metrics,
+                        timestamp: latest.timestamp,
+                        snr,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_averaged_sample!(
+    MeshNodeDeviceMetrics,
+    MeshNodeEnvironmentMetrics,
+    MeshNodeAirQualityMetrics,
+    MeshNodePowerMetrics,
+    MeshNodeLocalStats,
+    MeshNodeHealthMetrics,
+);
+
+impl TelemetrySample for NormalizedPosition {
+    // The sender's clock may be unset or wrong, so positions are ordered by
+    // when they were received like other telemetry
+    fn sampled_at(&self) -> u32 {
+        match (self.received_at, self.timestamp) {
+            (0, 0) => self.time,
+            (0, timestamp) => timestamp,
+            (received_at, _) => received_at,
+        }
+    }
+
+    // Averaging the positions of a moving node would place it where it never
+    // was, so the latest position of the interval is kept instead
+    fn merge(bucket: Vec<Self>) -> Self {
+        bucket.into_iter().last().expect("Bucket is never empty")
+    }
+}
+
+/// Metrics whose readings are averaged when samples are downsampled
+trait AveragedMetrics: Clone {
+    /// Averages the readings of the passed metrics, oldest first. Counters,
+    /// identifiers and other fields are taken from the latest metrics.
+    fn average(metrics: &[&Self]) -> Self;
+}
+
+macro_rules! impl_averaged_metrics {
+    ($($metrics:ty { $($field:ident: $mean:ident),* $(,)? }),* $(,)?) => {
+        $(
+            impl AveragedMetrics for $metrics {
+                fn average(metrics: &[&Self]) -> Self {
+                    let latest = *metrics.last().expect("Bucket is never empty");
+
+                    Self {
+                        $($field: $mean(metrics.iter().map(|m| m.$field)),)*
+                        ..latest.clone()
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_averaged_metrics!(
+    protobufs::DeviceMetrics {
+        battery_level: mean_u32,
+        voltage: mean_f32,
+        channel_utilization: mean_f32,
+        air_util_tx: mean_f32,
+    },
+    protobufs::EnvironmentMetrics {
+        temperature: mean_f32,
+        relative_humidity: mean_f32,
+        barometric_pressure: mean_f32,
+        gas_resistance: mean_f32,
+        voltage: mean_f32,
+        current: mean_f32,
+    },
+    protobufs::AirQualityMetrics {
+        pm10_standard: mean_u32,
+        pm25_standard: mean_u32,
+        pm100_standard: mean_u32,
+        pm10_environmental: mean_u32,
+        pm25_environmental: mean_u32,
+        pm100_environmental: mean_u32,
+    },
+    protobufs::PowerMetrics {
+        ch1_voltage: mean_f32,
+        ch1_current: mean_f32,
+        ch2_voltage: mean_f32,
+        ch2_current: mean_f32,
+        ch3_voltage: mean_f32,
+        ch3_current: mean_f32,
+    },
+    protobufs::LocalStats {
+        channel_utilization: mean_f32,
+        air_util_tx: mean_f32,
+    },
+    protobufs::HealthMetrics {
+        heart_bpm: mean_u32,
+        sp_o2: mean_u32,
+        temperature: mean_f32,
+    },
+);
+
+/// Mean of the readings that were reported. Zero means that a reading wasn't
+/// reported, and is kept if no readings were.
+fn mean_f32(values: impl Iterator<Item = f32>) -> f32 {
+    let (sum, count) = values
+        .filter(|value| *value != 0.0)
+        .fold((0.0, 0), |(sum, count), value| (sum + value, count + 1));
+
+    match count {
+        0 => 0.0,
+        count => sum / count as f32,
+    }
+}
+
+fn mean_u32(values: impl Iterator<Item = u32>) -> u32 {
+    let (sum, count) = values
+        .filter(|value| *value != 0)
+        .fold((0u64, 0u64), |(sum, count), value| {
+            (sum + u64::from(value), count + 1)
+        });
+
+    match count {
+        0 => 0,
+        count => (sum as f64 / count as f64).round() as u32,
+    }
+}
+
+/// Downsamples the samples that fell out of the full resolution window and
+/// drops the oldest samples once the series is full. Intervals are only
+/// merged once they are entirely outside of the window, so that every
+/// interval is merged exactly once.
+pub fn retain_samples<T: TelemetrySample>(
+    samples: &mut VecDeque<T>,
+    policy: &TelemetryRetentionPolicy,
+    now: u32,
+) {
+    let interval = u64::from(policy.downsample_interval_secs);
+    let cutoff = u64::from(now.saturating_sub(policy.full_resolution_secs));
+
+    if interval > 0 {
+        let mut retained = VecDeque::with_capacity(samples.len());
+        let mut bucket: Vec<T> = Vec::new();
+        let mut bucket_key = None;
+
+        for sample in samples.drain(..) {
+            let key = u64::from(sample.sampled_at()) / interval;
+
+            if (key + 1) * interval > cutoff {
+                flush_bucket(&mut bucket, &mut retained);
+                bucket_key = None;
+                retained.push_back(sample);
+                continue;
+            }
+
+            if bucket_key != Some(key) {
+                flush_bucket(&mut bucket, &mut retained);
+                bucket_key = Some(key);
+            }
+
+            bucket.push(sample);
+        }
+
+        flush_bucket(&mut bucket, &mut retained);
+        *samples = retained;
+    }
+
+    while samples.len() > policy.max_samples as usize {
+        samples.pop_front();
+    }
+}
+
+fn flush_bucket<T: TelemetrySample>(bucket: &mut Vec<T>, retained: &mut VecDeque<T>) {
+    match bucket.len() {
+        0 => {}
+        1 => retained.extend(bucket.drain(..)),
+        _ => retained.push_back(T::merge(std::mem::take(bucket))),
+    }
+}
+
+/// The telemetry history of a node within a time range
+#[derive(Clone, Debug, Default, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeTelemetryHistory {
+    pub node_num: u32,
+    pub device_metrics: Vec<MeshNodeDeviceMetrics>,
+    pub environment_metrics: Vec<MeshNodeEnvironmentMetrics>,
+    pub position_metrics: Vec<NormalizedPosition>,
+    pub air_quality_metrics: Vec<MeshNodeAirQualityMetrics>,
+    pub power_metrics: Vec<MeshNodePowerMetrics>,
+    pub local_stats: Vec<MeshNodeLocalStats>,
+    pub health_metrics: Vec<MeshNodeHealthMetrics>,
+}
+
+fn samples_between<T: TelemetrySample + Clone>(
+    samples: &VecDeque<T>,
+    start: Option<u32>,
+    end: Option<u32>,
+) -> Vec<T> {
+    samples
+        .iter()
+        .filter(|sample| {
+            let sampled_at = sample.sampled_at();
+            !start.is_some_and(|start| sampled_at < start)
+                && !end.is_some_and(|end| sampled_at > end)
+        })
+        .cloned()
+        .collect()
+}
+
+impl MeshNode {
+    pub fn apply_retention(&mut self, policy: &TelemetryRetentionPolicy, now: u32) {
+        retain_samples(&mut self.device_metrics, policy, now);
+        retain_samples(&mut self.environment_metrics, policy, now);
+        retain_samples(&mut self.position_metrics, policy, now);
+        retain_samples(&mut self.air_quality_metrics, policy, now);
+        retain_samples(&mut self.power_metrics, policy, now);
+        retain_samples(&mut self.local_stats, policy, now);
+        retain_samples(&mut self.health_metrics, policy, now);
+    }
+
+    /// Samples taken between the passed times in seconds since epoch, inclusive
+    pub fn telemetry_history(&self, start: Option<u32>, end: Option<u32>) -> NodeTelemetryHistory {
+        NodeTelemetryHistory {
+            node_num: self.node_num,
+            device_metrics: samples_between(&self.device_metrics, start, end),
+            environment_metrics: samples_between(&self.environment_metrics, start, end),
+            position_metrics: samples_between(&self.position_metrics, start, end),
+            air_quality_metrics: samples_between(&self.air_quality_metrics, start, end),
+            power_metrics: samples_between(&self.power_metrics, start, end),
+            local_stats: samples_between(&self.local_stats, start, end),
+            health_metrics: samples_between(&self.health_metrics, start, end),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use meshtastic::protobufs;
+
+    use super::*;
+
+    fn device_metrics(timestamp: u32, battery_level: u32, snr: f32) -> MeshNodeDeviceMetrics {
+        MeshNodeDeviceMetrics {
+            metrics: protobufs::DeviceMetrics {
+                battery_level,
+                voltage: 4.0,
+                uptime_seconds: timestamp,
+                ..Default::default()
+            },
+            timestamp,
+            snr,
+        }
+    }
+
+    #[test]
+    fn downsamples_intervals_outside_of_full_resolution_window() {
+        let policy = TelemetryRetentionPolicy {
+            max_samples: 100,
+            full_resolution_secs: 600,
+            downsample_interval_secs: 300,
+        };
+
+        let mut samples: VecDeque<MeshNodeDeviceMetrics> = VecDeque::from(vec![
+            device_metrics(0, 80, 2.0),
+            device_metrics(100, 90, 4.0),
+            device_metrics(200, 95, 6.0),
+            device_metrics(300, 50, 0.0),
+            device_metrics(900, 40, 0.0),
+            device_metrics(1000, 30, 0.0),
+        ]);
+
+        retain_samples(&mut samples, &policy, 1_200);
+
+        let timestamps: Vec<u32> = samples.iter().map(|s| s.timestamp).collect();
+        assert_eq!(timestamps, vec![200, 300, 900, 1000]);
+
+        assert_eq!(samples[0].metrics.battery_level, 88);
+        assert_eq!(samples[0].metrics.voltage, 4.0);
+        assert_eq!(samples[0].metrics.uptime_seconds, 200);
+        assert_eq!(samples[0].snr, 4.0);
+
+        // Merged intervals aren't merged again
+        retain_samples(&mut samples, &policy, 1_200);
+        assert_eq!(samples.len(), 4);
+        assert_eq!(samples[0].metrics.battery_level, 88);
+    }
+
+    #[test]
+    fn samples_positions_when_received() {
+        let position = NormalizedPosition {
+            time: 100,
+            timestamp: 200,
+            ..Default::default()
+        };
+        assert_eq!(position.sampled_at(), 200);
+
+        let received = NormalizedPosition {
+            received_at: 300,
+            ..position
+        };
+        assert_eq!(received.sampled_at(), 300);
+    }
+
+    #[test]
+    fn drops_oldest_samples_once_full() {
+        let policy = TelemetryRetentionPolicy {
+            max_samples: 2,
+            downsample_interval_secs: 0,
+            ..Default::default()
+        };
+
+        let mut samples: VecDeque<MeshNodeDeviceMetrics> =
+            (1..=4).map(|t| device_metrics(t, 100, 0.0)).collect();
+
+        retain_samples(&mut samples, &policy, 4);
+
+        let timestamps: Vec<u32> = samples.iter().map(|s| s.timestamp).collect();
+        assert_eq!(timestamps, vec![3, 4]);
+    }
+}
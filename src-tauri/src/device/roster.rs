@@ -0,0 +1,61 @@
+use log::debug;
+use meshtastic::ts::specta::{self, Type};
+use serde::{Deserialize, Serialize};
+
+use super::MeshDevice;
+
+/// Local annotations of a node, kept apart from the user info the node
+/// reports so that they survive the node renaming itself
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeAnnotation {
+    pub nickname: Option<String>,
+    /// Unit or team the node is assigned to
+    pub unit: Option<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RosterEntry {
+    pub node_num: u32,
+    pub annotation: NodeAnnotation,
+}
+
+/// Parses a node number, given either in decimal or as a node id such as `!a1b2c3d4`
+pub fn parse_node_num(value: &str) -> Option<u32> {
+    let value = value.trim();
+
+    match value.strip_prefix('!') {
+        Some(node_id) => u32::from_str_radix(node_id, 16).ok(),
+        None => value.parse().ok(),
+    }
+}
+
+impl MeshDevice {
+    /// Annotates nodes with the entries of an imported roster, replacing
+    /// existing annotations of the same nodes. Nodes don't need to have
+    /// been heard yet to be annotated.
+    pub fn import_roster(&mut self, entries: Vec<RosterEntry>) -> usize {
+        debug!("Importing {} roster entries", entries.len());
+
+        let entry_count = entries.len();
+
+        for entry in entries {
+            self.roster.insert(entry.node_num, entry.annotation);
+        }
+
+        entry_count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_decimal_numbers_and_node_ids() {
+        assert_eq!(parse_node_num("305419896"), Some(0x12345678));
+        assert_eq!(parse_node_num(" !12345678 "), Some(0x12345678));
+        assert_eq!(parse_node_num("!xyz"), None);
+    }
+}
@@ -4,13 +4,19 @@ use log::{debug, trace};
 use meshtastic::protobufs;
 
 use super::helpers::get_current_time_u32;
+use super::retention::TelemetryRetentionPolicy;
 use super::{
-    ChannelMessagePayload, ChannelMessageWithState, MeshChannel, MeshDevice, MeshNode,
-    MeshNodeDeviceMetrics, MeshNodeEnvironmentMetrics, NeighborInfoPacket, NormalizedWaypoint,
-    PositionPacket, SerialDeviceStatus, TelemetryPacket, TextPacket, UserPacket, WaypointPacket,
+    ChannelMessagePayload, ChannelMessageWithState, MeshChannel, MeshDevice,
+    MeshNodeAirQualityMetrics, MeshNodeDeviceMetrics, MeshNodeEnvironmentMetrics,
+    MeshNodeHealthMetrics, MeshNodeLocalStats, MeshNodePowerMetrics, NeighborInfoPacket,
+    NormalizedPosition, NormalizedWaypoint, PositionPacket, RangeTestRecord, SerialDeviceStatus,
+    StoreForwardServer, TelemetryPacket, TextPacket, TracerouteResult, UserPacket, WaypointPacket,
 };
 
-use crate::device::{ChannelMessageState, LastHeardMetadata};
+use crate::device::ChannelMessageState;
+
+/// Number of range test records kept before the oldest are dropped
+const MAX_RANGE_TEST_RECORDS: usize = 10_000;
 
 impl MeshDevice {
     pub fn set_ready(&mut self, ready: bool) {
@@ -132,96 +138,137 @@ impl MeshDevice {
     }
 
     pub fn set_device_metrics(&mut self, metrics: TelemetryPacket) {
-        let origin_node = self.nodes.get_mut(&metrics.packet.from);
-
-        if let Some(node) = origin_node {
-            if let Some(variant) = metrics.data.variant {
-                match variant {
-                    protobufs::telemetry::Variant::DeviceMetrics(device_metrics) => {
-                        debug!("Adding device metrics to node {:?}", metrics.packet.from);
-                        trace!("{:?}", device_metrics);
-
-                        self.device_metrics.battery_level = device_metrics.battery_level;
-                        self.device_metrics.voltage = device_metrics.voltage;
-                        self.device_metrics.air_util_tx = device_metrics.air_util_tx;
-                        self.device_metrics.channel_utilization =
-                            device_metrics.channel_utilization;
-
-                        node.device_metrics.push(MeshNodeDeviceMetrics {
-                            metrics: protobufs::DeviceMetrics { ..device_metrics },
-                            timestamp: get_current_time_u32(),
-                            snr: metrics.packet.rx_snr,
-                        });
-                    }
-                    protobufs::telemetry::Variant::EnvironmentMetrics(environment_metrics) => {
-                        debug!(
-                            "Adding environment metrics to node {:?}",
-                            metrics.packet.from
-                        );
-                        trace!("{:?}", environment_metrics);
-
-                        node.environment_metrics.push(MeshNodeEnvironmentMetrics {
-                            metrics: protobufs::EnvironmentMetrics {
-                                ..environment_metrics
-                            },
-                            timestamp: get_current_time_u32(),
-                            snr: metrics.packet.rx_snr,
-                        });
-                    }
-                    protobufs::telemetry::Variant::AirQualityMetrics(air_quality_metrics) => {
-                        debug!("Received air quality metrics, not handling");
-                        trace!("{:?}", air_quality_metrics);
-                    }
-                    protobufs::telemetry::Variant::PowerMetrics(power_metrics) => {
-                        debug!("Received power metrics, not handling");
-                        trace!("{:?}", power_metrics);
-                    }
-                    protobufs::telemetry::Variant::LocalStats(local_stats) => {
-                        debug!("Received local stats, not handling");
-                        trace!("{:?}", local_stats);
-                    }
-                    protobufs::telemetry::Variant::HealthMetrics(health_metrics) => {
-                        debug!("Received health metrics, not handling");
-                        trace!("{:?}", health_metrics);
-                    }
+        let TelemetryPacket { packet, data } = metrics;
+
+        let Some(variant) = data.variant else {
+            self.node_mut(packet.from);
+            return;
+        };
+
+        // Metrics and local stats from our own radio are also shown as device metrics and counters
+        if packet.from == self.my_node_info.my_node_num {
+            match &variant {
+                protobufs::telemetry::Variant::DeviceMetrics(device_metrics) => {
+                    self.device_metrics.battery_level = device_metrics.battery_level;
+                    self.device_metrics.voltage = device_metrics.voltage;
+                    self.device_metrics.air_util_tx = device_metrics.air_util_tx;
+                    self.device_metrics.channel_utilization = device_metrics.channel_utilization;
+                }
+                protobufs::telemetry::Variant::LocalStats(local_stats) => {
+                    self.local_stats = Some(local_stats.clone());
                 }
+                _ => {}
             }
-        } else {
-            let new_node = MeshNode {
-                node_num: metrics.packet.from,
-                last_heard: Some(LastHeardMetadata {
-                    timestamp: get_current_time_u32(),
-                    snr: metrics.packet.rx_snr,
-                    channel: metrics.packet.channel,
-                }),
-                user: None,
-                device_metrics: vec![],
-                environment_metrics: vec![],
-                position_metrics: vec![],
-            };
+        }
 
-            debug!(
-                "Inserting new node with id {} from metrics",
-                metrics.packet.from,
-            );
-            trace!("{:?}", new_node);
+        let node = self.node_mut(packet.from);
+        let timestamp = get_current_time_u32();
+        let snr = packet.rx_snr;
+
+        match variant {
+            protobufs::telemetry::Variant::DeviceMetrics(device_metrics) => {
+                debug!("Adding device metrics to node {:?}", packet.from);
+                trace!("{:?}", device_metrics);
 
-            self.nodes.insert(metrics.packet.from, new_node);
+                node.device_metrics.push_back(MeshNodeDeviceMetrics {
+                    metrics: device_metrics,
+                    timestamp,
+                    snr,
+                });
+            }
+            protobufs::telemetry::Variant::EnvironmentMetrics(environment_metrics) => {
+                debug!("Adding environment metrics to node {:?}", packet.from);
+                trace!("{:?}", environment_metrics);
+
+                node.environment_metrics
+                    .push_back(MeshNodeEnvironmentMetrics {
+                        metrics: environment_metrics,
+                        timestamp,
+                        snr,
+                    });
+            }
+            protobufs::telemetry::Variant::AirQualityMetrics(air_quality_metrics) => {
+                debug!("Adding air quality metrics to node {:?}", packet.from);
+                trace!("{:?}", air_quality_metrics);
+
+                node.air_quality_metrics
+                    .push_back(MeshNodeAirQualityMetrics {
+                        metrics: air_quality_metrics,
+                        timestamp,
+                        snr,
+                    });
+            }
+            protobufs::telemetry::Variant::PowerMetrics(power_metrics) => {
+                debug!("Adding power metrics to node {:?}", packet.from);
+                trace!("{:?}", power_metrics);
+
+                node.power_metrics.push_back(MeshNodePowerMetrics {
+                    metrics: power_metrics,
+                    timestamp,
+                    snr,
+                });
+            }
+            protobufs::telemetry::Variant::LocalStats(local_stats) => {
+                debug!("Adding local stats to node {:?}", packet.from);
+                trace!("{:?}", local_stats);
+
+                node.local_stats.push_back(MeshNodeLocalStats {
+                    metrics: local_stats,
+                    timestamp,
+                    snr,
+                });
+            }
+            protobufs::telemetry::Variant::HealthMetrics(health_metrics) => {
+                debug!("Adding health metrics to node {:?}", packet.from);
+                trace!("{:?}", health_metrics);
+
+                node.health_metrics.push_back(MeshNodeHealthMetrics {
+                    metrics: health_metrics,
+                    timestamp,
+                    snr,
+                });
+            }
         }
+
+        self.retain_telemetry(packet.from);
     }
 
-    pub fn add_channel(&mut self, channel: MeshChannel) {
+    /// Applies a new retention policy to the telemetry history of every node
+    pub fn set_telemetry_retention(&mut self, policy: TelemetryRetentionPolicy) {
+        debug!("Setting telemetry retention policy to {:?}", policy);
+
+        self.telemetry_retention = policy;
+
+        let now = get_current_time_u32();
+        for node in self.nodes.values_mut() {
+            node.apply_retention(&policy, now);
+        }
+    }
+
+    fn retain_telemetry(&mut self, node_num: u32) {
+        if let Some(node) = self.nodes.get_mut(&node_num) {
+            node.apply_retention(&self.telemetry_retention, get_current_time_u32());
+        }
+    }
+
+    pub fn add_channel(&mut self, mut channel: MeshChannel) {
         debug!("Adding device channel at index {}", channel.config.index);
         trace!("{:?}", channel);
 
-        self.channels.insert(
-            channel
-                .config
-                .index
-                .try_into()
-                .expect("Channel id out of u32 range"),
-            channel,
-        );
+        let channel_id: u32 = channel
+            .config
+            .index
+            .try_into()
+            .expect("Channel id out of u32 range");
+
+        // Keep messages that were received or rehydrated before the channel config
+        if let Some(existing) = self.channels.remove(&channel_id) {
+            let mut messages = existing.messages;
+            messages.append(&mut channel.messages);
+            channel.messages = messages;
+        }
+
+        self.channels.insert(channel_id, channel);
     }
 
     pub fn add_waypoint(&mut self, waypoint: NormalizedWaypoint) {
@@ -230,75 +277,39 @@ impl MeshDevice {
     }
 
     pub fn add_node_info(&mut self, node_info: protobufs::NodeInfo) {
-        let found_node = self.nodes.get_mut(&node_info.num);
+        let node_num = node_info.num;
+        debug!("Updating node with id {} from info", node_num);
+        trace!("{:?}", node_info);
 
-        if let Some(node) = found_node {
-            debug!("Updating existing node with id {} from info", node_info.num,);
-            trace!("{:?}", node_info);
-
-            node.update_from_node_info(node_info);
-        } else {
-            debug!("Inserting new node with id {} from info", node_info.num,);
-            trace!("{:?}", node_info);
-
-            let mut new_node = MeshNode::new(node_info.num);
-            new_node.update_from_node_info(node_info.clone());
-
-            self.nodes.insert(node_info.num, new_node);
-        }
+        self.node_mut(node_num).update_from_node_info(node_info);
+        self.retain_telemetry(node_num);
     }
 
     pub fn add_user(&mut self, user: UserPacket) {
-        let found_node = self.nodes.get_mut(&user.packet.from);
-
-        if let Some(node) = found_node {
-            trace!(
-                "Updating user of existing node {:?}: {:?}",
-                user.packet.from,
-                user.data
-            );
-            node.user = Some(user.data);
-        } else {
-            trace!(
-                "Adding user to new node {:?}: {:?}",
-                user.packet.from,
-                user.data
-            );
-
-            let mut new_node = MeshNode::new(self.my_node_info.my_node_num);
-            new_node.user = Some(user.data);
-            new_node.last_heard = Some(LastHeardMetadata {
-                timestamp: get_current_time_u32(),
-                snr: user.packet.rx_snr,
-                channel: user.packet.channel,
-            });
+        trace!(
+            "Updating user of node {:?}: {:?}",
+            user.packet.from,
+            user.data
+        );
 
-            self.nodes.insert(user.packet.from, new_node);
-        }
+        self.node_mut(user.packet.from).user = Some(user.data);
     }
 
     pub fn add_position(&mut self, position: PositionPacket) {
-        let found_node = self.nodes.get_mut(&position.packet.from);
-
-        if let Some(node) = found_node {
-            trace!(
-                "Updating position of existing node {:?}: {:?}",
-                position.packet.from,
-                position.data
-            );
-            node.position_metrics.push(position.data.into());
-        } else {
-            trace!(
-                "Adding position to new node {:?}: {:?}",
-                position.packet.from,
-                position.data
-            );
+        trace!(
+            "Updating position of node {:?}: {:?}",
+            position.packet.from,
+            position.data
+        );
 
-            let mut new_node = MeshNode::new(self.my_node_info.my_node_num);
-            new_node.position_metrics.push(position.data.into());
+        self.node_mut(position.packet.from)
+            .position_metrics
+            .push_back(NormalizedPosition {
+                received_at: get_current_time_u32(),
+                ..position.data.into()
+            });
 
-            self.nodes.insert(position.packet.from, new_node);
-        }
+        self.retain_telemetry(position.packet.from);
     }
 
     pub fn add_neighborinfo(&mut self, neighborinfo: NeighborInfoPacket) {
@@ -321,6 +332,17 @@ impl MeshDevice {
         }
     }
 
+    pub fn add_traceroute(&mut self, traceroute: TracerouteResult) {
+        debug!(
+            "Adding traceroute to node {} with {} hops",
+            traceroute.destination,
+            traceroute.route.len().saturating_sub(1)
+        );
+        trace!("{:?}", traceroute);
+
+        self.traceroutes.insert(traceroute.destination, traceroute);
+    }
+
     pub fn add_text_message(&mut self, message: TextPacket) {
         let channel = self.channels.get_mut(&message.packet.channel);
 
@@ -339,6 +361,96 @@ impl MeshDevice {
         }
     }
 
+    /// Adds a text message replayed by a store-and-forward router, ordered by
+    /// the time it was originally received. Returns `false` if the message was
+    /// already received, either directly or from an earlier replay.
+    pub fn merge_text_message(&mut self, message: TextPacket) -> bool {
+        let channel = match self.channels.get_mut(&message.packet.channel) {
+            Some(ch) => ch,
+            None => return false,
+        };
+
+        let message_id = message.packet.id;
+
+        if channel_contains_message(channel, message_id) {
+            trace!("Skipping duplicate replayed message {}", message_id);
+            return false;
+        }
+
+        debug!(
+            "Merging replayed text message into channel {:?}: {:?}",
+            message.packet.channel, message.data
+        );
+
+        let rx_time = message.packet.rx_time;
+        let index = channel
+            .messages
+            .iter()
+            .position(|m| message_rx_time(m) > rx_time)
+            .unwrap_or(channel.messages.len());
+
+        channel.messages.insert(
+            index,
+            ChannelMessageWithState {
+                payload: ChannelMessagePayload::Text(message),
+                state: ChannelMessageState::Acknowledged,
+            },
+        );
+
+        true
+    }
+
+    /// Records a range test packet along with the last known positions of its
+    /// sender and of this device
+    pub fn add_range_test_record(&mut self, packet: &protobufs::MeshPacket, sequence: u32) {
+        let last_position = |node_num: u32| {
+            self.nodes
+                .get(&node_num)
+                .and_then(|node| node.position_metrics.back().cloned())
+        };
+
+        let record = RangeTestRecord {
+            timestamp: if packet.rx_time != 0 {
+                packet.rx_time
+            } else {
+                get_current_time_u32()
+            },
+            from: packet.from,
+            sequence,
+            snr: packet.rx_snr,
+            rssi: packet.rx_rssi,
+            hops_away: (packet.hop_start != 0)
+                .then(|| packet.hop_start.saturating_sub(packet.hop_limit)),
+            sender_position: last_position(packet.from),
+            receiver_position: last_position(self.my_node_info.my_node_num),
+        };
+
+        debug!(
+            "Adding range test record {} from node {}",
+            sequence, packet.from
+        );
+        trace!("{:?}", record);
+
+        if self.range_test_records.len() >= MAX_RANGE_TEST_RECORDS {
+            self.range_test_records.pop_front();
+        }
+
+        self.range_test_records.push_back(record);
+    }
+
+    pub fn add_store_forward_server(&mut self, node_num: u32) -> &mut StoreForwardServer {
+        let server = self
+            .store_forward_servers
+            .entry(node_num)
+            .or_insert_with(|| {
+                debug!("Discovered store-and-forward router {}", node_num);
+                StoreForwardServer::new(node_num)
+            });
+
+        server.last_heard = get_current_time_u32();
+        server
+    }
+
     pub fn add_waypoint_message(&mut self, message: WaypointPacket) {
         let channel = self.channels.get_mut(&message.packet.channel);
 
@@ -382,3 +494,108 @@ impl MeshDevice {
         }
     }
 }
+
+fn channel_contains_message(channel: &MeshChannel, message_id: u32) -> bool {
+    channel.messages.iter().any(|m| m.packet_id() == message_id)
+}
+
+fn message_rx_time(message: &ChannelMessageWithState) -> u32 {
+    match &message.payload {
+        ChannelMessagePayload::Text(t) => t.packet.rx_time,
+        ChannelMessagePayload::Waypoint(w) => w.packet.rx_time,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_packet(id: u32, rx_time: u32) -> TextPacket {
+        TextPacket {
+            packet: protobufs::MeshPacket {
+                id,
+                rx_time,
+                ..Default::default()
+            },
+            data: format!("message {}", id),
+        }
+    }
+
+    #[test]
+    fn merges_replayed_messages_in_order_without_duplicates() {
+        let mut device = MeshDevice::new();
+        device.add_channel(MeshChannel::default());
+
+        device.add_text_message(text_packet(1, 100));
+        device.add_text_message(text_packet(3, 300));
+
+        assert!(device.merge_text_message(text_packet(2, 200)));
+        assert!(!device.merge_text_message(text_packet(3, 300)));
+
+        let ids: Vec<u32> = device.channels[&0]
+            .messages
+            .iter()
+            .map(|m| m.packet_id())
+            .collect();
+
+        assert_eq!(ids, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn stores_local_stats_of_own_radio_as_device_counters() {
+        let mut device = MeshDevice::new();
+        device.my_node_info.my_node_num = 1;
+
+        for from in [1, 2] {
+            device.set_device_metrics(TelemetryPacket {
+                packet: protobufs::MeshPacket {
+                    from,
+                    ..Default::default()
+                },
+                data: protobufs::Telemetry {
+                    variant: Some(protobufs::telemetry::Variant::LocalStats(
+                        protobufs::LocalStats {
+                            num_packets_tx: from * 10,
+                            ..Default::default()
+                        },
+                    )),
+                    ..Default::default()
+                },
+            });
+        }
+
+        assert_eq!(device.nodes[&1].local_stats.len(), 1);
+        assert_eq!(device.nodes[&2].local_stats.len(), 1);
+        assert_eq!(
+            device.local_stats.as_ref().map(|s| s.num_packets_tx),
+            Some(10)
+        );
+    }
+
+    #[test]
+    fn stores_device_metrics_of_own_radio_only() {
+        let mut device = MeshDevice::new();
+        device.my_node_info.my_node_num = 1;
+
+        for from in [1, 2] {
+            device.set_device_metrics(TelemetryPacket {
+                packet: protobufs::MeshPacket {
+                    from,
+                    ..Default::default()
+                },
+                data: protobufs::Telemetry {
+                    variant: Some(protobufs::telemetry::Variant::DeviceMetrics(
+                        protobufs::DeviceMetrics {
+                            battery_level: from * 10,
+                            ..Default::default()
+                        },
+                    )),
+                    ..Default::default()
+                },
+            });
+        }
+
+        assert_eq!(device.nodes[&2].device_metrics.len(), 1);
+        assert_eq!(device.device_metrics.battery_level, 10);
+    }
+}
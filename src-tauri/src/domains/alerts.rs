@@ -0,0 +1,62 @@
+use log::debug;
+
+use crate::api::contracts::alerts::{
+    AddAlertRuleRequest, AddAlertRuleResponse, ListAlertRulesRequest, ListAlertRulesResponse,
+    RemoveAlertRuleRequest, RemoveAlertRuleResponse,
+};
+use crate::ipc::CommandError;
+use crate::state;
+
+pub async fn handle_add_alert_rule(
+    request: AddAlertRuleRequest,
+    mesh_devices: &state::mesh_devices::MeshDevicesState,
+) -> Result<AddAlertRuleResponse, CommandError> {
+    let AddAlertRuleRequest { rule } = request;
+    debug!("Called handle_add_alert_rule with rule \"{}\"", rule.name);
+
+    if !rule.threshold.is_finite() {
+        return Err("Alert threshold must be a finite number".into());
+    }
+
+    if !rule.hysteresis.is_finite() || rule.hysteresis < 0.0 {
+        return Err("Alert hysteresis must not be negative".into());
+    }
+
+    let mut alerts_guard = mesh_devices.alerts.lock().map_err(|e| e.to_string())?;
+
+    let response = AddAlertRuleResponse {
+        rule: alerts_guard.add_rule(rule),
+    };
+    Ok(response)
+}
+
+pub async fn handle_remove_alert_rule(
+    request: RemoveAlertRuleRequest,
+    mesh_devices: &state::mesh_devices::MeshDevicesState,
+) -> Result<RemoveAlertRuleResponse, CommandError> {
+    let RemoveAlertRuleRequest { rule_id } = request;
+    debug!("Called handle_remove_alert_rule with rule {}", rule_id);
+
+    let mut alerts_guard = mesh_devices.alerts.lock().map_err(|e| e.to_string())?;
+
+    if !alerts_guard.remove_rule(rule_id) {
+        return Err(format!("Alert rule {} not found", rule_id).into());
+    }
+
+    let response = RemoveAlertRuleResponse {};
+    Ok(response)
+}
+
+pub async fn handle_list_alert_rules(
+    _request: ListAlertRulesRequest,
+    mesh_devices: &state::mesh_devices::MeshDevicesState,
+) -> Result<ListAlertRulesResponse, CommandError> {
+    debug!("Called handle_list_alert_rules");
+
+    let alerts_guard = mesh_devices.alerts.lock().map_err(|e| e.to_string())?;
+
+    let response = ListAlertRulesResponse {
+        rules: alerts_guard.rules().to_vec(),
+    };
+    Ok(response)
+}
@@ -0,0 +1,68 @@
+use std::path::PathBuf;
+
+use log::{debug, info};
+
+use crate::api::contracts::capture::{
+    StartPacketCaptureRequest, StartPacketCaptureResponse, StopPacketCaptureRequest,
+    StopPacketCaptureResponse,
+};
+use crate::capture::CaptureWriter;
+use crate::ipc::CommandError;
+use crate::state;
+
+pub async fn handle_start_packet_capture(
+    request: StartPacketCaptureRequest,
+    mesh_devices: &state::mesh_devices::MeshDevicesState,
+) -> Result<StartPacketCaptureResponse, CommandError> {
+    let StartPacketCaptureRequest {
+        device_key,
+        file_path,
+    } = request;
+
+    debug!(
+        "Called handle_start_packet_capture for device \"{}\" with path \"{}\"",
+        device_key, file_path
+    );
+
+    let mut devices_guard = mesh_devices.inner.lock().await;
+    let packet_api = devices_guard
+        .get_mut(&device_key)
+        .ok_or("Device not initialized")?;
+
+    let capture = CaptureWriter::create(&PathBuf::from(&file_path))
+        .map_err(|e| format!("Failed to create packet capture \"{}\": {}", file_path, e))?;
+
+    // Replaces any running capture, which is closed when dropped
+    packet_api.capture = Some(capture);
+
+    info!(
+        "Capturing packets from device \"{}\" to \"{}\"",
+        device_key, file_path
+    );
+
+    let response = StartPacketCaptureResponse {};
+    Ok(response)
+}
+
+pub async fn handle_stop_packet_capture(
+    request: StopPacketCaptureRequest,
+    mesh_devices: &state::mesh_devices::MeshDevicesState,
+) -> Result<StopPacketCaptureResponse, CommandError> {
+    let StopPacketCaptureRequest { device_key } = request;
+    debug!(
+        "Called handle_stop_packet_capture for device \"{}\"",
+        device_key
+    );
+
+    let mut devices_guard = mesh_devices.inner.lock().await;
+    let packet_api = devices_guard
+        .get_mut(&device_key)
+        .ok_or("Device not initialized")?;
+
+    if let Some(capture) = packet_api.capture.take() {
+        info!("Stopped packet capture {:?}", capture.path());
+    }
+
+    let response = StopPacketCaptureResponse {};
+    Ok(response)
+}
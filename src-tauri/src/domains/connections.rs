@@ -1,17 +1,22 @@
 use crate::api::contracts::connections::{
-    ConnectToBluetoothRequest, ConnectToBluetoothResponse, ConnectToSerialPortRequest,
-    ConnectToSerialPortResponse, ConnectToTcpPortRequest, ConnectToTcpPortResponse,
-    DropAllDeviceConnectionsRequest, DropAllDeviceConnectionsResponse, DropDeviceConnectionRequest,
-    DropDeviceConnectionResponse, GetAllBluetoothRequest, GetAllBluetoothResponse,
-    GetAllSerialPortsRequest, GetAllSerialPortsResponse, RequestAutoconnectPortRequest,
-    RequestAutoconnectPortResponse,
+    ConnectToBluetoothRequest, ConnectToBluetoothResponse, ConnectToReplayRequest,
+    ConnectToReplayResponse, ConnectToSerialPortRequest, ConnectToSerialPortResponse,
+    ConnectToTcpPortRequest, ConnectToTcpPortResponse, DropAllDeviceConnectionsRequest,
+    DropAllDeviceConnectionsResponse, DropDeviceConnectionRequest, DropDeviceConnectionResponse,
+    GetAllBluetoothRequest, GetAllBluetoothResponse, GetAllSerialPortsRequest,
+    GetAllSerialPortsResponse, RequestAutoconnectPortRequest, RequestAutoconnectPortResponse,
 };
 use crate::api::primitives::connections::BluetoothConnectionCandidate;
 use crate::api::primitives::connections::SerialPortConnectionCandidate;
+use crate::capture::{
+    capture_file_name, is_valid_replay_speed, read_capture, replay_delay, replay_into,
+    CaptureWriter, MAX_REPLAY_SPEED, MIN_REPLAY_SPEED, REPLAY_DEVICE_KEY_PREFIX,
+};
 use crate::device;
 use crate::device::SerialDeviceStatus;
+use crate::ipc::events;
 use crate::ipc::helpers::spawn_configuration_timeout_handler;
-use crate::ipc::helpers::spawn_decoded_handler;
+use crate::ipc::helpers::{spawn_decoded_handler, DecodedHandlerExit};
 use crate::ipc::CommandError;
 use crate::packet_api::MeshPacketApi;
 use crate::state;
@@ -20,23 +25,32 @@ use crate::state::DeviceKey;
 use btleplug::api::ScanFilter;
 use btleplug::api::{Central, Manager as _, Peripheral as _};
 use btleplug::platform::Manager;
-use log::debug;
+use log::{debug, info, warn};
 use meshtastic::api::{StreamApi, StreamHandle};
 use meshtastic::utils::stream::build_ble_stream;
 use meshtastic::utils::stream::build_serial_stream;
 use meshtastic::utils::stream::build_tcp_stream;
 use meshtastic::utils::stream::BleId;
+use std::path::PathBuf;
 use std::time::Duration;
+use tauri::async_runtime::JoinHandle;
 use tokio::io::AsyncReadExt;
 use tokio::io::AsyncWriteExt;
 use tokio::time;
+use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
 const MSH_SERVICE: Uuid = Uuid::from_u128(0x6ba1b218_15a8_461f_9fa8_5dcae273eafd);
 
+/// Time a device has to finish configuring before the UI is told it failed
+const CONFIGURATION_TIMEOUT: Duration = Duration::from_millis(15000);
+
+const RECONNECT_BASE_DELAY: Duration = Duration::from_secs(1);
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(60);
+
 pub async fn handle_request_autoconnect_port(
     _request: RequestAutoconnectPortRequest,
-    autoconnect_state: tauri::State<'_, state::autoconnect::AutoConnectState>,
+    autoconnect_state: &state::autoconnect::AutoConnectState,
 ) -> Result<RequestAutoconnectPortResponse, CommandError> {
     let autoconnect_port_guard = autoconnect_state.inner.lock().await;
     let autoconnect_port = autoconnect_port_guard
@@ -116,61 +130,250 @@ pub fn handle_get_all_serial_ports(
     Ok(response)
 }
 
-async fn create_new_connection<S>(
-    stream: StreamHandle<S>,
-    device_key: DeviceKey,
-    timeout_duration: Duration,
-    app_handle: tauri::AppHandle,
-    mesh_devices: tauri::State<'_, state::mesh_devices::MeshDevicesState>,
-    radio_connections: tauri::State<'_, state::radio_connections::RadioConnectionsState>,
-    mesh_graph: tauri::State<'_, state::graph::GraphState>,
-) -> Result<(), CommandError>
-where
-    S: AsyncReadExt + AsyncWriteExt + Send + 'static,
-{
-    // Initialize device and StreamApi instances
+/// How a radio was connected, kept so that dropped connections can be re-established
+#[derive(Clone, Debug)]
+pub enum ConnectionSpec {
+    Bluetooth(ConnectToBluetoothRequest),
+    Serial(ConnectToSerialPortRequest),
+    Tcp(ConnectToTcpPortRequest),
+}
+
+impl ConnectionSpec {
+    pub fn device_key(&self) -> DeviceKey {
+        match self {
+            ConnectionSpec::Bluetooth(request) => request.bluetooth_name.clone(),
+            ConnectionSpec::Serial(request) => request.port_name.clone(),
+            ConnectionSpec::Tcp(request) => request.address.clone(),
+        }
+    }
+}
+
+/// Returns the delay before the passed reconnection attempt (starting at 1),
+/// doubling after each failed attempt up to `RECONNECT_MAX_DELAY`
+pub fn reconnect_delay(attempt: u32) -> Duration {
+    let exponent = attempt.saturating_sub(1).min(u32::BITS - 1);
+    RECONNECT_BASE_DELAY
+        .saturating_mul(2u32.saturating_pow(exponent))
+        .min(RECONNECT_MAX_DELAY)
+}
+
+async fn create_new_connection(
+    spec: ConnectionSpec,
+    events: &state::events::EventsState,
+    mesh_devices: &state::mesh_devices::MeshDevicesState,
+    radio_connections: &state::radio_connections::RadioConnectionsState,
+    mesh_graph: &state::graph::GraphState,
+) -> Result<(), CommandError> {
+    let device_key = spec.device_key();
+
+    // Initialize device and persist it in Tauri state, so that reconnections
+    // can reuse it and keep the history it has accumulated
 
     let device = device::MeshDevice::new();
     let mut packet_api = MeshPacketApi::new(
-        app_handle.clone(),
+        events.inner.clone(),
         device_key.clone(),
         device,
         mesh_graph.inner.clone(),
+        mesh_devices.history_dir.clone(),
+    );
+    packet_api.alerts = mesh_devices.alerts.clone();
+    let cancellation = packet_api.cancellation.clone();
+
+    {
+        let mut devices_guard = mesh_devices.inner.lock().await;
+
+        if devices_guard.contains_key(&device_key) {
+            return Err(format!("Device \"{}\" is already connected", device_key).into());
+        }
+
+        // Capture before connecting so that the configuration packets are
+        // included, which replays need to reconstruct the device
+
+        if let Some(capture_dir) = mesh_devices.capture_dir.as_ref() {
+            let path = capture_dir.join(capture_file_name(&device_key, chrono::Utc::now()));
+
+            match CaptureWriter::create(&path) {
+                Ok(capture) => packet_api.capture = Some(capture),
+                Err(e) => warn!("Failed to create packet capture {:?}: {}", path, e),
+            }
+        }
+
+        devices_guard.insert(device_key.clone(), packet_api);
+    }
+
+    let decoded_handler = match open_connection(
+        &spec,
+        &cancellation,
+        events,
+        mesh_devices,
+        radio_connections,
+    )
+    .await
+    {
+        Ok(handle) => handle,
+        Err(e) => {
+            let mut devices_guard = mesh_devices.inner.lock().await;
+
+            // Once dropped, the key may already belong to another connection
+            if !cancellation.is_cancelled() {
+                devices_guard.remove(&device_key);
+            }
+
+            return Err(e);
+        }
+    };
+
+    // Spawn supervisor to re-establish the connection if it is lost
+
+    spawn_connection_supervisor(
+        spec,
+        cancellation,
+        decoded_handler,
+        events.clone(),
+        mesh_devices.clone(),
+        radio_connections.clone(),
     );
 
+    Ok(())
+}
+
+/// Builds the stream described by the passed spec and connects the
+/// device it describes, which must already be persisted in Tauri state.
+/// Fails without keeping the connection if the device is dropped meanwhile.
+async fn open_connection(
+    spec: &ConnectionSpec,
+    cancellation: &CancellationToken,
+    events: &state::events::EventsState,
+    mesh_devices: &state::mesh_devices::MeshDevicesState,
+    radio_connections: &state::radio_connections::RadioConnectionsState,
+) -> Result<JoinHandle<DecodedHandlerExit>, CommandError> {
+    let device_key = spec.device_key();
+
+    match spec {
+        ConnectionSpec::Bluetooth(request) => {
+            let stream = build_ble_stream(
+                &BleId::from_name(&request.bluetooth_name),
+                Duration::from_secs(5),
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+
+            connect_stream(
+                stream,
+                device_key,
+                cancellation,
+                events,
+                mesh_devices,
+                radio_connections,
+            )
+            .await
+        }
+        ConnectionSpec::Serial(request) => {
+            let stream = build_serial_stream(
+                request.port_name.clone(),
+                request.baud_rate,
+                request.dtr,
+                request.rts,
+            )
+            .map_err(|e| e.to_string())?;
+
+            connect_stream(
+                stream,
+                device_key,
+                cancellation,
+                events,
+                mesh_devices,
+                radio_connections,
+            )
+            .await
+        }
+        ConnectionSpec::Tcp(request) => {
+            let stream = build_tcp_stream(request.address.clone())
+                .await
+                .map_err(|e| e.to_string())?;
+
+            connect_stream(
+                stream,
+                device_key,
+                cancellation,
+                events,
+                mesh_devices,
+                radio_connections,
+            )
+            .await
+        }
+    }
+}
+
+async fn connect_stream<S>(
+    stream: StreamHandle<S>,
+    device_key: DeviceKey,
+    cancellation: &CancellationToken,
+    events: &state::events::EventsState,
+    mesh_devices: &state::mesh_devices::MeshDevicesState,
+    radio_connections: &state::radio_connections::RadioConnectionsState,
+) -> Result<JoinHandle<DecodedHandlerExit>, CommandError>
+where
+    S: AsyncReadExt + AsyncWriteExt + Send + 'static,
+{
     let stream_api = StreamApi::new();
 
     // Connect to device via stream API
 
-    packet_api.device.set_status(SerialDeviceStatus::Connecting);
+    set_device_status(
+        mesh_devices,
+        &device_key,
+        cancellation,
+        SerialDeviceStatus::Connecting,
+    )
+    .await?;
     let (decoded_listener, stream_api) = stream_api.connect(stream).await;
 
     // Configure device via stream API
 
-    packet_api
-        .device
-        .set_status(SerialDeviceStatus::Configuring);
+    let config_id = {
+        let mut devices_guard = mesh_devices.inner.lock().await;
+
+        if cancellation.is_cancelled() {
+            return Err("Device connection was dropped".into());
+        }
+
+        let packet_api = devices_guard
+            .get_mut(&device_key)
+            .ok_or("Device not initialized")?;
+
+        packet_api
+            .device
+            .set_status(SerialDeviceStatus::Configuring);
+
+        packet_api.device.config_id
+    };
 
     let stream_api = stream_api
-        .configure(packet_api.device.config_id)
+        .configure(config_id)
         .await
         .map_err(|e| e.to_string())?;
 
-    // Persist connection in Tauri state
+    // Persist StreamApi instance Tauri state
 
-    let handle = app_handle.clone();
     let mesh_devices_arc = mesh_devices.inner.clone();
     let radio_connections_arc = radio_connections.inner.clone();
 
-    // Persist device struct in Tauri state
-    {
-        let mut devices_guard = mesh_devices_arc.lock().await;
-        devices_guard.insert(device_key.clone(), packet_api);
-    }
-
-    // Persist StreamApi instance Tauri state
     {
+        // Held so that the device can't be dropped before the connection is
+        // persisted, as it would then never be disconnected
+        let _devices_guard = mesh_devices_arc.lock().await;
         let mut connections_guard = radio_connections_arc.lock().await;
+
+        if cancellation.is_cancelled() {
+            if let Err(e) = stream_api.disconnect().await {
+                debug!("Failed to disconnect from device: {:?}", e);
+            }
+
+            return Err("Device connection was dropped".into());
+        }
+
         connections_guard.insert(device_key.clone(), stream_api);
     }
 
@@ -178,46 +381,191 @@ where
     // Needs the device struct and port name to be loaded into Tauri state before running
 
     spawn_configuration_timeout_handler(
-        handle.clone(),
+        events.inner.clone(),
         mesh_devices_arc.clone(),
         device_key.clone(),
-        timeout_duration,
+        CONFIGURATION_TIMEOUT,
     );
 
     // Spawn decoded packet handler to route decoded packets
 
-    spawn_decoded_handler(decoded_listener, mesh_devices_arc, device_key);
+    let decoded_handler = spawn_decoded_handler(
+        decoded_listener,
+        mesh_devices_arc,
+        device_key,
+        events.packets.clone(),
+        radio_connections_arc,
+    );
+
+    Ok(decoded_handler)
+}
+
+async fn set_device_status(
+    mesh_devices: &state::mesh_devices::MeshDevicesState,
+    device_key: &DeviceKey,
+    cancellation: &CancellationToken,
+    status: SerialDeviceStatus,
+) -> Result<(), CommandError> {
+    let mut devices_guard = mesh_devices.inner.lock().await;
+
+    // Once dropped, the key may already belong to another connection
+    if cancellation.is_cancelled() {
+        return Err("Device connection was dropped".into());
+    }
+
+    let packet_api = devices_guard
+        .get_mut(device_key)
+        .ok_or("Device not initialized")?;
+
+    packet_api.device.set_status(status);
+
+    events::dispatch_updated_device(&packet_api.events, &packet_api.device)
+        .map_err(|e| e.to_string())?;
 
     Ok(())
 }
 
+/// Watches the decoded packet handler of a connection and reconnects with
+/// exponential backoff when its stream closes or the radio reboots. Stops once
+/// the connection has been cancelled by dropping the device.
+fn spawn_connection_supervisor(
+    spec: ConnectionSpec,
+    cancellation: CancellationToken,
+    mut decoded_handler: JoinHandle<DecodedHandlerExit>,
+    events: state::events::EventsState,
+    mesh_devices: state::mesh_devices::MeshDevicesState,
+    radio_connections: state::radio_connections::RadioConnectionsState,
+) {
+    let device_key = spec.device_key();
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let exit = decoded_handler
+                .await
+                .unwrap_or(DecodedHandlerExit::StreamClosed);
+
+            // Connections are cancelled when intentionally dropped
+            if cancellation.is_cancelled() {
+                debug!("Device \"{}\" was dropped, stopping supervisor", device_key);
+                return;
+            }
+
+            warn!(
+                "Lost connection to device \"{}\" ({:?}), reconnecting",
+                device_key, exit
+            );
+
+            // Release the previous connection before opening a new one, since
+            // serial ports and Bluetooth devices only accept a single connection
+
+            let previous_connection = {
+                let _devices_guard = mesh_devices.inner.lock().await;
+
+                if cancellation.is_cancelled() {
+                    return;
+                }
+
+                radio_connections.inner.lock().await.remove(&device_key)
+            };
+
+            if let Some(stream_api) = previous_connection {
+                if let Err(e) = stream_api.disconnect().await {
+                    debug!("Failed to disconnect from device: {:?}", e);
+                }
+            }
+
+            if set_device_status(
+                &mesh_devices,
+                &device_key,
+                &cancellation,
+                SerialDeviceStatus::Reconnecting,
+            )
+            .await
+            .is_err()
+            {
+                return;
+            }
+
+            let mut attempt = 0;
+
+            decoded_handler = loop {
+                attempt += 1;
+                let delay = reconnect_delay(attempt);
+
+                debug!(
+                    "Reconnecting to device \"{}\" in {:?} (attempt {})",
+                    device_key, delay, attempt
+                );
+
+                tokio::select! {
+                    _ = time::sleep(delay) => {}
+                    _ = cancellation.cancelled() => {
+                        debug!("Device \"{}\" was dropped, stopping supervisor", device_key);
+                        return;
+                    }
+                }
+
+                match open_connection(
+                    &spec,
+                    &cancellation,
+                    &events,
+                    &mesh_devices,
+                    &radio_connections,
+                )
+                .await
+                {
+                    Ok(handle) => break handle,
+                    Err(_) if cancellation.is_cancelled() => {
+                        debug!("Device \"{}\" was dropped, stopping supervisor", device_key);
+                        return;
+                    }
+                    Err(e) => {
+                        warn!("Failed to reconnect to device \"{}\": {}", device_key, e);
+
+                        // Keep the UI informed while waiting for the next attempt
+                        let _ = set_device_status(
+                            &mesh_devices,
+                            &device_key,
+                            &cancellation,
+                            SerialDeviceStatus::Reconnecting,
+                        )
+                        .await;
+                    }
+                }
+            };
+
+            // The device may have been dropped once the connection was
+            // opened, in which case dropping it disconnected the connection
+            if cancellation.is_cancelled() {
+                decoded_handler.abort();
+                return;
+            }
+
+            info!(
+                "Reconnected to device \"{}\" after {} attempt(s)",
+                device_key, attempt
+            );
+        }
+    });
+}
+
 pub async fn handle_connect_to_bluetooth(
     request: ConnectToBluetoothRequest,
-    app_handle: tauri::AppHandle,
-    mesh_devices: tauri::State<'_, state::mesh_devices::MeshDevicesState>,
-    radio_connections: tauri::State<'_, state::radio_connections::RadioConnectionsState>,
-    mesh_graph: tauri::State<'_, state::graph::GraphState>,
+    events: &state::events::EventsState,
+    mesh_devices: &state::mesh_devices::MeshDevicesState,
+    radio_connections: &state::radio_connections::RadioConnectionsState,
+    mesh_graph: &state::graph::GraphState,
 ) -> Result<ConnectToBluetoothResponse, CommandError> {
-    let ConnectToBluetoothRequest { bluetooth_name } = request;
-
     debug!(
         "Called connect_to_bluetooth command with device name \"{}\"",
-        bluetooth_name
+        request.bluetooth_name
     );
 
-    // Create serial connection stream
-
-    let stream = build_ble_stream(&BleId::from_name(&bluetooth_name), Duration::from_secs(5))
-        .await
-        .unwrap();
-
     // Create and persist new connection
 
     create_new_connection(
-        stream,
-        bluetooth_name,
-        Duration::from_millis(15000),
-        app_handle,
+        ConnectionSpec::Bluetooth(request),
+        events,
         mesh_devices,
         radio_connections,
         mesh_graph,
@@ -230,35 +578,21 @@ pub async fn handle_connect_to_bluetooth(
 
 pub async fn handle_connect_to_serial_port(
     request: ConnectToSerialPortRequest,
-    app_handle: tauri::AppHandle,
-    mesh_devices: tauri::State<'_, state::mesh_devices::MeshDevicesState>,
-    radio_connections: tauri::State<'_, state::radio_connections::RadioConnectionsState>,
-    mesh_graph: tauri::State<'_, state::graph::GraphState>,
+    events: &state::events::EventsState,
+    mesh_devices: &state::mesh_devices::MeshDevicesState,
+    radio_connections: &state::radio_connections::RadioConnectionsState,
+    mesh_graph: &state::graph::GraphState,
 ) -> Result<ConnectToSerialPortResponse, CommandError> {
-    let ConnectToSerialPortRequest {
-        port_name,
-        baud_rate,
-        dtr,
-        rts,
-    } = request;
-
     debug!(
         "Called connect_to_serial_port command with port \"{}\"",
-        port_name
+        request.port_name
     );
 
-    // Create serial connection stream
-
-    let stream =
-        build_serial_stream(port_name.clone(), baud_rate, dtr, rts).map_err(|e| e.to_string())?;
-
     // Create and persist new connection
 
     create_new_connection(
-        stream,
-        port_name,
-        Duration::from_millis(15000),
-        app_handle,
+        ConnectionSpec::Serial(request),
+        events,
         mesh_devices,
         radio_connections,
         mesh_graph,
@@ -271,31 +605,21 @@ pub async fn handle_connect_to_serial_port(
 
 pub async fn handle_connect_to_tcp_port(
     request: ConnectToTcpPortRequest,
-    app_handle: tauri::AppHandle,
-    mesh_devices: tauri::State<'_, state::mesh_devices::MeshDevicesState>,
-    radio_connections: tauri::State<'_, state::radio_connections::RadioConnectionsState>,
-    mesh_graph: tauri::State<'_, state::graph::GraphState>,
+    events: &state::events::EventsState,
+    mesh_devices: &state::mesh_devices::MeshDevicesState,
+    radio_connections: &state::radio_connections::RadioConnectionsState,
+    mesh_graph: &state::graph::GraphState,
 ) -> Result<ConnectToTcpPortResponse, CommandError> {
-    let ConnectToTcpPortRequest { address } = request;
-
     debug!(
         "Called connect_to_tcp_port command with address \"{}\"",
-        address
+        request.address
     );
 
-    // Create TCP connection stream
-
-    let stream = build_tcp_stream(address.clone())
-        .await
-        .map_err(|e| e.to_string())?;
-
     // Create and persist new connection
 
     create_new_connection(
-        stream,
-        address,
-        Duration::from_millis(15000),
-        app_handle,
+        ConnectionSpec::Tcp(request),
+        events,
         mesh_devices,
         radio_connections,
         mesh_graph,
@@ -306,10 +630,109 @@ pub async fn handle_connect_to_tcp_port(
     Ok(response)
 }
 
+pub async fn handle_connect_to_replay(
+    request: ConnectToReplayRequest,
+    events: &state::events::EventsState,
+    mesh_devices: &state::mesh_devices::MeshDevicesState,
+    mesh_graph: &state::graph::GraphState,
+) -> Result<ConnectToReplayResponse, CommandError> {
+    debug!(
+        "Called connect_to_replay command with file \"{}\"",
+        request.file_path
+    );
+
+    let ConnectToReplayRequest { file_path, speed } = request;
+    let device_key: DeviceKey = format!("{}{}", REPLAY_DEVICE_KEY_PREFIX, file_path);
+
+    let speed = speed.unwrap_or(1.0);
+
+    if !is_valid_replay_speed(speed) {
+        return Err(format!(
+            "Replay speed must be 0 or between {} and {}",
+            MIN_REPLAY_SPEED, MAX_REPLAY_SPEED
+        )
+        .into());
+    }
+
+    let records = read_capture(&PathBuf::from(&file_path))
+        .map_err(|e| format!("Failed to read packet capture \"{}\": {}", file_path, e))?;
+
+    // Replayed devices have no radio to write history for, and are never
+    // captured again since the capture already exists
+
+    let mut device = device::MeshDevice::new();
+    device.set_status(SerialDeviceStatus::Connected);
+
+    let packet_api = MeshPacketApi::new(
+        events.inner.clone(),
+        device_key.clone(),
+        device,
+        mesh_graph.inner.clone(),
+        None,
+    );
+    let cancellation = packet_api.cancellation.clone();
+
+    {
+        let mut devices_guard = mesh_devices.inner.lock().await;
+
+        if devices_guard.contains_key(&device_key) {
+            return Err(format!("Capture \"{}\" is already being replayed", file_path).into());
+        }
+
+        devices_guard.insert(device_key.clone(), packet_api);
+    }
+
+    // Feed the captured packets straight into the replayed device, preserving
+    // the time between them relative to the playback speed. Unlike a live
+    // radio, replays are never published to the packet bus, bridged to MQTT,
+    // captured again or evaluated against alert rules.
+
+    let replay_key = device_key.clone();
+    let replay_devices = mesh_devices.clone();
+
+    tauri::async_runtime::spawn(async move {
+        info!(
+            "Replaying {} packets from \"{}\"",
+            records.len(),
+            replay_key
+        );
+
+        let mut previous_timestamp_ms = records.first().map(|r| r.timestamp_ms);
+
+        for record in records {
+            let elapsed_ms = previous_timestamp_ms
+                .map(|previous| record.timestamp_ms.saturating_sub(previous))
+                .unwrap_or_default();
+
+            time::sleep(replay_delay(elapsed_ms, speed)).await;
+            previous_timestamp_ms = Some(record.timestamp_ms);
+
+            // Replays are cancelled when intentionally dropped, after which
+            // the key may belong to a later replay of the same capture
+            let mut devices_guard = replay_devices.inner.lock().await;
+            let packet_api = match devices_guard.get_mut(&replay_key) {
+                Some(packet_api) if !cancellation.is_cancelled() => packet_api,
+                _ => {
+                    debug!("Replay \"{}\" was dropped, stopping", replay_key);
+                    return;
+                }
+            };
+
+            replay_into(packet_api, [record]);
+        }
+
+        info!("Finished replaying \"{}\"", replay_key);
+    });
+
+    let response = ConnectToReplayResponse { device_key };
+    Ok(response)
+}
+
 pub async fn handle_drop_device_connection(
     request: DropDeviceConnectionRequest,
-    mesh_devices: tauri::State<'_, state::mesh_devices::MeshDevicesState>,
-    radio_connections: tauri::State<'_, state::radio_connections::RadioConnectionsState>,
+    mesh_devices: &state::mesh_devices::MeshDevicesState,
+    radio_connections: &state::radio_connections::RadioConnectionsState,
+    mesh_graph: &state::graph::GraphState,
 ) -> Result<DropDeviceConnectionResponse, CommandError> {
     let DropDeviceConnectionRequest { device_key } = request;
 
@@ -332,6 +755,7 @@ pub async fn handle_drop_device_connection(
         // Clear corresponding state device
 
         if let Some(packet_api) = state_devices.get_mut(&device_key) {
+            packet_api.cancellation.cancel();
             packet_api
                 .device
                 .set_status(SerialDeviceStatus::Disconnected);
@@ -340,34 +764,58 @@ pub async fn handle_drop_device_connection(
         state_devices.remove(&device_key);
     }
 
+    // The radio no longer vouches for what it observed while connected
+
+    mesh_graph
+        .inner
+        .lock()
+        .map_err(|e| e.to_string())?
+        .observations
+        .forget_observer(&device_key);
+
     let response = DropDeviceConnectionResponse {};
     Ok(response)
 }
 
 pub async fn handle_drop_all_device_connections(
     _request: DropAllDeviceConnectionsRequest,
-    mesh_devices: tauri::State<'_, state::mesh_devices::MeshDevicesState>,
-    radio_connections: tauri::State<'_, state::radio_connections::RadioConnectionsState>,
+    mesh_devices: &state::mesh_devices::MeshDevicesState,
+    radio_connections: &state::radio_connections::RadioConnectionsState,
+    mesh_graph: &state::graph::GraphState,
 ) -> Result<DropAllDeviceConnectionsResponse, CommandError> {
     debug!("Called drop_all_device_connections command");
 
     {
+        // Locked in the same order as when dropping a single device
+        let mut state_devices = mesh_devices.inner.lock().await;
         let mut connections_guard = radio_connections.inner.lock().await;
 
+        // Set all state devices as disconnected, stopping their supervisors
+
+        for (_port_name, packet_api) in state_devices.iter_mut() {
+            packet_api.cancellation.cancel();
+            packet_api
+                .device
+                .set_status(SerialDeviceStatus::Disconnected);
+        }
+
         // Disconnect from all open connections and empty HashMap
 
+        // Failures are only logged, as cancelled devices left in state
+        // would keep their keys from being connected again
+
         for (_, connection) in connections_guard.drain() {
-            connection.disconnect().await.map_err(|e| e.to_string())?;
+            if let Err(e) = connection.disconnect().await {
+                debug!("Failed to disconnect from device: {:?}", e);
+            }
         }
 
-        // Set all state devices as disconnected and empty HashMap
+        // Forget what the dropped radios observed
 
-        let mut state_devices = mesh_devices.inner.lock().await;
+        let mut graph_guard = mesh_graph.inner.lock().map_err(|e| e.to_string())?;
 
-        for (_port_name, packet_api) in state_devices.iter_mut() {
-            packet_api
-                .device
-                .set_status(SerialDeviceStatus::Disconnected);
+        for device_key in state_devices.keys() {
+            graph_guard.observations.forget_observer(device_key);
         }
 
         // This could be removed in the future to maintain state on previous devices
@@ -377,3 +825,17 @@ pub async fn handle_drop_all_device_connections(
     let response = DropAllDeviceConnectionsResponse {};
     Ok(response)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reconnect_delay_backs_off_exponentially() {
+        assert_eq!(reconnect_delay(1), Duration::from_secs(1));
+        assert_eq!(reconnect_delay(2), Duration::from_secs(2));
+        assert_eq!(reconnect_delay(4), Duration::from_secs(8));
+        assert_eq!(reconnect_delay(10), RECONNECT_MAX_DELAY);
+        assert_eq!(reconnect_delay(u32::MAX), RECONNECT_MAX_DELAY);
+    }
+}
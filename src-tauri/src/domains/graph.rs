@@ -1,20 +1,56 @@
 use std::time::Duration;
 
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 
 use crate::api::contracts::graph::{
-    GetGraphStateRequest, GetGraphStateResponse, InitializeTimeoutHandlerRequest,
-    InitializeTimeoutHandlerResponse, StopTimeoutHandlerRequest, StopTimeoutHandlerResponse,
+    ExportGraphRequest, ExportGraphResponse, GetBestPathRequest, GetBestPathResponse,
+    GetGraphSnapshotRequest, GetGraphSnapshotResponse, GetGraphStateRequest, GetGraphStateResponse,
+    GetObservedTopologyRequest, GetObservedTopologyResponse, GetRouteQualityRequest,
+    GetRouteQualityResponse, GetTopologyDiffRequest, GetTopologyDiffResponse,
+    InitializeTimeoutHandlerRequest, InitializeTimeoutHandlerResponse, ListGraphSnapshotsRequest,
+    ListGraphSnapshotsResponse, RunGraphAnalysisRequest, RunGraphAnalysisResponse,
+    SetInferNeighborNodesRequest, SetInferNeighborNodesResponse, StopTimeoutHandlerRequest,
+    StopTimeoutHandlerResponse,
 };
-use crate::ipc::events::dispatch_updated_graph;
-use crate::ipc::CommandError;
+use crate::api::primitives::graph::GraphExportFormat;
+use crate::export::graph::{graph_dot, graph_geojson, graph_graphml};
+use crate::graph::algorithms::{
+    articulation_points::articulation_points,
+    best_path::{best_path, route_quality},
+    betweenness::betweenness_centrality,
+    diffusion_centrality::diffusion_centrality,
+    stoer_wagner::global_min_cut,
+};
+use crate::graph::ds::timeout_policy::{MAX_BROADCAST_INTERVAL_MULTIPLIER, MAX_TIMEOUT_DURATION};
+use crate::ipc::events::dispatch_graph_deltas;
+use crate::ipc::{APMincutStringResults, CommandError};
 use crate::state;
 
-pub const DEFAULT_GRAPH_CLEAN_SECONDS: u64 = 60;
+/// Retransmission rounds diffusion centrality is computed for
+const DIFFUSION_HORIZONS: [u32; 3] = [1, 2, 3];
+
+/// Link delivery probabilities in percent that diffusion centrality is computed for
+const DIFFUSION_PROBABILITIES_PERCENT: [u32; 3] = [25, 50, 75];
+
+/// Rejects timeouts too long for the graph cleaner to compare against
+fn bounded_timeout(name: &str, secs: u64) -> Result<Duration, CommandError> {
+    let timeout = Duration::from_secs(secs);
+
+    if timeout > MAX_TIMEOUT_DURATION {
+        return Err(format!(
+            "{} must be at most {} seconds",
+            name,
+            MAX_TIMEOUT_DURATION.as_secs()
+        )
+        .into());
+    }
+
+    Ok(timeout)
+}
 
 pub async fn handle_get_graph_state(
     _request: GetGraphStateRequest,
-    mesh_graph: tauri::State<'_, state::graph::GraphState>,
+    mesh_graph: &state::graph::GraphState,
 ) -> Result<GetGraphStateResponse, CommandError> {
     debug!("Called handle_get_graph_state");
 
@@ -26,31 +62,79 @@ pub async fn handle_get_graph_state(
 }
 
 pub async fn handle_initialize_timeout_handler(
-    _request: InitializeTimeoutHandlerRequest,
-    app_handle: tauri::AppHandle,
-    mesh_graph_state: tauri::State<'_, state::graph::GraphState>,
+    request: InitializeTimeoutHandlerRequest,
+    events: &state::events::EventsState,
+    mesh_graph_state: &state::graph::GraphState,
 ) -> Result<InitializeTimeoutHandlerResponse, CommandError> {
+    let InitializeTimeoutHandlerRequest {
+        node_timeout_secs,
+        edge_timeout_secs,
+        broadcast_interval_multiplier,
+        clean_period_secs,
+    } = request;
     debug!("Called handle_initialize_timeout_handler");
 
     let mesh_graph_arc = mesh_graph_state.inner.clone();
+    let events_arc = events.inner.clone();
 
     let mut mesh_graph_handle = mesh_graph_state.inner.lock().map_err(|e| e.to_string())?;
 
+    // Fields that aren't passed keep their current value
+    let mut policy = mesh_graph_handle.timeout_policy();
+
+    if let Some(secs) = node_timeout_secs {
+        policy.node_timeout = bounded_timeout("Node timeout", secs)?;
+    }
+
+    if let Some(secs) = edge_timeout_secs {
+        policy.edge_timeout = bounded_timeout("Edge timeout", secs)?;
+    }
+
+    if let Some(multiplier) = broadcast_interval_multiplier {
+        if !multiplier.is_finite()
+            || multiplier <= 0.0
+            || multiplier > MAX_BROADCAST_INTERVAL_MULTIPLIER
+        {
+            return Err(format!(
+                "Broadcast interval multiplier must be positive and at most {}",
+                MAX_BROADCAST_INTERVAL_MULTIPLIER
+            )
+            .into());
+        }
+
+        policy.broadcast_interval_multiplier = multiplier;
+    }
+
+    if let Some(secs) = clean_period_secs {
+        if secs == 0 {
+            return Err("Clean period must be at least one second".into());
+        }
+
+        policy.clean_period = bounded_timeout("Clean period", secs)?;
+    }
+
+    mesh_graph_handle.set_timeout_policy(policy);
+    debug!("Graph timeout policy set to {:?}", policy);
+
     if mesh_graph_handle.timeout_handle.is_some() {
         info!("Graph timeout handler already initialized");
-        return Ok(InitializeTimeoutHandlerResponse {});
+        return Ok(InitializeTimeoutHandlerResponse { policy });
     }
 
     let handle = tauri::async_runtime::spawn(async move {
-        info!(
-            "Starting graph timeout handler, sleeping for {:?} seconds",
-            DEFAULT_GRAPH_CLEAN_SECONDS
-        );
-
-        let app_handle = app_handle;
+        info!("Starting graph timeout handler");
 
         loop {
-            tokio::time::sleep(Duration::from_secs(DEFAULT_GRAPH_CLEAN_SECONDS)).await;
+            // Read on every iteration so that policy changes apply to the running handler
+            let clean_period = match mesh_graph_arc.lock() {
+                Ok(handle) => handle.timeout_policy().clean_period,
+                Err(e) => {
+                    log::error!("Error getting graph handle: {}", e);
+                    break;
+                }
+            };
+
+            tokio::time::sleep(clean_period).await;
 
             debug!("Cleaning graph...");
 
@@ -65,14 +149,14 @@ pub async fn handle_initialize_timeout_handler(
 
                 mesh_graph_handle.clean();
 
-                dispatch_updated_graph(&app_handle, mesh_graph_handle.clone())
-                    .expect("Error dispatching updated graph event");
+                // A failed dispatch mustn't stop the graph from being pruned
+                let deltas = mesh_graph_handle.take_deltas();
+                if let Err(e) = dispatch_graph_deltas(&events_arc, deltas) {
+                    warn!("Error dispatching graph delta event: {}", e);
+                }
             }
 
-            debug!(
-                "Graph cleaned, sleeping for {:?} seconds",
-                DEFAULT_GRAPH_CLEAN_SECONDS
-            );
+            debug!("Graph cleaned");
         }
 
         error!("Graph timeout handler stopped");
@@ -80,13 +164,13 @@ pub async fn handle_initialize_timeout_handler(
 
     mesh_graph_handle.timeout_handle = Some(handle);
 
-    let response = InitializeTimeoutHandlerResponse {};
+    let response = InitializeTimeoutHandlerResponse { policy };
     Ok(response)
 }
 
 pub async fn handle_stop_timeout_handler(
     _request: StopTimeoutHandlerRequest,
-    mesh_graph: tauri::State<'_, state::graph::GraphState>,
+    mesh_graph: &state::graph::GraphState,
 ) -> Result<StopTimeoutHandlerResponse, CommandError> {
     debug!("Called handle_stop_timeout_handler");
 
@@ -101,3 +185,192 @@ pub async fn handle_stop_timeout_handler(
     let response = StopTimeoutHandlerResponse {};
     Ok(response)
 }
+
+pub async fn handle_run_graph_analysis(
+    _request: RunGraphAnalysisRequest,
+    mesh_graph: &state::graph::GraphState,
+) -> Result<RunGraphAnalysisResponse, CommandError> {
+    debug!("Called handle_run_graph_analysis");
+
+    // Analyze a copy so that packet handlers aren't blocked on large graphs
+    let graph = {
+        let mesh_graph_handle = mesh_graph.inner.lock().map_err(|e| e.to_string())?;
+        mesh_graph_handle.internal_graph().clone()
+    };
+
+    let min_cut = global_min_cut(&graph);
+
+    if let Some(cut) = min_cut.as_ref() {
+        debug!(
+            "Minimum cut of {} links separates nodes {:?}",
+            cut.weight, cut.partition
+        );
+    }
+
+    let results = APMincutStringResults {
+        ap_result: articulation_points(&graph),
+        mincut_result: min_cut.map(|cut| cut.edges).unwrap_or_default(),
+        diffcen_result: diffusion_centrality(
+            &graph,
+            &DIFFUSION_HORIZONS,
+            &DIFFUSION_PROBABILITIES_PERCENT,
+        ),
+        betweenness_result: betweenness_centrality(&graph),
+    };
+
+    debug!("Found {} articulation points", results.ap_result.len());
+
+    let response = RunGraphAnalysisResponse { results };
+    Ok(response)
+}
+
+pub async fn handle_get_best_path(
+    request: GetBestPathRequest,
+    mesh_graph: &state::graph::GraphState,
+) -> Result<GetBestPathResponse, CommandError> {
+    let GetBestPathRequest { from, to } = request;
+    debug!("Called handle_get_best_path from {} to {}", from, to);
+
+    let mesh_graph_handle = mesh_graph.inner.lock().map_err(|e| e.to_string())?;
+    let route = best_path(mesh_graph_handle.internal_graph(), from, to);
+
+    let response = GetBestPathResponse { route };
+    Ok(response)
+}
+
+pub async fn handle_get_route_quality(
+    request: GetRouteQualityRequest,
+    mesh_graph: &state::graph::GraphState,
+) -> Result<GetRouteQualityResponse, CommandError> {
+    let GetRouteQualityRequest { path } = request;
+    debug!("Called handle_get_route_quality with path {:?}", path);
+
+    if path.len() < 2 {
+        return Err("Route must contain at least two nodes".into());
+    }
+
+    let mesh_graph_handle = mesh_graph.inner.lock().map_err(|e| e.to_string())?;
+    let route = route_quality(mesh_graph_handle.internal_graph(), &path);
+
+    let response = GetRouteQualityResponse { route };
+    Ok(response)
+}
+
+pub async fn handle_export_graph(
+    request: ExportGraphRequest,
+    mesh_graph: &state::graph::GraphState,
+) -> Result<ExportGraphResponse, CommandError> {
+    let ExportGraphRequest { format, file_path } = request;
+    debug!(
+        "Called handle_export_graph with format {:?} and path \"{}\"",
+        format, file_path
+    );
+
+    let (contents, node_count, edge_count) = {
+        let mesh_graph_handle = mesh_graph.inner.lock().map_err(|e| e.to_string())?;
+
+        let contents = match format {
+            GraphExportFormat::GraphMl => graph_graphml(&mesh_graph_handle),
+            GraphExportFormat::Dot => graph_dot(&mesh_graph_handle),
+            GraphExportFormat::GeoJson => graph_geojson(&mesh_graph_handle).to_string(),
+        };
+
+        let internal_graph = mesh_graph_handle.internal_graph();
+        (
+            contents,
+            internal_graph.node_count(),
+            internal_graph.edge_count(),
+        )
+    };
+
+    tokio::fs::write(&file_path, contents)
+        .await
+        .map_err(|e| format!("Failed to write \"{}\": {}", file_path, e))?;
+
+    info!(
+        "Exported graph with {} nodes and {} links to \"{}\"",
+        node_count, edge_count, file_path
+    );
+
+    let response = ExportGraphResponse {
+        node_count: node_count.try_into().unwrap_or(u32::MAX),
+        edge_count: edge_count.try_into().unwrap_or(u32::MAX),
+    };
+    Ok(response)
+}
+
+pub async fn handle_list_graph_snapshots(
+    _request: ListGraphSnapshotsRequest,
+    mesh_graph: &state::graph::GraphState,
+) -> Result<ListGraphSnapshotsResponse, CommandError> {
+    debug!("Called handle_list_graph_snapshots");
+
+    let mesh_graph_handle = mesh_graph.inner.lock().map_err(|e| e.to_string())?;
+    let timestamps = mesh_graph_handle.history.timestamps();
+
+    let response = ListGraphSnapshotsResponse { timestamps };
+    Ok(response)
+}
+
+pub async fn handle_get_graph_snapshot(
+    request: GetGraphSnapshotRequest,
+    mesh_graph: &state::graph::GraphState,
+) -> Result<GetGraphSnapshotResponse, CommandError> {
+    let GetGraphSnapshotRequest { timestamp } = request;
+    debug!("Called handle_get_graph_snapshot at {}", timestamp);
+
+    let mesh_graph_handle = mesh_graph.inner.lock().map_err(|e| e.to_string())?;
+    let snapshot = mesh_graph_handle.history.at(timestamp);
+
+    let response = GetGraphSnapshotResponse { snapshot };
+    Ok(response)
+}
+
+pub async fn handle_get_topology_diff(
+    request: GetTopologyDiffRequest,
+    mesh_graph: &state::graph::GraphState,
+) -> Result<GetTopologyDiffResponse, CommandError> {
+    let GetTopologyDiffRequest { from, to } = request;
+    debug!("Called handle_get_topology_diff from {} to {}", from, to);
+
+    if from > to {
+        return Err("Start of the range must not be after its end".into());
+    }
+
+    let mesh_graph_handle = mesh_graph.inner.lock().map_err(|e| e.to_string())?;
+    let diff = mesh_graph_handle.history.diff(from, to);
+
+    let response = GetTopologyDiffResponse { diff };
+    Ok(response)
+}
+
+pub async fn handle_get_observed_topology(
+    request: GetObservedTopologyRequest,
+    mesh_graph: &state::graph::GraphState,
+) -> Result<GetObservedTopologyResponse, CommandError> {
+    let GetObservedTopologyRequest { observer } = request;
+    debug!(
+        "Called handle_get_observed_topology with observer {:?}",
+        observer
+    );
+
+    let mesh_graph_handle = mesh_graph.inner.lock().map_err(|e| e.to_string())?;
+    let topology = mesh_graph_handle.observed_topology(observer.as_deref());
+
+    let response = GetObservedTopologyResponse { topology };
+    Ok(response)
+}
+
+pub async fn handle_set_infer_neighbor_nodes(
+    request: SetInferNeighborNodesRequest,
+    mesh_graph: &state::graph::GraphState,
+) -> Result<SetInferNeighborNodesResponse, CommandError> {
+    let SetInferNeighborNodesRequest { enabled } = request;
+    debug!("Called handle_set_infer_neighbor_nodes with {}", enabled);
+
+    let mut mesh_graph_handle = mesh_graph.inner.lock().map_err(|e| e.to_string())?;
+    mesh_graph_handle.set_infer_neighbor_nodes(enabled);
+
+    let response = SetInferNeighborNodesResponse {};
+    Ok(response)
+}
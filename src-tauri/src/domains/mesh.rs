@@ -1,7 +1,11 @@
 use crate::api::contracts::mesh::DeleteWaypointRequest;
 use crate::api::contracts::mesh::DeleteWaypointResponse;
+use crate::api::contracts::mesh::RequestStoreForwardHistoryRequest;
+use crate::api::contracts::mesh::RequestStoreForwardHistoryResponse;
 use crate::api::contracts::mesh::SendTextRequest;
 use crate::api::contracts::mesh::SendTextResponse;
+use crate::api::contracts::mesh::SendTracerouteRequest;
+use crate::api::contracts::mesh::SendTracerouteResponse;
 use crate::api::contracts::mesh::SendWaypointRequest;
 use crate::api::contracts::mesh::SendWaypointResponse;
 use crate::device::helpers::convert_location_field_to_protos;
@@ -9,17 +13,18 @@ use crate::device::NormalizedWaypoint;
 use crate::ipc::events;
 use crate::ipc::CommandError;
 use crate::state::{self, DeviceKey};
+use crate::storage::HistoryRecord;
 
 use log::{debug, trace};
 use meshtastic::packet::PacketDestination;
 use meshtastic::protobufs;
-use meshtastic::types::MeshChannel;
+use meshtastic::types::{EncodedMeshPacketData, MeshChannel, NodeId};
+use meshtastic::Message;
 
 pub async fn handle_send_text(
     request: SendTextRequest,
-    app_handle: tauri::AppHandle,
-    mesh_devices: tauri::State<'_, state::mesh_devices::MeshDevicesState>,
-    radio_connections: tauri::State<'_, state::radio_connections::RadioConnectionsState>,
+    mesh_devices: &state::mesh_devices::MeshDevicesState,
+    radio_connections: &state::radio_connections::RadioConnectionsState,
 ) -> Result<SendTextResponse, CommandError> {
     let SendTextRequest {
         device_key,
@@ -49,7 +54,8 @@ pub async fn handle_send_text(
         .await
         .map_err(|e| e.to_string())?;
 
-    events::dispatch_updated_device(&app_handle, &packet_api.device).map_err(|e| e.to_string())?;
+    events::dispatch_updated_device(&packet_api.events, &packet_api.device)
+        .map_err(|e| e.to_string())?;
 
     let response = SendTextResponse {};
     Ok(response)
@@ -57,9 +63,8 @@ pub async fn handle_send_text(
 
 pub async fn handle_send_waypoint(
     request: SendWaypointRequest,
-    app_handle: tauri::AppHandle,
-    mesh_devices: tauri::State<'_, state::mesh_devices::MeshDevicesState>,
-    radio_connections: tauri::State<'_, state::radio_connections::RadioConnectionsState>,
+    mesh_devices: &state::mesh_devices::MeshDevicesState,
+    radio_connections: &state::radio_connections::RadioConnectionsState,
 ) -> Result<SendWaypointResponse, CommandError> {
     let SendWaypointRequest {
         device_key,
@@ -101,7 +106,8 @@ pub async fn handle_send_waypoint(
         .await
         .map_err(|e| e.to_string())?;
 
-    events::dispatch_updated_device(&app_handle, &packet_api.device).map_err(|e| e.to_string())?;
+    events::dispatch_updated_device(&packet_api.events, &packet_api.device)
+        .map_err(|e| e.to_string())?;
 
     let response = SendWaypointResponse {};
     Ok(response)
@@ -109,8 +115,7 @@ pub async fn handle_send_waypoint(
 
 pub async fn handle_delete_waypoint(
     request: DeleteWaypointRequest,
-    app_handle: tauri::AppHandle,
-    mesh_devices: tauri::State<'_, state::mesh_devices::MeshDevicesState>,
+    mesh_devices: &state::mesh_devices::MeshDevicesState,
 ) -> Result<DeleteWaypointResponse, CommandError> {
     let DeleteWaypointRequest {
         device_key,
@@ -124,10 +129,145 @@ pub async fn handle_delete_waypoint(
 
     if packet_api.device.waypoints.contains_key(&waypoint_id) {
         let _removed_waypoint = packet_api.device.waypoints.remove(&waypoint_id);
+        packet_api.record_history(HistoryRecord::WaypointDeleted { waypoint_id });
     }
 
-    events::dispatch_updated_device(&app_handle, &packet_api.device).map_err(|e| e.to_string())?;
+    events::dispatch_updated_device(&packet_api.events, &packet_api.device)
+        .map_err(|e| e.to_string())?;
 
     let response = DeleteWaypointResponse {};
     Ok(response)
 }
+
+pub async fn handle_send_traceroute(
+    request: SendTracerouteRequest,
+    mesh_devices: &state::mesh_devices::MeshDevicesState,
+    radio_connections: &state::radio_connections::RadioConnectionsState,
+) -> Result<SendTracerouteResponse, CommandError> {
+    let SendTracerouteRequest {
+        device_key,
+        destination,
+        channel,
+    } = request;
+    trace!(
+        "Called with destination {} on channel {}",
+        destination,
+        channel
+    );
+
+    let mut devices_guard = mesh_devices.inner.lock().await;
+    let packet_api = devices_guard
+        .get_mut(&device_key)
+        .ok_or("Device not connected")?;
+
+    if destination == packet_api.device.my_node_info.my_node_num {
+        return Err("Cannot send a traceroute to the connected node".into());
+    }
+
+    let mut connections_guard = radio_connections.inner.lock().await;
+    let connection = connections_guard
+        .get_mut(&device_key)
+        .ok_or("Radio connection not initialized")?;
+
+    // Intermediate nodes append themselves to the route as the request is relayed,
+    // so the request itself is sent with an empty route
+    let route_discovery = protobufs::RouteDiscovery::default();
+
+    connection
+        .send_mesh_packet(
+            packet_api,
+            EncodedMeshPacketData::new(route_discovery.encode_to_vec()),
+            protobufs::PortNum::TracerouteApp,
+            PacketDestination::Node(NodeId::new(destination)),
+            MeshChannel::new(channel).map_err(|e| e.to_string())?,
+            true,
+            true,
+            false,
+            None,
+            None,
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+    debug!("Sent traceroute request to node {}", destination);
+
+    let response = SendTracerouteResponse {};
+    Ok(response)
+}
+
+pub async fn handle_request_store_forward_history(
+    request: RequestStoreForwardHistoryRequest,
+    mesh_devices: &state::mesh_devices::MeshDevicesState,
+    radio_connections: &state::radio_connections::RadioConnectionsState,
+) -> Result<RequestStoreForwardHistoryResponse, CommandError> {
+    let RequestStoreForwardHistoryRequest {
+        device_key,
+        server,
+        channel,
+        window_minutes,
+        max_messages,
+    } = request;
+    trace!(
+        "Called with server {:?} on channel {} for {:?} minutes",
+        server,
+        channel,
+        window_minutes
+    );
+
+    let mut devices_guard = mesh_devices.inner.lock().await;
+    let packet_api = devices_guard
+        .get_mut(&device_key)
+        .ok_or("Device not connected")?;
+
+    let server = match server {
+        Some(server) => server,
+        None => packet_api
+            .device
+            .store_forward_servers
+            .values()
+            .max_by_key(|s| s.last_heard)
+            .map(|s| s.node_num)
+            .ok_or("No store-and-forward routers have been heard")?,
+    };
+
+    let mut connections_guard = radio_connections.inner.lock().await;
+    let connection = connections_guard
+        .get_mut(&device_key)
+        .ok_or("Radio connection not initialized")?;
+
+    // Routers fall back to their configured limits for fields left at zero
+    let store_and_forward = protobufs::StoreAndForward {
+        rr: protobufs::store_and_forward::RequestResponse::ClientHistory as i32,
+        variant: Some(protobufs::store_and_forward::Variant::History(
+            protobufs::store_and_forward::History {
+                history_messages: max_messages.unwrap_or_default(),
+                window: window_minutes.unwrap_or_default(),
+                last_request: 0,
+            },
+        )),
+    };
+
+    connection
+        .send_mesh_packet(
+            packet_api,
+            EncodedMeshPacketData::new(store_and_forward.encode_to_vec()),
+            protobufs::PortNum::StoreForwardApp,
+            PacketDestination::Node(NodeId::new(server)),
+            MeshChannel::new(channel).map_err(|e| e.to_string())?,
+            true,
+            false,
+            false,
+            None,
+            None,
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+    debug!(
+        "Requested message history from store-and-forward router {}",
+        server
+    );
+
+    let response = RequestStoreForwardHistoryResponse { server };
+    Ok(response)
+}
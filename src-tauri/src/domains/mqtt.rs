@@ -0,0 +1,58 @@
+use log::{debug, info};
+
+use crate::api::contracts::mqtt::{
+    StartMqttBridgeRequest, StartMqttBridgeResponse, StopMqttBridgeRequest, StopMqttBridgeResponse,
+};
+use crate::ipc::CommandError;
+use crate::mqtt::bridge::spawn_mqtt_bridge;
+use crate::state;
+
+pub async fn handle_start_mqtt_bridge(
+    request: StartMqttBridgeRequest,
+    events: &state::events::EventsState,
+    mesh_devices: &state::mesh_devices::MeshDevicesState,
+    radio_connections: &state::radio_connections::RadioConnectionsState,
+    mqtt_bridge: &state::mqtt::MqttBridgeState,
+) -> Result<StartMqttBridgeResponse, CommandError> {
+    let StartMqttBridgeRequest { config } = request;
+    debug!("Called handle_start_mqtt_bridge with config {:?}", config);
+
+    if config.host.is_empty() {
+        return Err("MQTT broker host must not be empty".into());
+    }
+
+    let mut bridge_guard = mqtt_bridge.inner.lock().await;
+
+    // Restart the bridge so that configuration changes take effect
+    if let Some(handle) = bridge_guard.take() {
+        info!("Stopping running MQTT bridge");
+        handle.abort();
+    }
+
+    let handle = spawn_mqtt_bridge(
+        config,
+        events.packets.clone(),
+        mesh_devices.clone(),
+        radio_connections.clone(),
+    );
+
+    *bridge_guard = Some(handle);
+
+    let response = StartMqttBridgeResponse {};
+    Ok(response)
+}
+
+pub async fn handle_stop_mqtt_bridge(
+    _request: StopMqttBridgeRequest,
+    mqtt_bridge: &state::mqtt::MqttBridgeState,
+) -> Result<StopMqttBridgeResponse, CommandError> {
+    debug!("Called handle_stop_mqtt_bridge");
+
+    if let Some(handle) = mqtt_bridge.inner.lock().await.take() {
+        info!("Stopping MQTT bridge");
+        handle.abort();
+    }
+
+    let response = StopMqttBridgeResponse {};
+    Ok(response)
+}
@@ -0,0 +1,104 @@
+use log::{debug, info};
+
+use crate::api::contracts::nodes::{
+    ExportNodesRequest, ExportNodesResponse, ImportNodeRosterRequest, ImportNodeRosterResponse,
+};
+use crate::api::primitives::nodes::NodeDatabaseFormat;
+use crate::export::nodes::{
+    node_records, nodes_csv, nodes_json, roster_from_csv, roster_from_json,
+};
+use crate::ipc::events;
+use crate::ipc::CommandError;
+use crate::state;
+use crate::storage::HistoryRecord;
+
+pub async fn handle_export_nodes(
+    request: ExportNodesRequest,
+    mesh_devices: &state::mesh_devices::MeshDevicesState,
+) -> Result<ExportNodesResponse, CommandError> {
+    let ExportNodesRequest {
+        device_key,
+        format,
+        file_path,
+    } = request;
+    debug!(
+        "Called handle_export_nodes with format {:?} and path \"{}\"",
+        format, file_path
+    );
+
+    let (contents, node_count) = {
+        let devices_guard = mesh_devices.inner.lock().await;
+        let packet_api = devices_guard
+            .get(&device_key)
+            .ok_or("Device not connected")?;
+
+        let records = node_records(&packet_api.device);
+
+        let contents = match format {
+            NodeDatabaseFormat::Csv => nodes_csv(&records),
+            NodeDatabaseFormat::Json => nodes_json(&records).map_err(|e| e.to_string())?,
+        };
+
+        (contents, records.len())
+    };
+
+    tokio::fs::write(&file_path, contents)
+        .await
+        .map_err(|e| format!("Failed to write \"{}\": {}", file_path, e))?;
+
+    info!("Exported {} nodes to \"{}\"", node_count, file_path);
+
+    let response = ExportNodesResponse {
+        node_count: node_count.try_into().unwrap_or(u32::MAX),
+    };
+    Ok(response)
+}
+
+pub async fn handle_import_node_roster(
+    request: ImportNodeRosterRequest,
+    mesh_devices: &state::mesh_devices::MeshDevicesState,
+) -> Result<ImportNodeRosterResponse, CommandError> {
+    let ImportNodeRosterRequest {
+        device_key,
+        format,
+        file_path,
+    } = request;
+    debug!(
+        "Called handle_import_node_roster with format {:?} and path \"{}\"",
+        format, file_path
+    );
+
+    let contents = tokio::fs::read_to_string(&file_path)
+        .await
+        .map_err(|e| format!("Failed to read \"{}\": {}", file_path, e))?;
+
+    let entries = match format {
+        NodeDatabaseFormat::Csv => roster_from_csv(&contents)?,
+        NodeDatabaseFormat::Json => roster_from_json(&contents)?,
+    };
+
+    let mut devices_guard = mesh_devices.inner.lock().await;
+    let packet_api = devices_guard
+        .get_mut(&device_key)
+        .ok_or("Device not connected")?;
+
+    let node_nums: Vec<u32> = entries.iter().map(|entry| entry.node_num).collect();
+    let annotation_count = packet_api.device.import_roster(entries);
+
+    for node_num in node_nums {
+        if let Some(annotation) = packet_api.device.roster.get(&node_num).cloned() {
+            packet_api.record_history(HistoryRecord::Annotation {
+                node_num,
+                annotation,
+            });
+        }
+    }
+
+    events::dispatch_updated_device(&packet_api.events, &packet_api.device)
+        .map_err(|e| e.to_string())?;
+
+    let response = ImportNodeRosterResponse {
+        annotation_count: annotation_count.try_into().unwrap_or(u32::MAX),
+    };
+    Ok(response)
+}
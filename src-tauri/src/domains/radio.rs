@@ -11,8 +11,8 @@ use log::trace;
 
 pub async fn handle_update_device_config(
     request: UpdateDeviceConfigRequest,
-    mesh_devices: tauri::State<'_, state::mesh_devices::MeshDevicesState>,
-    radio_connections: tauri::State<'_, state::radio_connections::RadioConnectionsState>,
+    mesh_devices: &state::mesh_devices::MeshDevicesState,
+    radio_connections: &state::radio_connections::RadioConnectionsState,
 ) -> Result<UpdateDeviceConfigResponse, CommandError> {
     let UpdateDeviceConfigRequest { device_key, config } = request;
     trace!("Called with config {:?}", config);
@@ -38,8 +38,8 @@ pub async fn handle_update_device_config(
 
 pub async fn handle_update_device_user(
     request: UpdateDeviceUserRequest,
-    mesh_devices: tauri::State<'_, state::mesh_devices::MeshDevicesState>,
-    radio_connections: tauri::State<'_, state::radio_connections::RadioConnectionsState>,
+    mesh_devices: &state::mesh_devices::MeshDevicesState,
+    radio_connections: &state::radio_connections::RadioConnectionsState,
 ) -> Result<UpdateDeviceUserResponse, CommandError> {
     let UpdateDeviceUserRequest { device_key, user } = request;
     trace!("Called with user {:?}", user);
@@ -64,8 +64,8 @@ pub async fn handle_update_device_user(
 
 pub async fn handle_start_configuration_transaction(
     request: StartConfigurationTransactionRequest,
-    mesh_devices: tauri::State<'_, state::mesh_devices::MeshDevicesState>,
-    radio_connections: tauri::State<'_, state::radio_connections::RadioConnectionsState>,
+    mesh_devices: &state::mesh_devices::MeshDevicesState,
+    radio_connections: &state::radio_connections::RadioConnectionsState,
 ) -> Result<StartConfigurationTransactionResponse, CommandError> {
     let StartConfigurationTransactionRequest { device_key } = request;
 
@@ -96,8 +96,8 @@ pub async fn handle_start_configuration_transaction(
 
 pub async fn handle_commit_configuration_transaction(
     request: CommitConfigurationTransactionRequest,
-    mesh_devices: tauri::State<'_, state::mesh_devices::MeshDevicesState>,
-    radio_connections: tauri::State<'_, state::radio_connections::RadioConnectionsState>,
+    mesh_devices: &state::mesh_devices::MeshDevicesState,
+    radio_connections: &state::radio_connections::RadioConnectionsState,
 ) -> Result<CommitConfigurationTransactionResponse, CommandError> {
     let CommitConfigurationTransactionRequest { device_key } = request;
 
@@ -128,9 +128,8 @@ pub async fn handle_commit_configuration_transaction(
 
 pub async fn handle_update_device_config_bulk(
     request: UpdateDeviceConfigBulkRequest,
-    app_handle: tauri::AppHandle,
-    mesh_devices: tauri::State<'_, state::mesh_devices::MeshDevicesState>,
-    radio_connections: tauri::State<'_, state::radio_connections::RadioConnectionsState>,
+    mesh_devices: &state::mesh_devices::MeshDevicesState,
+    radio_connections: &state::radio_connections::RadioConnectionsState,
 ) -> Result<UpdateDeviceConfigBulkResponse, CommandError> {
     let UpdateDeviceConfigBulkRequest { device_key, config } = request;
 
@@ -175,7 +174,8 @@ pub async fn handle_update_device_config_bulk(
         .await
         .map_err(|e| e.to_string())?;
 
-    events::dispatch_updated_device(&app_handle, &packet_api.device).map_err(|e| e.to_string())?;
+    events::dispatch_updated_device(&packet_api.events, &packet_api.device)
+        .map_err(|e| e.to_string())?;
 
     let response = UpdateDeviceConfigBulkResponse {};
     Ok(response)
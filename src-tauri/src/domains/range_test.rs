@@ -0,0 +1,102 @@
+use log::{debug, info};
+
+use crate::api::contracts::range_test::{
+    ClearRangeTestRequest, ClearRangeTestResponse, ExportRangeTestRequest, ExportRangeTestResponse,
+    GetRangeTestRecordsRequest, GetRangeTestRecordsResponse,
+};
+use crate::api::primitives::range_test::RangeTestExportFormat;
+use crate::export::range_test::{range_test_csv, range_test_geojson};
+use crate::ipc::CommandError;
+use crate::state;
+
+pub async fn handle_get_range_test_records(
+    request: GetRangeTestRecordsRequest,
+    mesh_devices: &state::mesh_devices::MeshDevicesState,
+) -> Result<GetRangeTestRecordsResponse, CommandError> {
+    let GetRangeTestRecordsRequest { device_key, since } = request;
+    debug!("Called handle_get_range_test_records since {:?}", since);
+
+    let devices_guard = mesh_devices.inner.lock().await;
+    let packet_api = devices_guard
+        .get(&device_key)
+        .ok_or("Device not connected")?;
+
+    let records = packet_api
+        .device
+        .range_test_records
+        .iter()
+        .filter(|record| match since {
+            Some(since) => record.timestamp > since,
+            None => true,
+        })
+        .cloned()
+        .collect();
+
+    let response = GetRangeTestRecordsResponse { records };
+    Ok(response)
+}
+
+pub async fn handle_export_range_test(
+    request: ExportRangeTestRequest,
+    mesh_devices: &state::mesh_devices::MeshDevicesState,
+) -> Result<ExportRangeTestResponse, CommandError> {
+    let ExportRangeTestRequest {
+        device_key,
+        format,
+        file_path,
+    } = request;
+    debug!(
+        "Called handle_export_range_test with format {:?} and path \"{}\"",
+        format, file_path
+    );
+
+    let (contents, record_count) = {
+        let devices_guard = mesh_devices.inner.lock().await;
+        let packet_api = devices_guard
+            .get(&device_key)
+            .ok_or("Device not connected")?;
+
+        let records = &packet_api.device.range_test_records;
+
+        match format {
+            RangeTestExportFormat::Csv => (range_test_csv(records), records.len()),
+            RangeTestExportFormat::GeoJson => {
+                let collection = range_test_geojson(records);
+                let record_count = collection.features.len();
+                (collection.to_string(), record_count)
+            }
+        }
+    };
+
+    tokio::fs::write(&file_path, contents)
+        .await
+        .map_err(|e| format!("Failed to write \"{}\": {}", file_path, e))?;
+
+    info!(
+        "Exported {} range test records to \"{}\"",
+        record_count, file_path
+    );
+
+    let response = ExportRangeTestResponse {
+        record_count: record_count.try_into().unwrap_or(u32::MAX),
+    };
+    Ok(response)
+}
+
+pub async fn handle_clear_range_test(
+    request: ClearRangeTestRequest,
+    mesh_devices: &state::mesh_devices::MeshDevicesState,
+) -> Result<ClearRangeTestResponse, CommandError> {
+    let ClearRangeTestRequest { device_key } = request;
+    debug!("Called handle_clear_range_test");
+
+    let mut devices_guard = mesh_devices.inner.lock().await;
+    let packet_api = devices_guard
+        .get_mut(&device_key)
+        .ok_or("Device not connected")?;
+
+    packet_api.device.range_test_records.clear();
+
+    let response = ClearRangeTestResponse {};
+    Ok(response)
+}
@@ -0,0 +1,80 @@
+use log::debug;
+
+use crate::api::contracts::telemetry::{
+    GetNodeTelemetryHistoryRequest, GetNodeTelemetryHistoryResponse, SetTelemetryRetentionRequest,
+    SetTelemetryRetentionResponse,
+};
+use crate::api::primitives::telemetry::TelemetryRetentionPolicy;
+use crate::ipc::events;
+use crate::ipc::CommandError;
+use crate::state;
+
+pub async fn handle_get_node_telemetry_history(
+    request: GetNodeTelemetryHistoryRequest,
+    mesh_devices: &state::mesh_devices::MeshDevicesState,
+) -> Result<GetNodeTelemetryHistoryResponse, CommandError> {
+    let GetNodeTelemetryHistoryRequest {
+        device_key,
+        node_num,
+        start,
+        end,
+    } = request;
+    debug!(
+        "Called handle_get_node_telemetry_history for node {} between {:?} and {:?}",
+        node_num, start, end
+    );
+
+    let devices_guard = mesh_devices.inner.lock().await;
+    let packet_api = devices_guard
+        .get(&device_key)
+        .ok_or("Device not connected")?;
+
+    let node = packet_api
+        .device
+        .nodes
+        .get(&node_num)
+        .ok_or(format!("Node {} not found", node_num))?;
+
+    let response = GetNodeTelemetryHistoryResponse {
+        history: node.telemetry_history(start, end),
+    };
+    Ok(response)
+}
+
+pub async fn handle_set_telemetry_retention(
+    request: SetTelemetryRetentionRequest,
+    mesh_devices: &state::mesh_devices::MeshDevicesState,
+) -> Result<SetTelemetryRetentionResponse, CommandError> {
+    let SetTelemetryRetentionRequest {
+        device_key,
+        max_samples,
+        full_resolution_secs,
+        downsample_interval_secs,
+    } = request;
+    debug!("Called handle_set_telemetry_retention");
+
+    if max_samples == Some(0) {
+        return Err("At least one telemetry sample must be kept".into());
+    }
+
+    let mut devices_guard = mesh_devices.inner.lock().await;
+    let packet_api = devices_guard
+        .get_mut(&device_key)
+        .ok_or("Device not connected")?;
+
+    let current = packet_api.device.telemetry_retention;
+    let policy = TelemetryRetentionPolicy {
+        max_samples: max_samples.unwrap_or(current.max_samples),
+        full_resolution_secs: full_resolution_secs.unwrap_or(current.full_resolution_secs),
+        downsample_interval_secs: downsample_interval_secs
+            .unwrap_or(current.downsample_interval_secs),
+    };
+
+    packet_api.device.set_telemetry_retention(policy);
+
+    events::dispatch_updated_device(&packet_api.events, &packet_api.device)
+        .map_err(|e| e.to_string())?;
+
+    let response = SetTelemetryRetentionResponse { policy };
+    Ok(response)
+}
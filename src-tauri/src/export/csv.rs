@@ -0,0 +1,107 @@
+/// Builds a CSV document with the passed header row, quoting fields as
+/// described in RFC 4180
+pub fn to_csv<I>(header: &[&str], rows: I) -> String
+where
+    I: IntoIterator<Item = Vec<String>>,
+{
+    let mut csv = format_row(header.iter().map(|h| h.to_string()));
+
+    for row in rows {
+        csv.push_str(&format_row(row));
+    }
+
+    csv
+}
+
+/// Formats an optional value as a CSV field, leaving unknown values empty
+pub fn optional_field<T: ToString>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+/// Parses a CSV document into its rows, unquoting fields as described in
+/// RFC 4180. Both CRLF and LF line endings are accepted.
+pub fn parse_csv(contents: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut rows = vec![];
+    let mut row = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = contents.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            (true, '"') => quoted = false,
+            (true, c) => field.push(c),
+            (false, '"') if field.is_empty() => quoted = true,
+            (false, ',') => row.push(std::mem::take(&mut field)),
+            (false, '\r') if chars.peek() == Some(&'\n') => {}
+            (false, '\n') => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            (false, c) => field.push(c),
+        }
+    }
+
+    if quoted {
+        return Err("Unterminated quoted field".into());
+    }
+
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+
+    Ok(rows)
+}
+
+fn format_row<I: IntoIterator<Item = String>>(fields: I) -> String {
+    let mut row = fields
+        .into_iter()
+        .map(|field| escape_field(&field))
+        .collect::<Vec<_>>()
+        .join(",");
+
+    row.push_str("\r\n");
+    row
+}
+
+fn escape_field(field: &str) -> String {
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_fields_with_separators() {
+        let csv = to_csv(
+            &["name", "notes"],
+            vec![vec!["Base, north".into(), "says \"hi\"".into()]],
+        );
+
+        assert_eq!(csv, "name,notes\r\n\"Base, north\",\"says \"\"hi\"\"\"\r\n");
+    }
+
+    #[test]
+    fn parses_quoted_fields() {
+        let rows = parse_csv("name,notes\r\n\"Base, north\",\"says \"\"hi\"\"\"\nlast,\n").unwrap();
+
+        assert_eq!(
+            rows,
+            vec![
+                vec!["name".to_string(), "notes".into()],
+                vec!["Base, north".into(), "says \"hi\"".into()],
+                vec!["last".into(), "".into()],
+            ]
+        );
+    }
+}
@@ -0,0 +1,273 @@
+use std::fmt::{Display, Write};
+
+use geojson::{Feature, FeatureCollection, Geometry, JsonObject, Value};
+use serde_json::json;
+
+use crate::device::helpers::format_node_id;
+use crate::graph::ds::{edge::GraphEdge, graph::MeshGraph, node::GraphNode};
+
+/// Attributes declared for nodes and links, as `(id, element, name, type)`
+const GRAPHML_KEYS: [(&str, &str, &str, &str); 10] = [
+    ("label", "node", "label", "string"),
+    ("inferred", "node", "inferred", "boolean"),
+    ("n_last_heard", "node", "lastHeard", "string"),
+    ("latitude", "node", "latitude", "double"),
+    ("longitude", "node", "longitude", "double"),
+    ("altitude", "node", "altitude", "int"),
+    ("snr", "edge", "snr", "double"),
+    ("e_last_heard", "edge", "lastHeard", "string"),
+    ("distance", "edge", "distanceMeters", "double"),
+    ("bearing", "edge", "bearingDegrees", "double"),
+];
+
+/// Builds a GraphML document of the graph for tools such as Gephi and yEd.
+/// Unknown attributes are omitted rather than written as empty values.
+pub fn graph_graphml(graph: &MeshGraph) -> String {
+    let mut xml = String::new();
+
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+
+    for (id, element, name, value_type) in GRAPHML_KEYS {
+        let _ = writeln!(
+            xml,
+            "  <key id=\"{}\" for=\"{}\" attr.name=\"{}\" attr.type=\"{}\"/>",
+            id, element, name, value_type
+        );
+    }
+
+    xml.push_str("  <graph id=\"mesh\" edgedefault=\"directed\">\n");
+
+    for node in sorted_nodes(graph) {
+        let position = node.position.as_ref();
+
+        let _ = writeln!(xml, "    <node id=\"n{}\">", node.node_num);
+        write_graphml_data(&mut xml, "label", Some(format_node_id(node.node_num)));
+        write_graphml_data(&mut xml, "inferred", Some(node.inferred));
+        write_graphml_data(&mut xml, "n_last_heard", Some(node.last_heard.to_rfc3339()));
+        write_graphml_data(&mut xml, "latitude", position.map(|p| p.latitude));
+        write_graphml_data(&mut xml, "longitude", position.map(|p| p.longitude));
+        write_graphml_data(&mut xml, "altitude", position.and_then(|p| p.altitude));
+        xml.push_str("    </node>\n");
+    }
+
+    for (source, target, edge) in sorted_edges(graph) {
+        let _ = writeln!(
+            xml,
+            "    <edge source=\"n{}\" target=\"n{}\">",
+            source.node_num, target.node_num
+        );
+        write_graphml_data(&mut xml, "snr", Some(edge.snr()));
+        write_graphml_data(&mut xml, "e_last_heard", Some(edge.last_heard.to_rfc3339()));
+        write_graphml_data(&mut xml, "distance", edge.distance_meters);
+        write_graphml_data(&mut xml, "bearing", edge.bearing_degrees);
+        xml.push_str("    </edge>\n");
+    }
+
+    xml.push_str("  </graph>\n");
+    xml.push_str("</graphml>\n");
+
+    xml
+}
+
+/// Builds a Graphviz DOT document of the graph, labelling links with their SNR
+pub fn graph_dot(graph: &MeshGraph) -> String {
+    let mut dot = String::from("digraph mesh {\n");
+
+    for node in sorted_nodes(graph) {
+        // Inferred nodes haven't been heard from directly
+        let style = if node.inferred { ", style=dashed" } else { "" };
+
+        let _ = writeln!(
+            dot,
+            "  {} [label=\"{}\", last_heard=\"{}\"{}];",
+            node.node_num,
+            format_node_id(node.node_num),
+            node.last_heard.to_rfc3339(),
+            style
+        );
+    }
+
+    for (source, target, edge) in sorted_edges(graph) {
+        let _ = write!(
+            dot,
+            "  {} -> {} [label=\"{:.2} dB\", snr={}, last_heard=\"{}\"",
+            source.node_num,
+            target.node_num,
+            edge.snr(),
+            edge.snr(),
+            edge.last_heard.to_rfc3339()
+        );
+
+        if let Some(distance) = edge.distance_meters {
+            let _ = write!(dot, ", distance_meters={:.1}", distance);
+        }
+
+        dot.push_str("];\n");
+    }
+
+    dot.push_str("}\n");
+    dot
+}
+
+/// Builds a feature collection with a point for every node with a known
+/// position and a line string for every link between two such nodes
+pub fn graph_geojson(graph: &MeshGraph) -> FeatureCollection {
+    let mut features = vec![];
+
+    for node in sorted_nodes(graph) {
+        let Some(position) = node.position else {
+            continue;
+        };
+
+        let mut properties = JsonObject::new();
+        properties.insert("nodeNum".into(), json!(node.node_num));
+        properties.insert("nodeId".into(), json!(format_node_id(node.node_num)));
+        properties.insert("lastHeard".into(), json!(node.last_heard.to_rfc3339()));
+        properties.insert("altitude".into(), json!(position.altitude));
+        properties.insert("inferred".into(), json!(node.inferred));
+
+        features.push(feature(
+            Value::Point(vec![position.longitude, position.latitude]),
+            properties,
+        ));
+    }
+
+    for (source, target, edge) in sorted_edges(graph) {
+        let (Some(from), Some(to)) = (source.position, target.position) else {
+            continue;
+        };
+
+        let mut properties = JsonObject::new();
+        properties.insert("from".into(), json!(source.node_num));
+        properties.insert("to".into(), json!(target.node_num));
+        properties.insert("snr".into(), json!(edge.snr()));
+        properties.insert("lastHeard".into(), json!(edge.last_heard.to_rfc3339()));
+        properties.insert("distanceMeters".into(), json!(edge.distance_meters));
+        properties.insert("bearingDegrees".into(), json!(edge.bearing_degrees));
+
+        features.push(feature(
+            Value::LineString(vec![
+                vec![from.longitude, from.latitude],
+                vec![to.longitude, to.latitude],
+            ]),
+            properties,
+        ));
+    }
+
+    FeatureCollection {
+        bbox: None,
+        features,
+        foreign_members: None,
+    }
+}
+
+fn feature(value: Value, properties: JsonObject) -> Feature {
+    Feature {
+        bbox: None,
+        geometry: Some(Geometry::new(value)),
+        id: None,
+        properties: Some(properties),
+        foreign_members: None,
+    }
+}
+
+fn write_graphml_data<T: Display>(xml: &mut String, key: &str, value: Option<T>) {
+    if let Some(value) = value {
+        // Values are numbers, timestamps and node IDs, which never need escaping
+        let _ = writeln!(xml, "      <data key=\"{}\">{}</data>", key, value);
+    }
+}
+
+/// Nodes ordered by node number so that exports of the same graph are identical
+fn sorted_nodes(graph: &MeshGraph) -> Vec<GraphNode> {
+    let mut nodes: Vec<GraphNode> = graph.nodes_lookup.values().copied().collect();
+    nodes.sort();
+    nodes
+}
+
+/// Links ordered by their endpoints, with endpoints resolved to the latest
+/// version of each node
+fn sorted_edges(graph: &MeshGraph) -> Vec<(GraphNode, GraphNode, &GraphEdge)> {
+    let mut edges: Vec<(GraphNode, GraphNode, &GraphEdge)> = graph
+        .internal_graph()
+        .all_edges()
+        .map(|(source, target, edge)| {
+            (
+                graph.get_node(source.node_num).unwrap_or(source),
+                graph.get_node(target.node_num).unwrap_or(target),
+                edge,
+            )
+        })
+        .collect();
+
+    edges.sort_by_key(|(source, target, _)| (source.node_num, target.node_num));
+    edges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::ds::position::GraphPosition;
+
+    fn test_graph() -> MeshGraph {
+        let mut graph = MeshGraph::new();
+
+        let first = graph.upsert_node(GraphNode {
+            position: Some(GraphPosition {
+                latitude: 1.0,
+                longitude: 2.0,
+                altitude: Some(100),
+            }),
+            ..GraphNode::new(1)
+        });
+        let second = graph.upsert_node(GraphNode {
+            position: Some(GraphPosition {
+                latitude: 1.5,
+                longitude: 2.5,
+                altitude: None,
+            }),
+            ..GraphNode::new(2)
+        });
+        let third = graph.upsert_node(GraphNode::new(3));
+
+        graph.upsert_edge(first, second, GraphEdge::from_traceroute_hop(1, 2, 6.5));
+        graph.upsert_edge(second, third, GraphEdge::from_traceroute_hop(2, 3, -3.0));
+
+        graph
+    }
+
+    #[test]
+    fn exports_all_nodes_and_links_as_graphml() {
+        let xml = graph_graphml(&test_graph());
+
+        assert_eq!(xml.matches("<node id=").count(), 3);
+        assert_eq!(xml.matches("<edge source=").count(), 2);
+        assert!(xml.contains("<edge source=\"n1\" target=\"n2\">"));
+        assert!(xml.contains("<data key=\"snr\">6.5</data>"));
+        assert!(xml.contains("<data key=\"label\">!00000001</data>"));
+    }
+
+    #[test]
+    fn exports_links_as_dot() {
+        let dot = graph_dot(&test_graph());
+
+        assert!(dot.starts_with("digraph mesh {\n"));
+        assert!(dot.contains("  2 -> 3 [label=\"-3.00 dB\", snr=-3"));
+        assert!(dot.ends_with("}\n"));
+    }
+
+    #[test]
+    fn skips_nodes_and_links_without_positions_in_geojson() {
+        let collection = graph_geojson(&test_graph());
+
+        // Two positioned nodes and the single link between them
+        assert_eq!(collection.features.len(), 3);
+
+        let line = collection.features[2].geometry.as_ref().unwrap();
+        assert_eq!(
+            line.value,
+            Value::LineString(vec![vec![2.0, 1.0], vec![2.5, 1.5]])
+        );
+    }
+}
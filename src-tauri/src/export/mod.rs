@@ -0,0 +1,7 @@
+//! Serializes client state into formats used by external tools, such as
+//! spreadsheets and GIS software, and parses files shared from them.
+
+pub mod csv;
+pub mod graph;
+pub mod nodes;
+pub mod range_test;
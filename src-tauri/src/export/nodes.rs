@@ -0,0 +1,282 @@
+use meshtastic::protobufs;
+use serde::{Deserialize, Serialize};
+
+use crate::device::{
+    helpers::format_node_id,
+    roster::{parse_node_num, NodeAnnotation, RosterEntry},
+    MeshDevice, MeshNode,
+};
+
+use super::csv::{optional_field, parse_csv, to_csv};
+
+const NODES_CSV_HEADER: [&str; 14] = [
+    "node_num",
+    "node_id",
+    "long_name",
+    "short_name",
+    "hardware_model",
+    "role",
+    "nickname",
+    "unit",
+    "notes",
+    "latitude",
+    "longitude",
+    "altitude",
+    "battery_level",
+    "last_heard",
+];
+
+/// A heard node along with its local annotations, flattened for sharing
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeRecord {
+    pub node_num: u32,
+    pub node_id: String,
+    pub long_name: Option<String>,
+    pub short_name: Option<String>,
+    pub hardware_model: Option<String>,
+    pub role: Option<String>,
+    pub nickname: Option<String>,
+    pub unit: Option<String>,
+    pub notes: Option<String>,
+    pub latitude: Option<f32>,
+    pub longitude: Option<f32>,
+    pub altitude: Option<i32>,
+    pub battery_level: Option<u32>,
+    /// Time the node was last heard in seconds since epoch
+    pub last_heard: Option<u32>,
+}
+
+impl NodeRecord {
+    fn new(node: &MeshNode, annotation: Option<&NodeAnnotation>) -> Self {
+        let user = node.user.as_ref();
+        let position = node.position_metrics.back();
+        let annotation = annotation.cloned().unwrap_or_default();
+
+        Self {
+            node_num: node.node_num,
+            node_id: format_node_id(node.node_num),
+            long_name: user.map(|u| u.long_name.clone()),
+            short_name: user.map(|u| u.short_name.clone()),
+            hardware_model: user
+                .and_then(|u| protobufs::HardwareModel::from_i32(u.hw_model))
+                .map(|model| model.as_str_name().to_string()),
+            role: user
+                .and_then(|u| protobufs::config::device_config::Role::from_i32(u.role))
+                .map(|role| role.as_str_name().to_string()),
+            nickname: annotation.nickname,
+            unit: annotation.unit,
+            notes: annotation.notes,
+            latitude: position.map(|p| p.latitude),
+            longitude: position.map(|p| p.longitude),
+            altitude: position.map(|p| p.altitude),
+            battery_level: node
+                .device_metrics
+                .back()
+                .map(|m| m.metrics().battery_level),
+            last_heard: node.last_heard.as_ref().map(|l| l.timestamp),
+        }
+    }
+}
+
+/// Records of every heard node, ordered by node number
+pub fn node_records(device: &MeshDevice) -> Vec<NodeRecord> {
+    let mut records: Vec<NodeRecord> = device
+        .nodes
+        .values()
+        .map(|node| NodeRecord::new(node, device.roster.get(&node.node_num)))
+        .collect();
+
+    records.sort_by_key(|record| record.node_num);
+    records
+}
+
+pub fn nodes_csv(records: &[NodeRecord]) -> String {
+    let rows = records.iter().map(|record| {
+        vec![
+            record.node_num.to_string(),
+            record.node_id.clone(),
+            optional_field(record.long_name.as_ref()),
+            optional_field(record.short_name.as_ref()),
+            optional_field(record.hardware_model.as_ref()),
+            optional_field(record.role.as_ref()),
+            optional_field(record.nickname.as_ref()),
+            optional_field(record.unit.as_ref()),
+            optional_field(record.notes.as_ref()),
+            optional_field(record.latitude),
+            optional_field(record.longitude),
+            optional_field(record.altitude),
+            optional_field(record.battery_level),
+            optional_field(record.last_heard),
+        ]
+    });
+
+    to_csv(&NODES_CSV_HEADER, rows)
+}
+
+pub fn nodes_json(records: &[NodeRecord]) -> Result<String, serde_json::Error> {
+    serde_json::to_string_pretty(records)
+}
+
+/// Parses a CSV roster with a header row. Nodes are identified by a
+/// `node_num` or `node_id` column, and the `nickname`, `unit` and `notes`
+/// columns are optional.
+pub fn roster_from_csv(contents: &str) -> Result<Vec<RosterEntry>, String> {
+    let mut rows = parse_csv(contents)?.into_iter();
+    let header: Vec<String> = rows
+        .next()
+        .ok_or("Roster is empty")?
+        .iter()
+        .map(|column| column.trim().to_lowercase())
+        .collect();
+
+    let column = |name: &str| header.iter().position(|column| column == name);
+
+    let node_column = column("node_num")
+        .or_else(|| column("node_id"))
+        .ok_or("Roster has no node_num or node_id column")?;
+    let (nickname_column, unit_column, notes_column) =
+        (column("nickname"), column("unit"), column("notes"));
+
+    rows.enumerate()
+        .filter(|(_, row)| row.iter().any(|field| !field.trim().is_empty()))
+        .map(|(index, row)| {
+            let field = |column: Option<usize>| {
+                column
+                    .and_then(|column| row.get(column))
+                    .map(|field| field.trim())
+                    .filter(|field| !field.is_empty())
+                    .map(String::from)
+            };
+
+            let node_num = field(Some(node_column))
+                .as_deref()
+                .and_then(parse_node_num)
+                .ok_or_else(|| format!("Invalid node on roster row {}", index + 2))?;
+
+            Ok(RosterEntry {
+                node_num,
+                annotation: NodeAnnotation {
+                    nickname: field(nickname_column),
+                    unit: field(unit_column),
+                    notes: field(notes_column),
+                },
+            })
+        })
+        .collect()
+}
+
+/// Exported nodes carry both their number and id, so either may be present
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RosterFileEntry {
+    node_num: Option<u32>,
+    node_id: Option<String>,
+    #[serde(flatten)]
+    annotation: NodeAnnotation,
+}
+
+/// Parses a JSON roster, an array of objects with a `nodeNum` or `nodeId`
+/// and optional `nickname`, `unit` and `notes`, such as a JSON node export
+pub fn roster_from_json(contents: &str) -> Result<Vec<RosterEntry>, String> {
+    let entries: Vec<RosterFileEntry> =
+        serde_json::from_str(contents).map_err(|e| format!("Invalid roster: {}", e))?;
+
+    entries
+        .into_iter()
+        .enumerate()
+        .map(|(index, entry)| {
+            let node_num = match (entry.node_num, entry.node_id) {
+                (Some(node_num), _) => node_num,
+                (None, Some(node_id)) => parse_node_num(&node_id)
+                    .ok_or_else(|| format!("Invalid node id on roster entry {}", index + 1))?,
+                (None, None) => {
+                    return Err(format!(
+                        "Roster entry {} has no nodeNum or nodeId",
+                        index + 1
+                    ))
+                }
+            };
+
+            Ok(RosterEntry {
+                node_num,
+                annotation: entry.annotation,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merges_annotations_into_exported_nodes() {
+        let mut device = MeshDevice::new();
+        device.node_mut(0x11).user = Some(protobufs::User {
+            long_name: "Ridge relay".into(),
+            short_name: "RR".into(),
+            ..Default::default()
+        });
+        device.node_mut(0x22);
+
+        let entries = roster_from_csv(
+            "node_id,nickname,unit,notes\r\n!00000011,North,Team A,\"Solar, 20W\"\r\n",
+        )
+        .unwrap();
+        device.import_roster(entries);
+
+        let records = node_records(&device);
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].long_name.as_deref(), Some("Ridge relay"));
+        assert_eq!(records[0].nickname.as_deref(), Some("North"));
+        assert_eq!(records[0].notes.as_deref(), Some("Solar, 20W"));
+        assert_eq!(records[1].nickname, None);
+
+        let csv = nodes_csv(&records);
+        assert!(csv.contains("\"Solar, 20W\""));
+    }
+
+    #[test]
+    fn parses_json_rosters_by_number_or_id() {
+        let entries = roster_from_json(
+            r#"[{"nodeNum": 17, "unit": "Team A"}, {"nodeId": "!00000022", "nickname": "South"}]"#,
+        )
+        .unwrap();
+
+        assert_eq!(entries[0].node_num, 0x11);
+        assert_eq!(entries[0].annotation.unit.as_deref(), Some("Team A"));
+        assert_eq!(entries[1].node_num, 0x22);
+        assert_eq!(entries[1].annotation.nickname.as_deref(), Some("South"));
+
+        assert!(roster_from_json(r#"[{"nickname": "Nowhere"}]"#).is_err());
+    }
+
+    #[test]
+    fn reimports_exported_nodes() {
+        let mut device = MeshDevice::new();
+        device.node_mut(0x11);
+        device.node_mut(0x22);
+        device.roster.insert(
+            0x11,
+            NodeAnnotation {
+                nickname: Some("North".into()),
+                unit: Some("Team A".into()),
+                notes: None,
+            },
+        );
+
+        let json = nodes_json(&node_records(&device)).unwrap();
+        let entries = roster_from_json(&json).unwrap();
+
+        let mut reimported = MeshDevice::new();
+        reimported.import_roster(entries);
+
+        assert_eq!(reimported.roster.len(), 2);
+        assert_eq!(reimported.roster.get(&0x11), device.roster.get(&0x11));
+        assert_eq!(
+            reimported.roster.get(&0x22),
+            Some(&NodeAnnotation::default())
+        );
+    }
+}
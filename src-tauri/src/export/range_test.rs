@@ -0,0 +1,131 @@
+use geojson::{Feature, FeatureCollection, Geometry, JsonObject, Value};
+use serde_json::json;
+
+use crate::device::RangeTestRecord;
+
+use super::csv::{optional_field, to_csv};
+
+const RANGE_TEST_CSV_HEADER: [&str; 12] = [
+    "timestamp",
+    "from",
+    "sequence",
+    "snr",
+    "rssi",
+    "hops_away",
+    "sender_latitude",
+    "sender_longitude",
+    "sender_altitude",
+    "receiver_latitude",
+    "receiver_longitude",
+    "receiver_altitude",
+];
+
+pub fn range_test_csv<'a>(records: impl IntoIterator<Item = &'a RangeTestRecord>) -> String {
+    let rows = records.into_iter().map(|record| {
+        let sender = record.sender_position.as_ref();
+        let receiver = record.receiver_position.as_ref();
+
+        vec![
+            record.timestamp.to_string(),
+            record.from.to_string(),
+            record.sequence.to_string(),
+            record.snr.to_string(),
+            record.rssi.to_string(),
+            optional_field(record.hops_away),
+            optional_field(sender.map(|p| p.latitude)),
+            optional_field(sender.map(|p| p.longitude)),
+            optional_field(sender.map(|p| p.altitude)),
+            optional_field(receiver.map(|p| p.latitude)),
+            optional_field(receiver.map(|p| p.longitude)),
+            optional_field(receiver.map(|p| p.altitude)),
+        ]
+    });
+
+    to_csv(&RANGE_TEST_CSV_HEADER, rows)
+}
+
+/// Builds a feature for every record with a known sender position, located
+/// where the packet was sent from so that coverage can be mapped
+pub fn range_test_geojson<'a>(
+    records: impl IntoIterator<Item = &'a RangeTestRecord>,
+) -> FeatureCollection {
+    let features = records
+        .into_iter()
+        .filter_map(|record| {
+            let sender = record.sender_position.as_ref()?;
+
+            let mut properties = JsonObject::new();
+            properties.insert("timestamp".into(), json!(record.timestamp));
+            properties.insert("from".into(), json!(record.from));
+            properties.insert("sequence".into(), json!(record.sequence));
+            properties.insert("snr".into(), json!(record.snr));
+            properties.insert("rssi".into(), json!(record.rssi));
+            properties.insert("hopsAway".into(), json!(record.hops_away));
+
+            if let Some(receiver) = record.receiver_position.as_ref() {
+                properties.insert("receiverLatitude".into(), json!(receiver.latitude));
+                properties.insert("receiverLongitude".into(), json!(receiver.longitude));
+            }
+
+            Some(Feature {
+                bbox: None,
+                geometry: Some(Geometry::new(Value::Point(vec![
+                    sender.longitude as f64,
+                    sender.latitude as f64,
+                ]))),
+                id: None,
+                properties: Some(properties),
+                foreign_members: None,
+            })
+        })
+        .collect();
+
+    FeatureCollection {
+        bbox: None,
+        features,
+        foreign_members: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::device::NormalizedPosition;
+
+    fn record(sender_position: Option<NormalizedPosition>) -> RangeTestRecord {
+        RangeTestRecord {
+            timestamp: 1_700_000_000,
+            from: 0x11,
+            sequence: 3,
+            snr: 5.5,
+            rssi: -90,
+            hops_away: Some(0),
+            sender_position,
+            receiver_position: None,
+        }
+    }
+
+    #[test]
+    fn exports_records_as_csv() {
+        let csv = range_test_csv(&[record(None)]);
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1], "1700000000,17,3,5.5,-90,0,,,,,,");
+    }
+
+    #[test]
+    fn skips_records_without_sender_position() {
+        let position = NormalizedPosition {
+            latitude: 2.5,
+            longitude: -71.0,
+            ..Default::default()
+        };
+
+        let collection = range_test_geojson(&[record(Some(position)), record(None)]);
+        assert_eq!(collection.features.len(), 1);
+
+        let geometry = collection.features[0].geometry.as_ref().unwrap();
+        assert_eq!(geometry.value, Value::Point(vec![-71.0, 2.5]));
+    }
+}
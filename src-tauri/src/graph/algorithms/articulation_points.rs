@@ -0,0 +1,95 @@
+use super::UndirectedGraph;
+use crate::graph::ds::graph::InternalGraph;
+
+/// Returns the node numbers of all nodes whose removal would disconnect the
+/// part of the mesh they belong to, in ascending order
+pub fn articulation_points(graph: &InternalGraph) -> Vec<u32> {
+    let graph = UndirectedGraph::from_internal(graph);
+
+    let mut search = Search {
+        graph: &graph,
+        discovery: vec![None; graph.len()],
+        low: vec![0; graph.len()],
+        is_articulation_point: vec![false; graph.len()],
+        time: 0,
+    };
+
+    for root in 0..graph.len() {
+        if search.discovery[root].is_none() {
+            search.visit(root, None);
+        }
+    }
+
+    graph
+        .nodes
+        .iter()
+        .zip(search.is_articulation_point)
+        .filter(|(_, is_articulation_point)| *is_articulation_point)
+        .map(|(node_num, _)| *node_num)
+        .collect()
+}
+
+/// Depth-first search state for Tarjan's algorithm
+struct Search<'a> {
+    graph: &'a UndirectedGraph,
+    discovery: Vec<Option<usize>>,
+    low: Vec<usize>,
+    is_articulation_point: Vec<bool>,
+    time: usize,
+}
+
+impl Search<'_> {
+    fn visit(&mut self, node: usize, parent: Option<usize>) {
+        self.discovery[node] = Some(self.time);
+        self.low[node] = self.time;
+        self.time += 1;
+
+        let mut children = 0;
+
+        for &neighbor in self.graph.neighbors[node].iter() {
+            match self.discovery[neighbor] {
+                Some(neighbor_discovery) => {
+                    if Some(neighbor) != parent {
+                        self.low[node] = self.low[node].min(neighbor_discovery);
+                    }
+                }
+                None => {
+                    children += 1;
+                    self.visit(neighbor, Some(node));
+                    self.low[node] = self.low[node].min(self.low[neighbor]);
+
+                    // A non-root node is an articulation point if some subtree
+                    // below it can't reach any of its ancestors
+                    let discovery = self.discovery[node].unwrap_or_default();
+                    if parent.is_some() && self.low[neighbor] >= discovery {
+                        self.is_articulation_point[node] = true;
+                    }
+                }
+            }
+        }
+
+        // The root is an articulation point if it has multiple subtrees
+        if parent.is_none() && children > 1 {
+            self.is_articulation_point[node] = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::algorithms::test_graph;
+
+    #[test]
+    fn finds_relays_joining_clusters() {
+        // Two triangles joined through node 4
+        let graph = test_graph(&[(1, 2), (2, 3), (3, 1), (3, 4), (4, 5), (5, 6), (6, 4)]);
+        assert_eq!(articulation_points(&graph), vec![3, 4]);
+    }
+
+    #[test]
+    fn cycles_have_no_articulation_points() {
+        let graph = test_graph(&[(1, 2), (2, 3), (3, 4), (4, 1)]);
+        assert!(articulation_points(&graph).is_empty());
+    }
+}
@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+
+use meshtastic::ts::specta::{self, Type};
+use petgraph::algo::astar;
+use petgraph::graphmap::UnGraphMap;
+use serde::{Deserialize, Serialize};
+
+use crate::graph::ds::graph::InternalGraph;
+
+/// SNR (dB) at which a packet has an even chance of being decoded. LoRa can
+/// demodulate below the noise floor, down to about -20 dB for the slowest presets.
+const SNR_DECODE_THRESHOLD: f64 = -12.5;
+
+/// Spread (dB) of the transition between packets being lost and being decoded
+const SNR_DECODE_SCALE: f64 = 2.5;
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteLink {
+    pub from: u32,
+    pub to: u32,
+    pub snr: f64,
+
+    /// Estimated chance that a single transmission over the link is received
+    pub delivery_probability: f64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct RoutePrediction {
+    /// Node numbers from the source to the destination, inclusive
+    pub path: Vec<u32>,
+    pub hop_count: u32,
+
+    /// Expected number of transmissions needed to deliver a packet, with
+    /// every hop retransmitting until the next one receives it
+    pub expected_transmissions: f64,
+
+    /// Estimated chance that a packet is delivered without retransmissions
+    pub delivery_probability: f64,
+
+    /// The link least likely to deliver a packet, `None` for empty routes
+    pub bottleneck: Option<RouteLink>,
+}
+
+/// Maps an SNR to the chance that a packet sent over the link is decoded
+pub fn snr_delivery_probability(snr: f64) -> f64 {
+    1.0 / (1.0 + (-(snr - SNR_DECODE_THRESHOLD) / SNR_DECODE_SCALE).exp())
+}
+
+/// Returns the SNR of every link keyed by its source and target node numbers
+fn link_snrs(graph: &InternalGraph) -> HashMap<(u32, u32), f64> {
+    graph
+        .all_edges()
+        .map(|(source, target, edge)| ((source.node_num, target.node_num), edge.snr()))
+        .collect()
+}
+
+/// Finds the route between two nodes that needs the fewest expected
+/// transmissions, using each link's SNR to estimate how often it drops packets
+pub fn best_path(graph: &InternalGraph, from: u32, to: u32) -> Option<RoutePrediction> {
+    let mut costs: UnGraphMap<u32, f64> = UnGraphMap::new();
+
+    for node in graph.nodes() {
+        costs.add_node(node.node_num);
+    }
+
+    for (source, target, edge) in graph.all_edges() {
+        let cost = 1.0 / snr_delivery_probability(edge.snr());
+
+        // Keep the better direction when both directions of a link are known
+        let existing = costs.edge_weight(source.node_num, target.node_num).copied();
+        if existing.map_or(true, |existing| cost < existing) {
+            costs.add_edge(source.node_num, target.node_num, cost);
+        }
+    }
+
+    if !costs.contains_node(from) || !costs.contains_node(to) {
+        return None;
+    }
+
+    let (_, path) = astar(
+        &costs,
+        from,
+        |node| node == to,
+        |(_, _, cost)| *cost,
+        |_| 0.0,
+    )?;
+
+    route_quality(graph, &path)
+}
+
+/// Estimates how reliably a packet travels along the passed route. Returns
+/// `None` if any consecutive pair of nodes in the route isn't linked.
+pub fn route_quality(graph: &InternalGraph, path: &[u32]) -> Option<RoutePrediction> {
+    let snrs = link_snrs(graph);
+
+    let links = path
+        .windows(2)
+        .map(|hop| {
+            // Prefer the SNR measured in the direction of travel, links are
+            // assumed to be symmetric otherwise
+            let snr = *snrs
+                .get(&(hop[0], hop[1]))
+                .or_else(|| snrs.get(&(hop[1], hop[0])))?;
+
+            Some(RouteLink {
+                from: hop[0],
+                to: hop[1],
+                snr,
+                delivery_probability: snr_delivery_probability(snr),
+            })
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    let bottleneck = links
+        .iter()
+        .min_by(|a, b| a.delivery_probability.total_cmp(&b.delivery_probability))
+        .cloned();
+
+    Some(RoutePrediction {
+        path: path.to_vec(),
+        hop_count: links.len().try_into().unwrap_or(u32::MAX),
+        expected_transmissions: links.iter().map(|l| 1.0 / l.delivery_probability).sum(),
+        delivery_probability: links.iter().map(|l| l.delivery_probability).product(),
+        bottleneck,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::ds::{edge::GraphEdge, node::GraphNode};
+
+    fn graph_with_snrs(links: &[(u32, u32, f32)]) -> InternalGraph {
+        let node = GraphNode::new;
+
+        let mut graph = InternalGraph::new();
+        for (from, to, snr) in links {
+            graph.add_edge(
+                node(*from),
+                node(*to),
+                GraphEdge::from_traceroute_hop(*from, *to, *snr),
+            );
+        }
+
+        graph
+    }
+
+    #[test]
+    fn prefers_strong_links_over_fewer_hops() {
+        // The direct link is barely above the noise floor, the relay is solid
+        let graph = graph_with_snrs(&[(1, 3, -18.0), (1, 2, 8.0), (2, 3, 6.0)]);
+        let route = best_path(&graph, 1, 3).unwrap();
+
+        assert_eq!(route.path, vec![1, 2, 3]);
+        assert_eq!(route.hop_count, 2);
+
+        let bottleneck = route.bottleneck.unwrap();
+        assert_eq!((bottleneck.from, bottleneck.to), (2, 3));
+    }
+
+    #[test]
+    fn follows_links_in_either_direction() {
+        let graph = graph_with_snrs(&[(2, 1, 5.0)]);
+        let route = best_path(&graph, 1, 2).unwrap();
+
+        assert_eq!(route.path, vec![1, 2]);
+        assert!(route.delivery_probability > 0.9);
+    }
+
+    #[test]
+    fn rejects_routes_over_unknown_links() {
+        let graph = graph_with_snrs(&[(1, 2, 5.0), (3, 4, 5.0)]);
+
+        assert!(best_path(&graph, 1, 4).is_none());
+        assert!(route_quality(&graph, &[1, 2, 4]).is_none());
+    }
+
+    #[test]
+    fn delivery_probability_increases_with_snr() {
+        assert!(snr_delivery_probability(-20.0) < 0.1);
+        assert!((snr_delivery_probability(SNR_DECODE_THRESHOLD) - 0.5).abs() < 1e-9);
+        assert!(snr_delivery_probability(10.0) > 0.99);
+    }
+}
@@ -0,0 +1,93 @@
+use std::collections::{HashMap, VecDeque};
+
+use super::UndirectedGraph;
+use crate::graph::ds::graph::InternalGraph;
+
+/// Computes the normalized betweenness centrality of every node using
+/// Brandes' algorithm, i.e. the share of shortest paths between other nodes
+/// that pass through it. Every link counts as a single hop.
+pub fn betweenness_centrality(graph: &InternalGraph) -> HashMap<u32, f64> {
+    let graph = UndirectedGraph::from_internal(graph);
+    let node_count = graph.len();
+
+    let mut centrality = vec![0.0; node_count];
+
+    for source in 0..node_count {
+        let mut stack = Vec::with_capacity(node_count);
+        let mut predecessors: Vec<Vec<usize>> = vec![vec![]; node_count];
+        let mut path_counts = vec![0.0; node_count];
+        let mut distances: Vec<Option<usize>> = vec![None; node_count];
+
+        path_counts[source] = 1.0;
+        distances[source] = Some(0);
+
+        let mut queue = VecDeque::from([source]);
+
+        while let Some(node) = queue.pop_front() {
+            stack.push(node);
+            let distance = distances[node].unwrap_or_default();
+
+            for &neighbor in graph.neighbors[node].iter() {
+                if distances[neighbor].is_none() {
+                    distances[neighbor] = Some(distance + 1);
+                    queue.push_back(neighbor);
+                }
+
+                if distances[neighbor] == Some(distance + 1) {
+                    path_counts[neighbor] += path_counts[node];
+                    predecessors[neighbor].push(node);
+                }
+            }
+        }
+
+        let mut dependencies = vec![0.0; node_count];
+
+        while let Some(node) = stack.pop() {
+            for &predecessor in predecessors[node].iter() {
+                dependencies[predecessor] +=
+                    path_counts[predecessor] / path_counts[node] * (1.0 + dependencies[node]);
+            }
+
+            if node != source {
+                centrality[node] += dependencies[node];
+            }
+        }
+    }
+
+    // Every path is counted from both of its ends in an undirected graph
+    let pair_count = if node_count > 2 {
+        ((node_count - 1) * (node_count - 2)) as f64
+    } else {
+        1.0
+    };
+
+    graph
+        .nodes
+        .iter()
+        .zip(centrality)
+        .map(|(node_num, c)| (*node_num, c / pair_count))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::algorithms::test_graph;
+
+    #[test]
+    fn center_of_star_carries_all_paths() {
+        let graph = test_graph(&[(1, 2), (1, 3), (1, 4)]);
+        let centrality = betweenness_centrality(&graph);
+
+        assert!((centrality[&1] - 1.0).abs() < 1e-9);
+        assert_eq!(centrality[&2], 0.0);
+    }
+
+    #[test]
+    fn middle_of_line_carries_all_paths() {
+        let graph = test_graph(&[(1, 2), (2, 3)]);
+        let centrality = betweenness_centrality(&graph);
+
+        assert!((centrality[&2] - 1.0).abs() < 1e-9);
+    }
+}
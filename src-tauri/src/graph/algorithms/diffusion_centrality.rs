@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+
+use super::UndirectedGraph;
+use crate::graph::ds::graph::InternalGraph;
+
+/// Computes the diffusion centrality of every node, i.e. the expected number
+/// of times a message originating at the node reaches other nodes within
+/// `T` retransmission rounds when each link carries it with probability `q`.
+/// Results are keyed by node number, then by `T`, then by `q` in percent.
+pub fn diffusion_centrality(
+    graph: &InternalGraph,
+    horizons: &[u32],
+    probabilities_percent: &[u32],
+) -> HashMap<u32, HashMap<u32, HashMap<u32, f64>>> {
+    let graph = UndirectedGraph::from_internal(graph);
+    let max_horizon = horizons.iter().copied().max().unwrap_or_default();
+
+    let mut results: HashMap<u32, HashMap<u32, HashMap<u32, f64>>> = graph
+        .nodes
+        .iter()
+        .map(|node_num| (*node_num, HashMap::new()))
+        .collect();
+
+    for &probability_percent in probabilities_percent {
+        let q = probability_percent as f64 / 100.0;
+
+        // DC(q, T) = sum over t = 1..T of (qA)^t * 1
+        let mut walk = vec![1.0; graph.len()];
+        let mut total = vec![0.0; graph.len()];
+
+        for t in 1..=max_horizon {
+            walk = graph
+                .neighbors
+                .iter()
+                .map(|neighbors| q * neighbors.iter().map(|n| walk[*n]).sum::<f64>())
+                .collect();
+
+            for (node_total, node_walk) in total.iter_mut().zip(walk.iter()) {
+                *node_total += node_walk;
+            }
+
+            if !horizons.contains(&t) {
+                continue;
+            }
+
+            for (node_num, node_total) in graph.nodes.iter().zip(total.iter()) {
+                results
+                    .entry(*node_num)
+                    .or_default()
+                    .entry(t)
+                    .or_default()
+                    .insert(probability_percent, *node_total);
+            }
+        }
+    }
+
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::algorithms::test_graph;
+
+    #[test]
+    fn sums_walks_up_to_horizon() {
+        // Line 1 - 2 - 3, with every link certain to carry the message
+        let graph = test_graph(&[(1, 2), (2, 3)]);
+        let results = diffusion_centrality(&graph, &[1, 2], &[100]);
+
+        assert_eq!(results[&2][&1][&100], 2.0);
+        assert_eq!(results[&1][&1][&100], 1.0);
+
+        // Two-step walks from 1 are 1-2-1 and 1-2-3
+        assert_eq!(results[&1][&2][&100], 3.0);
+    }
+}
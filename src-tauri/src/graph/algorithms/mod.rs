@@ -0,0 +1,75 @@
+//! Network analysis over the mesh topology. Links are treated as undirected,
+//! since a link heard in one direction can generally carry traffic in both.
+
+use std::collections::{BTreeSet, HashMap};
+
+use super::ds::graph::InternalGraph;
+
+pub mod articulation_points;
+pub mod best_path;
+pub mod betweenness;
+pub mod diffusion_centrality;
+pub mod stoer_wagner;
+
+/// Undirected view of an `InternalGraph`, with nodes indexed in ascending
+/// order of node number so that results are deterministic
+pub(crate) struct UndirectedGraph {
+    pub nodes: Vec<u32>,
+    pub neighbors: Vec<Vec<usize>>,
+}
+
+impl UndirectedGraph {
+    pub fn from_internal(graph: &InternalGraph) -> Self {
+        let nodes: Vec<u32> = graph
+            .nodes()
+            .map(|n| n.node_num)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+
+        let index: HashMap<u32, usize> = nodes.iter().enumerate().map(|(i, n)| (*n, i)).collect();
+
+        let mut neighbors = vec![BTreeSet::new(); nodes.len()];
+
+        for (source, target, _) in graph.all_edges() {
+            let (source, target) = (index[&source.node_num], index[&target.node_num]);
+
+            // Self loops don't affect connectivity
+            if source != target {
+                neighbors[source].insert(target);
+                neighbors[target].insert(source);
+            }
+        }
+
+        Self {
+            nodes,
+            neighbors: neighbors
+                .into_iter()
+                .map(|n| n.into_iter().collect())
+                .collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+}
+
+#[cfg(test)]
+pub(crate) fn test_graph(edges: &[(u32, u32)]) -> InternalGraph {
+    use super::ds::{edge::GraphEdge, node::GraphNode};
+
+    let node = GraphNode::new;
+
+    let mut graph = InternalGraph::new();
+
+    for (from, to) in edges {
+        graph.add_edge(
+            node(*from),
+            node(*to),
+            GraphEdge::from_traceroute_hop(*from, *to, 0.0),
+        );
+    }
+
+    graph
+}
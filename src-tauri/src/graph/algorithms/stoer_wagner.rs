@@ -0,0 +1,136 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use super::UndirectedGraph;
+use crate::graph::ds::graph::InternalGraph;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MinCut {
+    /// Number of links that need to fail to split the mesh
+    pub weight: u32,
+
+    /// Node numbers on one side of the cut
+    pub partition: Vec<u32>,
+
+    /// Links crossing the cut, as pairs of node numbers
+    pub edges: Vec<(u32, u32)>,
+}
+
+/// Finds the smallest set of links whose failure would split the mesh, using
+/// the Stoer-Wagner algorithm with every link weighted equally. Returns `None`
+/// for graphs with fewer than two nodes.
+pub fn global_min_cut(graph: &InternalGraph) -> Option<MinCut> {
+    let graph = UndirectedGraph::from_internal(graph);
+    let node_count = graph.len();
+
+    if node_count < 2 {
+        return None;
+    }
+
+    let mut weights = vec![vec![0u32; node_count]; node_count];
+    for (node, neighbors) in graph.neighbors.iter().enumerate() {
+        for &neighbor in neighbors {
+            weights[node][neighbor] = 1;
+        }
+    }
+
+    // Original nodes merged into each remaining vertex
+    let mut merged: Vec<Vec<usize>> = (0..node_count).map(|n| vec![n]).collect();
+    let mut active: Vec<usize> = (0..node_count).collect();
+
+    let mut best: Option<(u32, Vec<usize>)> = None;
+
+    while active.len() > 1 {
+        // Maximum adjacency ordering of the active vertices
+        let mut connectivity = vec![0u32; node_count];
+        let mut added = vec![false; node_count];
+        let mut previous = active[0];
+        let mut last = active[0];
+
+        for _ in 0..active.len() {
+            let next = *active
+                .iter()
+                .filter(|v| !added[**v])
+                .max_by_key(|v| (connectivity[**v], std::cmp::Reverse(**v)))
+                .expect("Active vertices remain while ordering");
+
+            added[next] = true;
+            previous = last;
+            last = next;
+
+            for &v in active.iter() {
+                if !added[v] {
+                    connectivity[v] += weights[next][v];
+                }
+            }
+        }
+
+        // The cut of the phase separates the last vertex from all others
+        let cut_weight = connectivity[last];
+        if best
+            .as_ref()
+            .map_or(true, |(weight, _)| cut_weight < *weight)
+        {
+            best = Some((cut_weight, merged[last].clone()));
+        }
+
+        // Merge the last vertex into the one added before it
+        let last_merged = std::mem::take(&mut merged[last]);
+        merged[previous].extend(last_merged);
+
+        for &v in active.iter() {
+            weights[previous][v] += weights[last][v];
+            weights[v][previous] = weights[previous][v];
+        }
+
+        weights[previous][previous] = 0;
+        active.retain(|v| *v != last);
+    }
+
+    let (weight, partition) = best?;
+    let in_partition: HashSet<usize> = partition.iter().copied().collect();
+
+    let mut edges = vec![];
+    for &node in partition.iter() {
+        for &neighbor in graph.neighbors[node].iter() {
+            if !in_partition.contains(&neighbor) {
+                edges.push((graph.nodes[node], graph.nodes[neighbor]));
+            }
+        }
+    }
+    edges.sort();
+
+    let mut partition: Vec<u32> = partition.into_iter().map(|n| graph.nodes[n]).collect();
+    partition.sort();
+
+    Some(MinCut {
+        weight,
+        partition,
+        edges,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::algorithms::test_graph;
+
+    #[test]
+    fn finds_bridge_between_clusters() {
+        let graph = test_graph(&[(1, 2), (2, 3), (3, 1), (3, 4), (4, 5), (5, 6), (6, 4)]);
+
+        let cut = global_min_cut(&graph).unwrap();
+        assert_eq!(cut.weight, 1);
+
+        let edge = cut.edges[0];
+        assert!(edge == (3, 4) || edge == (4, 3));
+    }
+
+    #[test]
+    fn cycles_need_two_links_cut() {
+        let graph = test_graph(&[(1, 2), (2, 3), (3, 4), (4, 1)]);
+        assert_eq!(global_min_cut(&graph).unwrap().weight, 2);
+    }
+}
@@ -2,13 +2,19 @@ use std::time::Duration;
 
 use meshtastic::protobufs::{self, MeshPacket};
 
-use crate::graph::ds::{edge::GraphEdge, graph::MeshGraph, node::GraphNode};
+use crate::device::TracerouteResult;
+use crate::graph::ds::{
+    edge::GraphEdge, graph::MeshGraph, node::GraphNode, position::GraphPosition,
+    timeout_policy::advertised_interval,
+};
+use crate::state::DeviceKey;
 
 pub const DEFAULT_NODE_TIMEOUT_DURATION: Duration = Duration::from_secs(15 * 60);
 
 impl MeshGraph {
     pub fn update_from_neighbor_info(
         &mut self,
+        observer: &DeviceKey,
         packet: MeshPacket,
         neighbor_info: protobufs::NeighborInfo,
     ) {
@@ -22,39 +28,59 @@ impl MeshGraph {
         let own_node = match self.get_node(packet.from) {
             Some(node) => GraphNode {
                 last_heard: chrono::Utc::now(),
+                broadcast_interval: advertised_interval(neighbor_info.node_broadcast_interval_secs)
+                    .or(node.broadcast_interval),
+                inferred: false,
                 ..node
             },
             None => neighbor_info.clone().into(),
         };
 
         self.upsert_node(own_node.clone());
+        self.observations.observe_node(observer, own_node.node_num);
 
-        // Update neighbor nodes, don't insert as this isn't how neighbor info works
+        // Update neighbor nodes. Unknown neighbors haven't been heard from directly,
+        // so they are only inserted as inferred nodes if enabled
         for neighbor in neighbor_info.neighbors {
             log::info!("Adding neighbor node {} to graph", neighbor.node_id);
 
             let remote_node = match self.get_node(neighbor.node_id) {
                 Some(g) => g,
+                None if self.infer_neighbor_nodes() => {
+                    log::debug!("Inferring neighbor node {}", neighbor.node_id);
+
+                    let inferred_node = GraphNode {
+                        inferred: true,
+                        ..GraphNode::from(neighbor.clone())
+                    };
+
+                    self.observations.observe_node(observer, neighbor.node_id);
+                    self.upsert_node(inferred_node)
+                }
                 None => {
                     continue;
                 }
             };
 
-            self.upsert_edge(
+            self.upsert_observed_edge(
+                observer,
                 own_node.clone(),
                 remote_node,
                 GraphEdge::from_neighbor(own_node.node_num, neighbor),
             );
         }
+
+        self.record_snapshot();
     }
 
-    pub fn update_from_node_info(&mut self, node_info: protobufs::NodeInfo) {
+    pub fn update_from_node_info(&mut self, observer: &DeviceKey, node_info: protobufs::NodeInfo) {
         log::info!(
             "Updating graph from node info packet from node {}",
             node_info.num
         );
 
-        if node_info.position.is_none() {
+        // Nodes already in the graph are still updated, which confirms inferred nodes
+        if node_info.position.is_none() && !self.contains_node(node_info.num) {
             log::info!(
                 "Node info packet from node {} has no position, not adding to graph",
                 node_info.num
@@ -62,39 +88,218 @@ impl MeshGraph {
             return;
         }
 
+        let position = node_info
+            .position
+            .as_ref()
+            .and_then(GraphPosition::from_position);
+
         let own_node = match self.get_node(node_info.num) {
             Some(node) => GraphNode {
                 last_heard: chrono::Utc::now(),
+                position: position.or(node.position),
+                inferred: false,
                 ..node
             },
             None => GraphNode {
-                node_num: node_info.num,
-                last_heard: chrono::Utc::now(),
-                timeout_duration: DEFAULT_NODE_TIMEOUT_DURATION,
+                position,
+                ..GraphNode::new(node_info.num)
             },
         };
 
         self.upsert_node(own_node);
+        self.observations.observe_node(observer, node_info.num);
+
+        self.record_snapshot();
     }
 
-    pub fn update_from_position(&mut self, packet: MeshPacket, _position: protobufs::Position) {
+    pub fn update_from_position(
+        &mut self,
+        observer: &DeviceKey,
+        packet: MeshPacket,
+        position: protobufs::Position,
+    ) {
         log::info!(
             "Updating graph from position packet from node {}",
             packet.from
         );
 
+        // Positions without a fix don't invalidate the last known position
+        let position = GraphPosition::from_position(&position);
+
         let own_node = match self.get_node(packet.from) {
             Some(node) => GraphNode {
                 last_heard: chrono::Utc::now(),
+                position: position.or(node.position),
+                inferred: false,
                 ..node
             },
             None => GraphNode {
-                node_num: packet.from,
-                last_heard: chrono::Utc::now(),
-                timeout_duration: DEFAULT_NODE_TIMEOUT_DURATION,
+                position,
+                ..GraphNode::new(packet.from)
             },
         };
 
         self.upsert_node(own_node);
+        self.observations.observe_node(observer, packet.from);
+
+        self.record_snapshot();
+    }
+
+    pub fn update_from_traceroute(&mut self, observer: &DeviceKey, traceroute: &TracerouteResult) {
+        log::info!(
+            "Updating graph from traceroute to node {}",
+            traceroute.destination
+        );
+
+        // Every node in a traceroute relayed the packet, so unlike neighbor info
+        // it is safe to insert nodes that aren't yet in the graph
+        for route in [&traceroute.route, &traceroute.route_back] {
+            for hops in route.windows(2) {
+                let (from_hop, to_hop) = (&hops[0], &hops[1]);
+
+                let snr = match to_hop.snr {
+                    Some(snr) => snr,
+                    None => {
+                        log::trace!(
+                            "No SNR reported for hop between {} and {}, skipping edge",
+                            from_hop.node_num,
+                            to_hop.node_num
+                        );
+                        continue;
+                    }
+                };
+
+                let from_node = self.touch_node(observer, from_hop.node_num);
+                let to_node = self.touch_node(observer, to_hop.node_num);
+
+                self.upsert_observed_edge(
+                    observer,
+                    from_node,
+                    to_node,
+                    GraphEdge::from_traceroute_hop(from_hop.node_num, to_hop.node_num, snr),
+                );
+            }
+        }
+
+        self.record_snapshot();
+    }
+
+    /// Infers a link from a packet received by the observing radio. Packets
+    /// that weren't relayed come directly from their sender, while relayed
+    /// packets come directly from the relaying node if it can be identified.
+    pub fn update_from_received_packet(
+        &mut self,
+        observer: &DeviceKey,
+        my_node_num: u32,
+        packet: &MeshPacket,
+    ) {
+        // Packets without a hop start are from firmware that doesn't report hops
+        if my_node_num == 0 || packet.from == my_node_num || packet.hop_start == 0 {
+            return;
+        }
+
+        // Packets from the MQTT uplink weren't received over the air
+        if packet.via_mqtt {
+            return;
+        }
+
+        let hops_away = packet.hop_start.saturating_sub(packet.hop_limit);
+
+        let from = if hops_away == 0 {
+            packet.from
+        } else {
+            match self.resolve_relay_node(my_node_num, packet.relay_node) {
+                Some(relay_node) => relay_node,
+                None => return,
+            }
+        };
+
+        log::debug!(
+            "Inferring link from {} to {} from received packet",
+            from,
+            my_node_num
+        );
+
+        let previous_edge = self.get_edge(from, my_node_num);
+        let is_new_link = previous_edge.is_none();
+        let previous_statistics = previous_edge.and_then(|edge| edge.rx_statistics);
+
+        // The node was heard directly, which confirms it if it was inferred
+        let from_node = match self.get_node(from) {
+            Some(node) if node.inferred => {
+                self.observations.observe_node(observer, from);
+                self.upsert_node(GraphNode {
+                    last_heard: chrono::Utc::now(),
+                    inferred: false,
+                    ..node
+                })
+            }
+            _ => self.touch_node(observer, from),
+        };
+
+        let my_node = self.touch_node(observer, my_node_num);
+
+        self.upsert_observed_edge(
+            observer,
+            from_node,
+            my_node,
+            GraphEdge::from_received_packet(
+                from,
+                my_node_num,
+                packet.rx_snr,
+                packet.rx_rssi,
+                previous_statistics,
+            ),
+        );
+
+        // Most packets come over links that are already known, which don't
+        // change the topology
+        if is_new_link {
+            self.record_snapshot();
+        }
+    }
+
+    /// Packets only carry the last byte of the relaying node's number, so the
+    /// relay is only known if a single node in the graph matches it
+    fn resolve_relay_node(&self, my_node_num: u32, relay_node: u32) -> Option<u32> {
+        if relay_node == 0 {
+            return None;
+        }
+
+        let mut candidates = self
+            .nodes_lookup
+            .keys()
+            .filter(|node_num| **node_num != my_node_num && *node_num & 0xff == relay_node & 0xff);
+
+        match (candidates.next(), candidates.next()) {
+            (Some(node_num), None) => Some(*node_num),
+            _ => None,
+        }
+    }
+
+    /// Marks a node as heard from, adding it if it isn't in the graph. Only
+    /// the time it was heard changes, so an existing node is updated in the
+    /// lookup without replacing it in the graph or reporting the change.
+    fn touch_node(&mut self, observer: &DeviceKey, node_num: u32) -> GraphNode {
+        self.observations.observe_node(observer, node_num);
+
+        if let Some(node) = self.nodes_lookup.get_mut(&node_num) {
+            node.last_heard = chrono::Utc::now();
+            return *node;
+        }
+
+        self.upsert_node(GraphNode::new(node_num))
+    }
+
+    fn upsert_observed_edge(
+        &mut self,
+        observer: &DeviceKey,
+        source: GraphNode,
+        target: GraphNode,
+        edge: GraphEdge,
+    ) {
+        self.observations
+            .observe_edge(observer, source.node_num, target.node_num, edge.snr());
+        self.upsert_edge(source, target, edge);
     }
 }
@@ -0,0 +1,30 @@
+use meshtastic::ts::specta::{self, Type};
+use serde::{Deserialize, Serialize};
+
+use super::{edge::GraphEdge, node::GraphNode};
+
+/// A single change to the graph, emitted so that clients don't need to
+/// receive the whole graph after every packet
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+#[serde(tag = "type")]
+pub enum GraphDelta {
+    NodeUpserted {
+        node: GraphNode,
+    },
+    /// Links of a removed node are removed along with it and aren't
+    /// reported separately
+    NodeRemoved {
+        #[serde(rename = "nodeNum")]
+        node_num: u32,
+    },
+    EdgeUpserted {
+        from: u32,
+        to: u32,
+        edge: GraphEdge,
+    },
+    EdgeRemoved {
+        from: u32,
+        to: u32,
+    },
+}
@@ -10,6 +10,10 @@ use serde::{Deserialize, Serialize};
 
 use crate::graph::api::update_from_packet::DEFAULT_NODE_TIMEOUT_DURATION;
 
+use super::{
+    link_statistics::LinkStatistics, position::GraphPosition, timeout_policy::advertised_interval,
+};
+
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 #[serde(rename_all = "camelCase")]
 pub struct GraphEdge {
@@ -18,9 +22,49 @@ pub struct GraphEdge {
     to: u32,
     pub last_heard: DateTime<Utc>,
     pub timeout_duration: Duration,
+
+    /// Interval at which the reporting node advertised that it broadcasts neighbor info
+    pub broadcast_interval: Option<Duration>,
+
+    /// Great-circle distance between the nodes in meters, if both positions are known
+    pub distance_meters: Option<f64>,
+
+    /// Initial bearing from the source towards the target in degrees
+    /// clockwise from true north, if both positions are known
+    pub bearing_degrees: Option<f64>,
+
+    /// Signal statistics of packets the target received directly from the source
+    pub rx_statistics: Option<LinkStatistics>,
 }
 
 impl GraphEdge {
+    /// Updates the distance and bearing of the edge from the positions of its nodes
+    pub fn set_geometry(&mut self, from: Option<GraphPosition>, to: Option<GraphPosition>) {
+        match (from, to) {
+            (Some(from), Some(to)) => {
+                self.distance_meters = Some(from.distance_to(&to));
+                self.bearing_degrees = Some(from.bearing_to(&to));
+            }
+            _ => {
+                self.distance_meters = None;
+                self.bearing_degrees = None;
+            }
+        }
+    }
+
+    /// SNR (dB) at which the target heard the source
+    pub fn snr(&self) -> f64 {
+        self.snr
+    }
+
+    pub fn from_node(&self) -> u32 {
+        self.from
+    }
+
+    pub fn to_node(&self) -> u32 {
+        self.to
+    }
+
     pub fn from_neighbor(to_node_id: u32, neighbor: Neighbor) -> Self {
         let timeout_secs: u64 = if neighbor.node_broadcast_interval_secs == 0 {
             trace!(
@@ -46,6 +90,60 @@ impl GraphEdge {
             to: to_node_id,
             last_heard: chrono::Utc::now(),
             timeout_duration: Duration::from_secs(timeout_secs),
+            broadcast_interval: advertised_interval(neighbor.node_broadcast_interval_secs),
+            distance_meters: None,
+            bearing_degrees: None,
+            rx_statistics: None,
+        }
+    }
+
+    pub fn from_traceroute_hop(from_node_id: u32, to_node_id: u32, snr: f32) -> Self {
+        log::debug!(
+            "Creating edge from traceroute hop between {} and {}",
+            from_node_id,
+            to_node_id
+        );
+
+        Self {
+            snr: snr.into(),
+            from: from_node_id,
+            to: to_node_id,
+            last_heard: chrono::Utc::now(),
+            timeout_duration: DEFAULT_NODE_TIMEOUT_DURATION,
+            broadcast_interval: None,
+            distance_meters: None,
+            bearing_degrees: None,
+            rx_statistics: None,
+        }
+    }
+
+    /// Creates an edge from a packet that the target received directly from
+    /// the source, adding it to the link's previous statistics
+    pub fn from_received_packet(
+        from_node_id: u32,
+        to_node_id: u32,
+        rx_snr: f32,
+        rx_rssi: i32,
+        previous_statistics: Option<LinkStatistics>,
+    ) -> Self {
+        let rx_statistics = match previous_statistics {
+            Some(mut statistics) => {
+                statistics.record(rx_snr, rx_rssi);
+                statistics
+            }
+            None => LinkStatistics::new(rx_snr, rx_rssi),
+        };
+
+        Self {
+            snr: rx_snr.into(),
+            from: from_node_id,
+            to: to_node_id,
+            last_heard: chrono::Utc::now(),
+            timeout_duration: DEFAULT_NODE_TIMEOUT_DURATION,
+            broadcast_interval: None,
+            distance_meters: None,
+            bearing_degrees: None,
+            rx_statistics: Some(rx_statistics),
         }
     }
 }
@@ -5,8 +5,12 @@ use serde::{Deserialize, Serialize};
 use tauri::async_runtime::JoinHandle;
 
 use super::{
+    delta::GraphDelta,
     edge,
+    history::GraphHistory,
     node::{self, GraphNode},
+    observations::{GraphObservations, ObservedEdge, ObservedNode, ObservedTopology},
+    timeout_policy::GraphTimeoutPolicy,
 };
 
 pub type InternalGraph = GraphMap<node::GraphNode, edge::GraphEdge, petgraph::Directed>;
@@ -14,9 +18,20 @@ pub type InternalGraph = GraphMap<node::GraphNode, edge::GraphEdge, petgraph::Di
 #[derive(Serialize, Deserialize)]
 pub struct MeshGraph {
     graph: InternalGraph,
+    // Holds the latest attributes of each node, which may be newer than the graph's keys
     pub nodes_lookup: HashMap<u32, GraphNode>, // TODO use NodeId -- need to implement serialize and deserialize
     #[serde(skip)]
     pub timeout_handle: Option<JoinHandle<()>>,
+    #[serde(skip)]
+    pub history: GraphHistory,
+    #[serde(skip)]
+    deltas: Vec<GraphDelta>,
+    #[serde(skip)]
+    pub observations: GraphObservations,
+    #[serde(skip)]
+    timeout_policy: GraphTimeoutPolicy,
+    #[serde(skip)]
+    infer_neighbor_nodes: bool,
 }
 
 impl Clone for MeshGraph {
@@ -25,6 +40,11 @@ impl Clone for MeshGraph {
             graph: self.graph.clone(),
             nodes_lookup: self.nodes_lookup.clone(),
             timeout_handle: None,
+            history: GraphHistory::default(), // History is only kept by the live graph
+            deltas: vec![],
+            observations: self.observations.clone(),
+            timeout_policy: self.timeout_policy,
+            infer_neighbor_nodes: self.infer_neighbor_nodes,
         }
     }
 }
@@ -35,17 +55,57 @@ impl MeshGraph {
             graph: GraphMap::new(),
             nodes_lookup: HashMap::new(),
             timeout_handle: None,
+            history: GraphHistory::default(),
+            deltas: vec![],
+            observations: GraphObservations::default(),
+            timeout_policy: GraphTimeoutPolicy::default(),
+            infer_neighbor_nodes: false,
         }
     }
+
+    pub fn infer_neighbor_nodes(&self) -> bool {
+        self.infer_neighbor_nodes
+    }
+
+    /// Sets whether neighbors that aren't in the graph are inserted as
+    /// inferred nodes, rather than their links being skipped
+    pub fn set_infer_neighbor_nodes(&mut self, enabled: bool) {
+        self.infer_neighbor_nodes = enabled;
+    }
+
+    pub fn timeout_policy(&self) -> GraphTimeoutPolicy {
+        self.timeout_policy
+    }
+
+    /// Replaces the timeout policy. The new policy applies to existing nodes
+    /// and links on the next clean, but their reported timeouts are only
+    /// updated when they are next heard from.
+    pub fn set_timeout_policy(&mut self, timeout_policy: GraphTimeoutPolicy) {
+        self.timeout_policy = timeout_policy;
+    }
+
+    /// Takes the changes made to the graph since this was last called
+    pub fn take_deltas(&mut self) -> Vec<GraphDelta> {
+        std::mem::take(&mut self.deltas)
+    }
 }
 
 impl MeshGraph {
-    fn add_node(&mut self, node: GraphNode) -> GraphNode {
+    fn add_node(&mut self, mut node: GraphNode) -> GraphNode {
+        node.timeout_duration = self
+            .timeout_policy
+            .node_timeout_for(node.broadcast_interval);
+
         let created_node = self.graph.add_node(node);
         self.nodes_lookup.insert(node.node_num, node);
+        self.deltas.push(GraphDelta::NodeUpserted { node });
         created_node
     }
 
+    pub fn internal_graph(&self) -> &InternalGraph {
+        &self.graph
+    }
+
     pub fn get_node(&self, node_num: u32) -> Option<GraphNode> {
         self.nodes_lookup.get(&node_num).cloned()
     }
@@ -54,15 +114,45 @@ impl MeshGraph {
         self.nodes_lookup.contains_key(&node_num)
     }
 
+    /// Inserts a node or replaces the attributes of an existing node, keeping
+    /// its links. Links are updated with the node's current position.
     pub fn upsert_node(&mut self, node: GraphNode) -> GraphNode {
-        if self.contains_node(node.node_num) {
-            self.remove_node(node.node_num);
+        if !self.contains_node(node.node_num) {
+            return self.add_node(node);
         }
 
-        self.add_node(node)
+        // Graph keys can't be updated in place, so the node is replaced and its
+        // links restored. The links themselves haven't changed, so only the
+        // node is reported.
+        let links: Vec<(GraphNode, GraphNode, edge::GraphEdge)> = self
+            .graph
+            .all_edges()
+            .filter(|(source, target, _)| *source == node || *target == node)
+            .map(|(source, target, edge)| (source, target, edge.clone()))
+            .collect();
+
+        self.detach_node(node.node_num);
+        let created_node = self.add_node(node);
+
+        for (source, target, edge) in links {
+            let source = if source == node { node } else { source };
+            let target = if target == node { node } else { target };
+
+            self.insert_edge(source, target, edge);
+        }
+
+        created_node
     }
 
     pub fn remove_node(&mut self, node_num: u32) -> Option<GraphNode> {
+        let removed_node = self.detach_node(node_num)?;
+        self.observations.forget_node(node_num);
+        self.deltas.push(GraphDelta::NodeRemoved { node_num });
+        Some(removed_node)
+    }
+
+    /// Removes a node and its links without reporting the change
+    fn detach_node(&mut self, node_num: u32) -> Option<GraphNode> {
         let graph_node = self.get_node(node_num)?;
 
         if self.graph.remove_node(graph_node) == false {
@@ -81,30 +171,79 @@ impl MeshGraph {
         target: GraphNode,
         edge: edge::GraphEdge,
     ) -> Option<edge::GraphEdge> {
-        if self.graph.contains_edge(source, target) {
-            self.remove_edge(source, target); // Remove the edge if it exists
+        let previous_edge = self.insert_edge(source, target, edge);
+
+        if let Some(edge) = self.graph.edge_weight(source, target) {
+            self.deltas.push(GraphDelta::EdgeUpserted {
+                from: source.node_num,
+                to: target.node_num,
+                edge: edge.clone(),
+            });
         }
 
+        previous_edge
+    }
+
+    /// Adds or replaces a link without reporting the change
+    fn insert_edge(
+        &mut self,
+        source: GraphNode,
+        target: GraphNode,
+        mut edge: edge::GraphEdge,
+    ) -> Option<edge::GraphEdge> {
+        if let Some(previous_edge) = self.graph.remove_edge(source, target) {
+            // Other sources of links don't carry received signal statistics
+            if edge.rx_statistics.is_none() {
+                edge.rx_statistics = previous_edge.rx_statistics;
+            }
+        }
+
+        edge.set_geometry(source.position, target.position);
+        edge.timeout_duration = self
+            .timeout_policy
+            .edge_timeout_for(edge.broadcast_interval);
+
         self.graph.add_edge(source, target, edge)
     }
 
+    pub fn get_edge(&self, from: u32, to: u32) -> Option<&edge::GraphEdge> {
+        let from = self.get_node(from)?;
+        let to = self.get_node(to)?;
+
+        self.graph.edge_weight(from, to)
+    }
+
     pub fn remove_edge(&mut self, from: GraphNode, to: GraphNode) -> Option<edge::GraphEdge> {
-        self.graph.remove_edge(from, to)
+        let removed_edge = self.graph.remove_edge(from, to)?;
+        self.observations.forget_edge(from.node_num, to.node_num);
+
+        self.deltas.push(GraphDelta::EdgeRemoved {
+            from: from.node_num,
+            to: to.node_num,
+        });
+
+        Some(removed_edge)
     }
 }
 
 impl MeshGraph {
     pub fn clean(&mut self) {
         let now = chrono::Utc::now();
+        let policy = self.timeout_policy;
+
+        let has_expired = |last_heard: chrono::DateTime<chrono::Utc>, timeout| {
+            now - last_heard
+                > chrono::TimeDelta::from_std(timeout).unwrap_or(chrono::TimeDelta::MAX)
+        };
 
         // Edges will be removed if either the source or target node is removed
         let mut nodes_to_remove = vec![];
 
         for node in self.nodes_lookup.values() {
-            if now - node.last_heard
-                > chrono::TimeDelta::from_std(node.timeout_duration)
-                    .expect("Duration out of range of TimeDelta")
-            {
+            if has_expired(
+                node.last_heard,
+                policy.node_timeout_for(node.broadcast_interval),
+            ) {
                 log::trace!("Node {} has timed out", node.node_num);
                 nodes_to_remove.push(node.node_num);
             } else {
@@ -116,5 +255,327 @@ impl MeshGraph {
             self.remove_node(node_num);
             log::debug!("Node {} removed from graph", node_num);
         }
+
+        // Links also expire on their own, as a node may stay in range of some
+        // nodes while losing others
+        let edges_to_remove: Vec<(GraphNode, GraphNode)> = self
+            .graph
+            .all_edges()
+            .filter(|(_, _, edge)| {
+                has_expired(
+                    edge.last_heard,
+                    policy.edge_timeout_for(edge.broadcast_interval),
+                )
+            })
+            .map(|(source, target, _)| (source, target))
+            .collect();
+
+        for (source, target) in edges_to_remove {
+            self.remove_edge(source, target);
+            log::debug!(
+                "Edge from {} to {} removed from graph",
+                source.node_num,
+                target.node_num
+            );
+        }
+
+        self.record_snapshot();
+    }
+}
+
+impl MeshGraph {
+    /// The graph with the radios that observed each node and link. If an
+    /// observer is passed, only what that radio observed is included.
+    pub fn observed_topology(&self, observer: Option<&str>) -> ObservedTopology {
+        let mut nodes: Vec<ObservedNode> = self
+            .nodes_lookup
+            .values()
+            .map(|node| ObservedNode {
+                node: *node,
+                observers: self.observations.node_observations(node.node_num),
+            })
+            .filter(|node| match observer {
+                Some(observer) => node.observers.iter().any(|o| o.observer == observer),
+                None => true,
+            })
+            .collect();
+        nodes.sort_by_key(|node| node.node.node_num);
+
+        let mut edges: Vec<ObservedEdge> = self
+            .graph
+            .all_edges()
+            .map(|(source, target, edge)| ObservedEdge {
+                from: source.node_num,
+                to: target.node_num,
+                edge: edge.clone(),
+                observers: self
+                    .observations
+                    .edge_observations(source.node_num, target.node_num),
+            })
+            .filter(|edge| match observer {
+                Some(observer) => edge.observers.iter().any(|o| o.observer == observer),
+                None => true,
+            })
+            .collect();
+        edges.sort_by_key(|edge| (edge.from, edge.to));
+
+        ObservedTopology { nodes, edges }
+    }
+
+    /// Records the current graph in its history if the topology has changed
+    pub fn record_snapshot(&mut self) {
+        let edges = self.graph.all_edges().map(|(_, _, edge)| edge);
+
+        if self
+            .history
+            .record(chrono::Utc::now(), self.nodes_lookup.values(), edges)
+        {
+            log::trace!("Recorded graph snapshot");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::device::{TracerouteHop, TracerouteResult};
+    use crate::graph::ds::{edge::GraphEdge, position::GraphPosition};
+    use meshtastic::protobufs;
+
+    #[test]
+    fn upsert_node_keeps_links_and_updates_geometry() {
+        let mut graph = MeshGraph::new();
+        let first = graph.upsert_node(GraphNode::new(1));
+        let second = graph.upsert_node(GraphNode::new(2));
+
+        graph.upsert_edge(first, second, GraphEdge::from_traceroute_hop(1, 2, 5.0));
+        graph.upsert_edge(second, first, GraphEdge::from_traceroute_hop(2, 1, 4.0));
+
+        let position = |latitude| GraphPosition {
+            latitude,
+            longitude: 0.0,
+            altitude: None,
+        };
+
+        graph.upsert_node(GraphNode {
+            position: Some(position(0.0)),
+            ..first
+        });
+        graph.upsert_node(GraphNode {
+            position: Some(position(1.0)),
+            ..second
+        });
+
+        let internal = graph.internal_graph();
+        assert_eq!(internal.edge_count(), 2);
+
+        let edge = internal.edge_weight(first, second).unwrap();
+        assert_eq!(edge.snr(), 5.0);
+        assert!((edge.distance_meters.unwrap() - 111_195.0).abs() < 10.0);
+        assert!(edge.bearing_degrees.unwrap().abs() < 1e-9);
+
+        let reverse = internal.edge_weight(second, first).unwrap();
+        assert!((reverse.bearing_degrees.unwrap() - 180.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn reports_changes_as_deltas() {
+        let mut graph = MeshGraph::new();
+        let first = graph.upsert_node(GraphNode::new(1));
+        let second = graph.upsert_node(GraphNode::new(2));
+        graph.upsert_edge(first, second, GraphEdge::from_traceroute_hop(1, 2, 5.0));
+        graph.take_deltas();
+
+        // Replacing a node only reports the node, not a removal or its restored link
+        graph.upsert_node(first);
+        let deltas = graph.take_deltas();
+        assert!(matches!(
+            deltas.as_slice(),
+            [GraphDelta::NodeUpserted { node }] if node.node_num == 1
+        ));
+        assert_eq!(graph.internal_graph().edge_count(), 1);
+
+        graph.remove_node(2);
+        assert!(matches!(
+            graph.take_deltas().as_slice(),
+            [GraphDelta::NodeRemoved { node_num: 2 }]
+        ));
+        assert!(graph.take_deltas().is_empty());
+    }
+
+    #[test]
+    fn filters_topology_by_observer() {
+        let mut graph = MeshGraph::new();
+        let (first, second) = ("radio-a".to_string(), "radio-b".to_string());
+
+        let traceroute = |route: Vec<u32>| TracerouteResult {
+            destination: *route.last().unwrap(),
+            timestamp: 0,
+            route: route
+                .into_iter()
+                .map(|node_num| TracerouteHop {
+                    node_num,
+                    snr: Some(1.0),
+                })
+                .collect(),
+            route_back: vec![],
+        };
+
+        graph.update_from_traceroute(&first, &traceroute(vec![1, 2]));
+        graph.update_from_traceroute(&second, &traceroute(vec![2, 3]));
+
+        let merged = graph.observed_topology(None);
+        assert_eq!(merged.nodes.len(), 3);
+        assert_eq!(merged.nodes[1].observers.len(), 2);
+
+        let seen_by_first = graph.observed_topology(Some(&first));
+        let nodes: Vec<u32> = seen_by_first
+            .nodes
+            .iter()
+            .map(|n| n.node.node_num)
+            .collect();
+        assert_eq!(nodes, vec![1, 2]);
+        assert_eq!(seen_by_first.edges.len(), 1);
+        assert_eq!(seen_by_first.edges[0].observers[0].observer, first);
+    }
+
+    #[test]
+    fn keeps_every_traceroute_hop() {
+        let mut graph = MeshGraph::new();
+        let hop = |node_num| TracerouteHop {
+            node_num,
+            snr: Some(2.0),
+        };
+
+        graph.update_from_traceroute(
+            &"radio".to_string(),
+            &TracerouteResult {
+                destination: 4,
+                timestamp: 0,
+                route: vec![hop(1), hop(2), hop(3), hop(4)],
+                route_back: vec![hop(4), hop(3), hop(2), hop(1)],
+            },
+        );
+
+        let internal = graph.internal_graph();
+        assert_eq!(internal.node_count(), 4);
+        assert_eq!(internal.edge_count(), 6);
+        assert!(graph.get_edge(1, 2).is_some());
+        assert!(graph.get_edge(2, 1).is_some());
+    }
+
+    #[test]
+    fn clean_removes_expired_edges_of_live_nodes() {
+        let mut graph = MeshGraph::new();
+        let first = graph.upsert_node(GraphNode::new(1));
+        let second = graph.upsert_node(GraphNode::new(2));
+
+        let mut edge = GraphEdge::from_traceroute_hop(1, 2, 5.0);
+        edge.last_heard = chrono::Utc::now() - chrono::TimeDelta::minutes(10);
+        graph.upsert_edge(first, second, edge);
+
+        graph.set_timeout_policy(GraphTimeoutPolicy {
+            edge_timeout: std::time::Duration::from_secs(5 * 60),
+            ..Default::default()
+        });
+        graph.clean();
+
+        assert_eq!(graph.internal_graph().node_count(), 2);
+        assert_eq!(graph.internal_graph().edge_count(), 0);
+    }
+
+    #[test]
+    fn infers_unknown_neighbors_until_heard_directly() {
+        let mut graph = MeshGraph::new();
+        let observer = "radio-a".to_string();
+        graph.set_infer_neighbor_nodes(true);
+
+        let packet = |from| protobufs::MeshPacket {
+            from,
+            ..Default::default()
+        };
+
+        let neighbor_info = protobufs::NeighborInfo {
+            node_id: 1,
+            neighbors: vec![protobufs::Neighbor {
+                node_id: 2,
+                snr: 3.0,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        graph.update_from_neighbor_info(&observer, packet(1), neighbor_info);
+
+        assert!(!graph.get_node(1).unwrap().inferred);
+        assert!(graph.get_node(2).unwrap().inferred);
+        assert_eq!(graph.internal_graph().edge_count(), 1);
+
+        graph.update_from_position(&observer, packet(2), protobufs::Position::default());
+
+        assert!(!graph.get_node(2).unwrap().inferred);
+        assert_eq!(graph.internal_graph().edge_count(), 1);
+    }
+
+    #[test]
+    fn infers_links_from_received_packets() {
+        let mut graph = MeshGraph::new();
+        let observer = "radio-a".to_string();
+        let my_node_num = 0x0a0b_0c0d;
+
+        let packet = |from, hop_limit, relay_node, rx_snr| protobufs::MeshPacket {
+            from,
+            hop_start: 3,
+            hop_limit,
+            relay_node,
+            rx_snr,
+            rx_rssi: -100,
+            ..Default::default()
+        };
+
+        graph.update_from_received_packet(&observer, my_node_num, &packet(0x11, 3, 0, 6.0));
+        graph.take_deltas();
+        graph.update_from_received_packet(&observer, my_node_num, &packet(0x11, 3, 0, 2.0));
+
+        // Packets over a known link only report the link and don't change the topology
+        assert!(matches!(
+            graph.take_deltas().as_slice(),
+            [GraphDelta::EdgeUpserted { from: 0x11, .. }]
+        ));
+        assert_eq!(graph.history.timestamps().len(), 1);
+
+        let statistics = graph
+            .get_edge(0x11, my_node_num)
+            .unwrap()
+            .rx_statistics
+            .unwrap();
+        assert_eq!(statistics.packet_count, 2);
+        assert_eq!(statistics.mean_snr, 4.0);
+
+        // Relayed packets are attributed to the relay when it can be identified
+        graph.update_from_received_packet(&observer, my_node_num, &packet(0x22, 1, 0x11, 1.0));
+        assert!(graph.get_edge(0x22, my_node_num).is_none());
+        assert_eq!(
+            graph
+                .get_edge(0x11, my_node_num)
+                .unwrap()
+                .rx_statistics
+                .unwrap()
+                .packet_count,
+            3
+        );
+
+        // Relays matching several nodes are ambiguous
+        graph.upsert_node(GraphNode::new(0x0111));
+        graph.update_from_received_packet(&observer, my_node_num, &packet(0x22, 1, 0x11, 1.0));
+        assert_eq!(
+            graph
+                .get_edge(0x11, my_node_num)
+                .unwrap()
+                .rx_statistics
+                .unwrap()
+                .packet_count,
+            3
+        );
     }
 }
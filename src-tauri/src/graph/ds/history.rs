@@ -0,0 +1,282 @@
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+use chrono::{DateTime, Utc};
+use meshtastic::ts::specta::{self, Type};
+use serde::{Deserialize, Serialize};
+
+use super::{edge::GraphEdge, node::GraphNode};
+
+/// Number of points in time the graph is kept for before the oldest are discarded
+pub const MAX_GRAPH_SNAPSHOTS: usize = 2_000;
+
+/// The nodes and links of the graph at a point in time
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphSnapshot {
+    pub timestamp: DateTime<Utc>,
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+impl GraphSnapshot {
+    fn node_nums(&self) -> BTreeSet<u32> {
+        self.nodes.iter().map(|node| node.node_num).collect()
+    }
+
+    fn links(&self) -> BTreeSet<(u32, u32)> {
+        self.edges
+            .iter()
+            .map(|edge| (edge.from_node(), edge.to_node()))
+            .collect()
+    }
+
+    fn apply(&mut self, change: &TopologyChange) {
+        self.timestamp = change.timestamp;
+
+        self.nodes
+            .retain(|node| !change.removed_nodes.contains(&node.node_num));
+        self.nodes.extend(change.added_nodes.iter().copied());
+        self.nodes.sort();
+
+        self.edges.retain(|edge| {
+            !change
+                .removed_edges
+                .contains(&(edge.from_node(), edge.to_node()))
+        });
+        self.edges.extend(change.added_edges.iter().cloned());
+        self.edges.sort_by_key(|e| (e.from_node(), e.to_node()));
+    }
+}
+
+/// Nodes and links that appeared or disappeared between two points in time
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct TopologyDiff {
+    pub added_nodes: Vec<u32>,
+    pub removed_nodes: Vec<u32>,
+    pub added_edges: Vec<(u32, u32)>,
+    pub removed_edges: Vec<(u32, u32)>,
+}
+
+/// A change to the topology of the graph, which only holds the nodes and
+/// links that changed rather than the whole graph
+#[derive(Debug, Clone)]
+struct TopologyChange {
+    timestamp: DateTime<Utc>,
+    added_nodes: Vec<GraphNode>,
+    removed_nodes: Vec<u32>,
+    added_edges: Vec<GraphEdge>,
+    removed_edges: Vec<(u32, u32)>,
+}
+
+/// Topology of the graph over time, kept as the oldest graph followed by
+/// the changes to it. Nodes and links keep the attributes they had when
+/// they were added.
+#[derive(Debug, Clone, Default)]
+pub struct GraphHistory {
+    base: Option<GraphSnapshot>,
+    changes: VecDeque<TopologyChange>,
+
+    // Topology after the latest change, so that unchanged graphs are
+    // detected without rebuilding them
+    latest_nodes: BTreeSet<u32>,
+    latest_links: BTreeSet<(u32, u32)>,
+}
+
+impl GraphHistory {
+    /// Records the graph if its topology differs from the latest recorded one.
+    /// Returns whether the graph was recorded.
+    pub fn record<'a>(
+        &mut self,
+        timestamp: DateTime<Utc>,
+        nodes: impl IntoIterator<Item = &'a GraphNode>,
+        edges: impl IntoIterator<Item = &'a GraphEdge>,
+    ) -> bool {
+        let nodes: BTreeMap<u32, &GraphNode> = nodes
+            .into_iter()
+            .map(|node| (node.node_num, node))
+            .collect();
+        let edges: BTreeMap<(u32, u32), &GraphEdge> = edges
+            .into_iter()
+            .map(|edge| ((edge.from_node(), edge.to_node()), edge))
+            .collect();
+
+        let node_nums: BTreeSet<u32> = nodes.keys().copied().collect();
+        let links: BTreeSet<(u32, u32)> = edges.keys().copied().collect();
+
+        if self.base.is_some() && node_nums == self.latest_nodes && links == self.latest_links {
+            return false;
+        }
+
+        if self.base.is_none() {
+            self.base = Some(GraphSnapshot {
+                timestamp,
+                nodes: nodes.values().map(|node| **node).collect(),
+                edges: edges.values().map(|edge| (*edge).clone()).collect(),
+            });
+        } else {
+            self.changes.push_back(TopologyChange {
+                timestamp,
+                added_nodes: node_nums
+                    .difference(&self.latest_nodes)
+                    .map(|node_num| *nodes[node_num])
+                    .collect(),
+                removed_nodes: self.latest_nodes.difference(&node_nums).copied().collect(),
+                added_edges: links
+                    .difference(&self.latest_links)
+                    .map(|link| edges[link].clone())
+                    .collect(),
+                removed_edges: self.latest_links.difference(&links).copied().collect(),
+            });
+        }
+
+        self.latest_nodes = node_nums;
+        self.latest_links = links;
+
+        // Fold the oldest changes into the base graph, which keeps the
+        // history bounded no matter how large the graph grows
+        while self.changes.len() >= MAX_GRAPH_SNAPSHOTS {
+            if let (Some(base), Some(change)) = (self.base.as_mut(), self.changes.pop_front()) {
+                base.apply(&change);
+            }
+        }
+
+        true
+    }
+
+    pub fn timestamps(&self) -> Vec<DateTime<Utc>> {
+        self.base
+            .iter()
+            .map(|base| base.timestamp)
+            .chain(self.changes.iter().map(|change| change.timestamp))
+            .collect()
+    }
+
+    /// The graph as it was at the passed time, which is the latest graph
+    /// recorded at or before it
+    pub fn at(&self, timestamp: DateTime<Utc>) -> Option<GraphSnapshot> {
+        let mut snapshot = self
+            .base
+            .clone()
+            .filter(|base| base.timestamp <= timestamp)?;
+
+        for change in self
+            .changes
+            .iter()
+            .take_while(|change| change.timestamp <= timestamp)
+        {
+            snapshot.apply(change);
+        }
+
+        Some(snapshot)
+    }
+
+    /// Compares the graph at two points in time. The graph is treated as
+    /// empty before the first recorded graph.
+    pub fn diff(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> TopologyDiff {
+        let (from_nodes, from_links) = self
+            .at(from)
+            .map(|s| (s.node_nums(), s.links()))
+            .unwrap_or_default();
+
+        let (to_nodes, to_links) = self
+            .at(to)
+            .map(|s| (s.node_nums(), s.links()))
+            .unwrap_or_default();
+
+        TopologyDiff {
+            added_nodes: to_nodes.difference(&from_nodes).copied().collect(),
+            removed_nodes: from_nodes.difference(&to_nodes).copied().collect(),
+            added_edges: to_links.difference(&from_links).copied().collect(),
+            removed_edges: from_links.difference(&to_links).copied().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(
+        history: &mut GraphHistory,
+        seconds: i64,
+        nodes: &[u32],
+        links: &[(u32, u32)],
+    ) -> bool {
+        let nodes: Vec<GraphNode> = nodes.iter().map(|n| GraphNode::new(*n)).collect();
+        let edges: Vec<GraphEdge> = links
+            .iter()
+            .map(|(from, to)| GraphEdge::from_traceroute_hop(*from, *to, 0.0))
+            .collect();
+
+        history.record(time(seconds), &nodes, &edges)
+    }
+
+    fn time(seconds: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(seconds, 0).unwrap()
+    }
+
+    #[test]
+    fn skips_snapshots_without_topology_changes() {
+        let mut history = GraphHistory::default();
+
+        assert!(record(&mut history, 10, &[1, 2], &[(1, 2)]));
+        assert!(!record(&mut history, 20, &[2, 1], &[(1, 2)]));
+        assert!(record(&mut history, 30, &[1, 2], &[(2, 1)]));
+
+        assert_eq!(history.timestamps(), vec![time(10), time(30)]);
+    }
+
+    #[test]
+    fn finds_graph_at_time() {
+        let mut history = GraphHistory::default();
+        record(&mut history, 10, &[1], &[]);
+        record(&mut history, 20, &[1, 2], &[]);
+
+        assert!(history.at(time(5)).is_none());
+        assert_eq!(history.at(time(15)).unwrap().timestamp, time(10));
+        assert_eq!(history.at(time(20)).unwrap().timestamp, time(20));
+        assert_eq!(
+            history.at(time(20)).unwrap().node_nums(),
+            BTreeSet::from([1, 2])
+        );
+    }
+
+    #[test]
+    fn diffs_topology_between_times() {
+        let mut history = GraphHistory::default();
+        record(&mut history, 10, &[1, 2], &[(1, 2)]);
+        record(&mut history, 20, &[2, 3], &[(2, 3)]);
+
+        let diff = history.diff(time(10), time(25));
+        assert_eq!(
+            diff,
+            TopologyDiff {
+                added_nodes: vec![3],
+                removed_nodes: vec![1],
+                added_edges: vec![(2, 3)],
+                removed_edges: vec![(1, 2)],
+            }
+        );
+
+        // Everything is new when compared to before the first snapshot
+        assert_eq!(history.diff(time(0), time(10)).added_nodes, vec![1, 2]);
+    }
+
+    #[test]
+    fn discards_oldest_snapshots() {
+        let mut history = GraphHistory::default();
+
+        for i in 0..(MAX_GRAPH_SNAPSHOTS as u32 + 5) {
+            record(&mut history, i as i64, &[i], &[]);
+        }
+
+        let timestamps = history.timestamps();
+        assert_eq!(timestamps.len(), MAX_GRAPH_SNAPSHOTS);
+        assert_eq!(timestamps[0], time(5));
+
+        // Discarded changes are folded into the oldest graph that's kept
+        let oldest = history.at(time(5)).unwrap();
+        assert_eq!(oldest.node_nums(), BTreeSet::from([5]));
+    }
+}
@@ -0,0 +1,68 @@
+use meshtastic::ts::specta::{self, Type};
+use serde::{Deserialize, Serialize};
+
+/// Running signal statistics of the packets received over a link
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkStatistics {
+    pub packet_count: u32,
+    pub mean_snr: f64,
+    pub min_snr: f64,
+    pub max_snr: f64,
+    /// `None` until a packet with a reported RSSI is received
+    pub mean_rssi: Option<f64>,
+    rssi_count: u32,
+}
+
+impl LinkStatistics {
+    pub fn new(snr: f32, rssi: i32) -> Self {
+        let mut statistics = Self {
+            packet_count: 0,
+            mean_snr: 0.0,
+            min_snr: f64::MAX,
+            max_snr: f64::MIN,
+            mean_rssi: None,
+            rssi_count: 0,
+        };
+
+        statistics.record(snr, rssi);
+        statistics
+    }
+
+    /// Adds a received packet to the statistics. An RSSI of zero means that
+    /// the radio didn't report one.
+    pub fn record(&mut self, snr: f32, rssi: i32) {
+        let snr = f64::from(snr);
+
+        self.packet_count = self.packet_count.saturating_add(1);
+        self.mean_snr += (snr - self.mean_snr) / f64::from(self.packet_count);
+        self.min_snr = self.min_snr.min(snr);
+        self.max_snr = self.max_snr.max(snr);
+
+        if rssi != 0 {
+            self.rssi_count = self.rssi_count.saturating_add(1);
+
+            let mean_rssi = self.mean_rssi.unwrap_or_default();
+            self.mean_rssi =
+                Some(mean_rssi + (f64::from(rssi) - mean_rssi) / f64::from(self.rssi_count));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracks_running_signal_statistics() {
+        let mut statistics = LinkStatistics::new(4.0, -90);
+        statistics.record(-2.0, 0);
+        statistics.record(7.0, -100);
+
+        assert_eq!(statistics.packet_count, 3);
+        assert_eq!(statistics.mean_snr, 3.0);
+        assert_eq!(statistics.min_snr, -2.0);
+        assert_eq!(statistics.max_snr, 7.0);
+        assert_eq!(statistics.mean_rssi, Some(-95.0));
+    }
+}
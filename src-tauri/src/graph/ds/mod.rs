@@ -1,3 +1,9 @@
+pub mod delta;
 pub mod edge;
 pub mod graph;
+pub mod history;
+pub mod link_statistics;
 pub mod node;
+pub mod observations;
+pub mod position;
+pub mod timeout_policy;
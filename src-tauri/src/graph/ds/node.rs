@@ -1,3 +1,5 @@
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
 use std::time::Duration;
 
 use chrono::{DateTime, Utc};
@@ -10,12 +12,53 @@ use serde::{Deserialize, Serialize};
 
 use crate::graph::api::update_from_packet::DEFAULT_NODE_TIMEOUT_DURATION;
 
-#[derive(Debug, Clone, Serialize, Deserialize, Copy, Hash, PartialOrd, Ord, Type)]
+use super::{position::GraphPosition, timeout_policy::advertised_interval};
+
+/// A node in the mesh graph. Nodes are identified by their node number alone,
+/// so that a node's attributes can change without affecting its links.
+#[derive(Debug, Clone, Serialize, Deserialize, Copy, Type)]
 #[serde(rename_all = "camelCase")]
 pub struct GraphNode {
     pub node_num: u32,
     pub last_heard: DateTime<Utc>,
     pub timeout_duration: Duration,
+    /// Interval at which the node advertised that it broadcasts neighbor info
+    pub broadcast_interval: Option<Duration>,
+    pub position: Option<GraphPosition>,
+    /// Whether the node is only known from another node's neighbor info,
+    /// and hasn't been heard from directly
+    pub inferred: bool,
+}
+
+impl GraphNode {
+    pub fn new(node_num: u32) -> Self {
+        Self {
+            node_num,
+            last_heard: chrono::Utc::now(),
+            timeout_duration: DEFAULT_NODE_TIMEOUT_DURATION,
+            broadcast_interval: None,
+            position: None,
+            inferred: false,
+        }
+    }
+}
+
+impl Hash for GraphNode {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.node_num.hash(state);
+    }
+}
+
+impl PartialOrd for GraphNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for GraphNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.node_num.cmp(&other.node_num)
+    }
 }
 
 impl PartialEq<GraphNode> for GraphNode {
@@ -55,6 +98,9 @@ impl From<protobufs::NeighborInfo> for GraphNode {
             last_heard: DateTime::from_timestamp_millis(chrono::Utc::now().timestamp_millis())
                 .expect("Failed to convert timestamp to DateTime"),
             timeout_duration: Duration::from_secs(timeout_secs),
+            broadcast_interval: advertised_interval(neighbor_info.node_broadcast_interval_secs),
+            position: None,
+            inferred: false,
         }
     }
 }
@@ -88,6 +134,9 @@ impl From<Neighbor> for GraphNode {
             last_heard: DateTime::from_timestamp_millis(last_heard_secs * 1000)
                 .expect("Failed to convert timestamp to DateTime"),
             timeout_duration: Duration::from_secs(timeout_secs),
+            broadcast_interval: advertised_interval(neighbor.node_broadcast_interval_secs),
+            position: None,
+            inferred: false,
         }
     }
 }
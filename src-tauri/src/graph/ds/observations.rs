@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use meshtastic::ts::specta::{self, Type};
+use serde::{Deserialize, Serialize};
+
+use crate::state::DeviceKey;
+
+use super::{edge::GraphEdge, node::GraphNode};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeObservation {
+    pub observer: DeviceKey,
+    pub last_heard: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct EdgeObservation {
+    pub observer: DeviceKey,
+    /// SNR (dB) of the link as reported through this observer
+    pub snr: f64,
+    pub last_heard: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ObservedNode {
+    pub node: GraphNode,
+    pub observers: Vec<NodeObservation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ObservedEdge {
+    pub from: u32,
+    pub to: u32,
+    /// The latest version of the link reported by any observer
+    pub edge: GraphEdge,
+    pub observers: Vec<EdgeObservation>,
+}
+
+/// The graph merged from all connected radios, with the radios that
+/// observed each node and link
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ObservedTopology {
+    pub nodes: Vec<ObservedNode>,
+    pub edges: Vec<ObservedEdge>,
+}
+
+/// Tracks which connected radio reported each node and link of the graph
+#[derive(Debug, Clone, Default)]
+pub struct GraphObservations {
+    nodes: HashMap<u32, HashMap<DeviceKey, DateTime<Utc>>>,
+    edges: HashMap<(u32, u32), HashMap<DeviceKey, EdgeObservation>>,
+}
+
+impl GraphObservations {
+    pub fn observe_node(&mut self, observer: &DeviceKey, node_num: u32) {
+        self.nodes
+            .entry(node_num)
+            .or_default()
+            .insert(observer.clone(), chrono::Utc::now());
+    }
+
+    pub fn observe_edge(&mut self, observer: &DeviceKey, from: u32, to: u32, snr: f64) {
+        let observation = EdgeObservation {
+            observer: observer.clone(),
+            snr,
+            last_heard: chrono::Utc::now(),
+        };
+
+        self.edges
+            .entry((from, to))
+            .or_default()
+            .insert(observer.clone(), observation);
+    }
+
+    /// Forgets a node along with the observations of its links
+    pub fn forget_node(&mut self, node_num: u32) {
+        self.nodes.remove(&node_num);
+        self.edges
+            .retain(|(from, to), _| *from != node_num && *to != node_num);
+    }
+
+    pub fn forget_edge(&mut self, from: u32, to: u32) {
+        self.edges.remove(&(from, to));
+    }
+
+    /// Forgets everything a radio observed, such as when it's disconnected
+    pub fn forget_observer(&mut self, observer: &DeviceKey) {
+        self.nodes.retain(|_, observers| {
+            observers.remove(observer);
+            !observers.is_empty()
+        });
+
+        self.edges.retain(|_, observers| {
+            observers.remove(observer);
+            !observers.is_empty()
+        });
+    }
+
+    /// Observations of a node, ordered by observer
+    pub fn node_observations(&self, node_num: u32) -> Vec<NodeObservation> {
+        let mut observations: Vec<NodeObservation> = self
+            .nodes
+            .get(&node_num)
+            .map(|observers| {
+                observers
+                    .iter()
+                    .map(|(observer, last_heard)| NodeObservation {
+                        observer: observer.clone(),
+                        last_heard: *last_heard,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        observations.sort_by(|a, b| a.observer.cmp(&b.observer));
+        observations
+    }
+
+    /// Observations of a link, ordered by observer
+    pub fn edge_observations(&self, from: u32, to: u32) -> Vec<EdgeObservation> {
+        let mut observations: Vec<EdgeObservation> = self
+            .edges
+            .get(&(from, to))
+            .map(|observers| observers.values().cloned().collect())
+            .unwrap_or_default();
+
+        observations.sort_by(|a, b| a.observer.cmp(&b.observer));
+        observations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_latest_snr_per_observer() {
+        let mut observations = GraphObservations::default();
+        let (first, second) = ("radio-a".to_string(), "radio-b".to_string());
+
+        observations.observe_edge(&first, 1, 2, 4.0);
+        observations.observe_edge(&second, 1, 2, -6.5);
+        observations.observe_edge(&first, 1, 2, 7.25);
+
+        let snrs: Vec<(DeviceKey, f64)> = observations
+            .edge_observations(1, 2)
+            .into_iter()
+            .map(|o| (o.observer, o.snr))
+            .collect();
+
+        assert_eq!(snrs, vec![(first, 7.25), (second, -6.5)]);
+    }
+
+    #[test]
+    fn forgetting_a_node_forgets_its_links() {
+        let mut observations = GraphObservations::default();
+        let observer = "radio-a".to_string();
+
+        observations.observe_node(&observer, 1);
+        observations.observe_node(&observer, 2);
+        observations.observe_edge(&observer, 1, 2, 0.0);
+        observations.observe_edge(&observer, 2, 3, 0.0);
+
+        observations.forget_node(1);
+
+        assert!(observations.node_observations(1).is_empty());
+        assert_eq!(observations.node_observations(2).len(), 1);
+        assert!(observations.edge_observations(1, 2).is_empty());
+        assert_eq!(observations.edge_observations(2, 3).len(), 1);
+    }
+
+    #[test]
+    fn forgetting_an_observer_keeps_other_observations() {
+        let mut observations = GraphObservations::default();
+        let (first, second) = ("radio-a".to_string(), "radio-b".to_string());
+
+        observations.observe_node(&first, 1);
+        observations.observe_node(&second, 1);
+        observations.observe_edge(&first, 1, 2, 0.0);
+
+        observations.forget_observer(&first);
+
+        let observers: Vec<DeviceKey> = observations
+            .node_observations(1)
+            .into_iter()
+            .map(|o| o.observer)
+            .collect();
+
+        assert_eq!(observers, vec![second]);
+        assert!(observations.edge_observations(1, 2).is_empty());
+        assert!(observations.edges.is_empty());
+    }
+}
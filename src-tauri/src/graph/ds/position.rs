@@ -0,0 +1,94 @@
+use meshtastic::{
+    protobufs,
+    ts::specta::{self, Type},
+};
+use serde::{Deserialize, Serialize};
+
+/// Mean radius of the Earth in meters
+const EARTH_RADIUS_METERS: f64 = 6_371_008.8;
+
+/// Scale of the integer coordinates reported by nodes
+const LOCATION_FIELD_SCALE: f64 = 1e7;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphPosition {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude: Option<i32>,
+}
+
+impl GraphPosition {
+    /// Normalizes a position reported by a node. Returns `None` if the
+    /// position has no fix, which the firmware reports as null island.
+    pub fn from_position(position: &protobufs::Position) -> Option<Self> {
+        let latitude_i = position.latitude_i.unwrap_or_default();
+        let longitude_i = position.longitude_i.unwrap_or_default();
+
+        if latitude_i == 0 && longitude_i == 0 {
+            return None;
+        }
+
+        Some(Self {
+            // Kept at full precision, unlike `NormalizedPosition`
+            latitude: latitude_i as f64 / LOCATION_FIELD_SCALE,
+            longitude: longitude_i as f64 / LOCATION_FIELD_SCALE,
+            altitude: position.altitude,
+        })
+    }
+
+    /// Great-circle distance to another position in meters, ignoring altitude
+    pub fn distance_to(&self, other: &GraphPosition) -> f64 {
+        let (lat_1, lat_2) = (self.latitude.to_radians(), other.latitude.to_radians());
+        let delta_lat = lat_2 - lat_1;
+        let delta_lon = (other.longitude - self.longitude).to_radians();
+
+        let a = (delta_lat / 2.0).sin().powi(2)
+            + lat_1.cos() * lat_2.cos() * (delta_lon / 2.0).sin().powi(2);
+
+        2.0 * EARTH_RADIUS_METERS * a.sqrt().atan2((1.0 - a).sqrt())
+    }
+
+    /// Initial bearing towards another position in degrees clockwise from true north
+    pub fn bearing_to(&self, other: &GraphPosition) -> f64 {
+        let (lat_1, lat_2) = (self.latitude.to_radians(), other.latitude.to_radians());
+        let delta_lon = (other.longitude - self.longitude).to_radians();
+
+        let y = delta_lon.sin() * lat_2.cos();
+        let x = lat_1.cos() * lat_2.sin() - lat_1.sin() * lat_2.cos() * delta_lon.cos();
+
+        (y.atan2(x).to_degrees() + 360.0) % 360.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(latitude: f64, longitude: f64) -> GraphPosition {
+        GraphPosition {
+            latitude,
+            longitude,
+            altitude: None,
+        }
+    }
+
+    #[test]
+    fn computes_distance_and_bearing() {
+        let origin = position(0.0, 0.0);
+        let north = position(1.0, 0.0);
+        let east = position(0.0, 1.0);
+
+        // One degree of latitude is about 111.2 km
+        assert!((origin.distance_to(&north) - 111_195.0).abs() < 10.0);
+        assert!((origin.bearing_to(&north) - 0.0).abs() < 1e-9);
+        assert!((origin.bearing_to(&east) - 90.0).abs() < 1e-9);
+        assert!((east.bearing_to(&origin) - 270.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn ignores_positions_without_fix() {
+        let position = protobufs::Position::default();
+        assert!(GraphPosition::from_position(&position).is_none());
+    }
+}
@@ -0,0 +1,111 @@
+use std::time::Duration;
+
+use meshtastic::ts::specta::{self, Type};
+use serde::{Deserialize, Serialize};
+
+use crate::graph::api::update_from_packet::DEFAULT_NODE_TIMEOUT_DURATION;
+
+pub const DEFAULT_EDGE_TIMEOUT_DURATION: Duration = DEFAULT_NODE_TIMEOUT_DURATION;
+pub const DEFAULT_CLEAN_PERIOD: Duration = Duration::from_secs(60);
+
+/// Longest timeout or clean period that can be configured
+pub const MAX_TIMEOUT_DURATION: Duration = Duration::from_secs(365 * 24 * 60 * 60);
+pub const MAX_BROADCAST_INTERVAL_MULTIPLIER: f64 = 1_000.0;
+
+/// Controls when the graph cleaner removes nodes and links that haven't
+/// been heard from
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphTimeoutPolicy {
+    /// Timeout of nodes that don't advertise a broadcast interval
+    pub node_timeout: Duration,
+
+    /// Timeout of links that don't advertise a broadcast interval
+    pub edge_timeout: Duration,
+
+    /// Number of advertised broadcast intervals that may be missed before
+    /// a node or link times out
+    pub broadcast_interval_multiplier: f64,
+
+    /// Time between runs of the graph cleaner
+    pub clean_period: Duration,
+}
+
+impl Default for GraphTimeoutPolicy {
+    fn default() -> Self {
+        Self {
+            node_timeout: DEFAULT_NODE_TIMEOUT_DURATION,
+            edge_timeout: DEFAULT_EDGE_TIMEOUT_DURATION,
+            broadcast_interval_multiplier: 1.0,
+            clean_period: DEFAULT_CLEAN_PERIOD,
+        }
+    }
+}
+
+impl GraphTimeoutPolicy {
+    pub fn node_timeout_for(&self, broadcast_interval: Option<Duration>) -> Duration {
+        self.timeout_for(broadcast_interval, self.node_timeout)
+    }
+
+    pub fn edge_timeout_for(&self, broadcast_interval: Option<Duration>) -> Duration {
+        self.timeout_for(broadcast_interval, self.edge_timeout)
+    }
+
+    fn timeout_for(&self, broadcast_interval: Option<Duration>, default: Duration) -> Duration {
+        match broadcast_interval {
+            // Saturates rather than panicking on intervals too long to represent
+            Some(interval) => Duration::try_from_secs_f64(
+                interval.as_secs_f64() * self.broadcast_interval_multiplier,
+            )
+            .unwrap_or(Duration::MAX),
+            None => default,
+        }
+    }
+}
+
+/// Converts a broadcast interval reported by the firmware, where zero means
+/// that no interval was advertised
+pub fn advertised_interval(interval_secs: u32) -> Option<Duration> {
+    match interval_secs {
+        0 => None,
+        secs => Some(Duration::from_secs(secs.into())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scales_advertised_intervals() {
+        let policy = GraphTimeoutPolicy {
+            node_timeout: Duration::from_secs(600),
+            edge_timeout: Duration::from_secs(300),
+            broadcast_interval_multiplier: 2.5,
+            ..Default::default()
+        };
+
+        let interval = advertised_interval(120);
+        assert_eq!(policy.node_timeout_for(interval), Duration::from_secs(300));
+        assert_eq!(policy.edge_timeout_for(interval), Duration::from_secs(300));
+
+        assert_eq!(policy.node_timeout_for(None), Duration::from_secs(600));
+        assert_eq!(
+            policy.edge_timeout_for(advertised_interval(0)),
+            Duration::from_secs(300)
+        );
+    }
+
+    #[test]
+    fn saturates_scaled_intervals() {
+        let policy = GraphTimeoutPolicy {
+            broadcast_interval_multiplier: f64::MAX,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            policy.node_timeout_for(advertised_interval(u32::MAX)),
+            Duration::MAX
+        );
+    }
+}
@@ -1,2 +1,3 @@
+pub mod algorithms;
 pub mod api;
 pub mod ds;
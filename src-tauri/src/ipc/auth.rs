@@ -0,0 +1,174 @@
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
+
+use rand::{distributions::Alphanumeric, Rng};
+
+/// Environment variable the API token is read from when none is configured
+pub const API_TOKEN_ENV_VAR: &str = "MESHTASTIC_API_TOKEN";
+
+const GENERATED_TOKEN_LENGTH: usize = 32;
+
+/// Credentials that clients of the socket and HTTP APIs must present
+#[derive(Clone)]
+pub struct ApiAuth {
+    token: String,
+
+    /// Whether the token was generated rather than configured, in which
+    /// case it has to be handed to the user
+    generated: bool,
+
+    /// Web origins allowed to call the HTTP API from a browser
+    allowed_origins: Vec<String>,
+}
+
+impl ApiAuth {
+    pub fn new(token: String) -> Self {
+        Self {
+            token,
+            generated: false,
+            allowed_origins: vec![],
+        }
+    }
+
+    pub fn with_allowed_origins(self, allowed_origins: Vec<String>) -> Self {
+        Self {
+            allowed_origins,
+            ..self
+        }
+    }
+
+    /// Uses the passed token, falling back to the token in the environment.
+    /// If neither is set, a random token is generated so that the API is
+    /// never served without authentication. The generated token is never
+    /// logged, see `generated_token` and `write_token_file`.
+    pub fn from_config(token: Option<String>) -> Self {
+        let token = token
+            .or_else(|| std::env::var(API_TOKEN_ENV_VAR).ok())
+            .filter(|token| !token.is_empty());
+
+        match token {
+            Some(token) => Self::new(token),
+            None => {
+                let token: String = rand::thread_rng()
+                    .sample_iter(&Alphanumeric)
+                    .take(GENERATED_TOKEN_LENGTH)
+                    .map(char::from)
+                    .collect();
+
+                Self {
+                    generated: true,
+                    ..Self::new(token)
+                }
+            }
+        }
+    }
+
+    /// The token, if it was generated rather than configured
+    pub fn generated_token(&self) -> Option<&str> {
+        self.generated.then_some(self.token.as_str())
+    }
+
+    /// Writes the token to a file that only the current user can read, so
+    /// that it can be handed to clients without ending up in the logs
+    pub fn write_token_file(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+            options.mode(0o600);
+
+            // The mode only applies to newly created files
+            if path.exists() {
+                std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+            }
+        }
+
+        let mut file = options.open(path)?;
+        file.write_all(self.token.as_bytes())
+    }
+
+    /// Compares in constant time, so that the token can't be guessed byte by
+    /// byte from how long comparisons take
+    pub fn is_token(&self, candidate: &str) -> bool {
+        let (token, candidate) = (self.token.as_bytes(), candidate.as_bytes());
+
+        token.len() == candidate.len()
+            && token
+                .iter()
+                .zip(candidate)
+                .fold(0, |difference, (a, b)| difference | (a ^ b))
+                == 0
+    }
+
+    /// Whether an `Authorization` header carries the token as a bearer token
+    pub fn is_authorized(&self, authorization: Option<&str>) -> bool {
+        authorization
+            .and_then(|authorization| authorization.strip_prefix("Bearer "))
+            .is_some_and(|token| self.is_token(token.trim()))
+    }
+
+    /// Browsers send the origin of the page making a request, which lets
+    /// pages on other sites be told apart from clients outside of a browser
+    pub fn is_allowed_origin(&self, origin: Option<&str>) -> bool {
+        match origin {
+            Some(origin) => self
+                .allowed_origins
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(origin)),
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_only_the_exact_token() {
+        let auth = ApiAuth::new("secret".into());
+
+        assert!(auth.is_token("secret"));
+        assert!(!auth.is_token("secreT"));
+        assert!(!auth.is_token("secret2"));
+        assert!(!auth.is_token(""));
+    }
+
+    #[test]
+    fn accepts_only_bearer_tokens() {
+        let auth = ApiAuth::new("secret".into());
+
+        assert!(auth.is_authorized(Some("Bearer secret")));
+        assert!(!auth.is_authorized(Some("Basic secret")));
+        assert!(!auth.is_authorized(Some("secret")));
+        assert!(!auth.is_authorized(None));
+    }
+
+    #[test]
+    fn allows_only_configured_origins() {
+        let auth = ApiAuth::new("secret".into())
+            .with_allowed_origins(vec!["http://localhost:5173".into()]);
+
+        assert!(auth.is_allowed_origin(None));
+        assert!(auth.is_allowed_origin(Some("http://localhost:5173")));
+        assert!(!auth.is_allowed_origin(Some("https://example.com")));
+    }
+
+    #[test]
+    fn generates_a_token_when_none_is_configured() {
+        let auth = ApiAuth::from_config(Some(String::new()));
+        assert!(!auth.is_token(""));
+        assert!(auth.generated_token().is_some());
+
+        let auth = ApiAuth::from_config(Some("secret".into()));
+        assert_eq!(auth.generated_token(), None);
+    }
+}
@@ -0,0 +1,41 @@
+use crate::api::contracts::alerts::{
+    AddAlertRuleRequest, AddAlertRuleResponse, ListAlertRulesRequest, ListAlertRulesResponse,
+    RemoveAlertRuleRequest, RemoveAlertRuleResponse,
+};
+use crate::domains::alerts::{
+    handle_add_alert_rule, handle_list_alert_rules, handle_remove_alert_rule,
+};
+use crate::ipc::CommandError;
+use crate::state;
+
+use log::debug;
+
+#[tauri::command]
+pub async fn add_alert_rule(
+    request: AddAlertRuleRequest,
+    mesh_devices: tauri::State<'_, state::mesh_devices::MeshDevicesState>,
+) -> Result<AddAlertRuleResponse, CommandError> {
+    debug!("Called add_alert_rule command");
+    let response = handle_add_alert_rule(request, &mesh_devices).await?;
+    Ok(response)
+}
+
+#[tauri::command]
+pub async fn remove_alert_rule(
+    request: RemoveAlertRuleRequest,
+    mesh_devices: tauri::State<'_, state::mesh_devices::MeshDevicesState>,
+) -> Result<RemoveAlertRuleResponse, CommandError> {
+    debug!("Called remove_alert_rule command");
+    let response = handle_remove_alert_rule(request, &mesh_devices).await?;
+    Ok(response)
+}
+
+#[tauri::command]
+pub async fn list_alert_rules(
+    request: ListAlertRulesRequest,
+    mesh_devices: tauri::State<'_, state::mesh_devices::MeshDevicesState>,
+) -> Result<ListAlertRulesResponse, CommandError> {
+    debug!("Called list_alert_rules command");
+    let response = handle_list_alert_rules(request, &mesh_devices).await?;
+    Ok(response)
+}
@@ -0,0 +1,29 @@
+use crate::api::contracts::capture::{
+    StartPacketCaptureRequest, StartPacketCaptureResponse, StopPacketCaptureRequest,
+    StopPacketCaptureResponse,
+};
+use crate::domains::capture::{handle_start_packet_capture, handle_stop_packet_capture};
+use crate::ipc::CommandError;
+use crate::state;
+
+use log::debug;
+
+#[tauri::command]
+pub async fn start_packet_capture(
+    request: StartPacketCaptureRequest,
+    mesh_devices: tauri::State<'_, state::mesh_devices::MeshDevicesState>,
+) -> Result<StartPacketCaptureResponse, CommandError> {
+    debug!("Called start_packet_capture command");
+    let response = handle_start_packet_capture(request, &mesh_devices).await?;
+    Ok(response)
+}
+
+#[tauri::command]
+pub async fn stop_packet_capture(
+    request: StopPacketCaptureRequest,
+    mesh_devices: tauri::State<'_, state::mesh_devices::MeshDevicesState>,
+) -> Result<StopPacketCaptureResponse, CommandError> {
+    debug!("Called stop_packet_capture command");
+    let response = handle_stop_packet_capture(request, &mesh_devices).await?;
+    Ok(response)
+}
@@ -1,15 +1,15 @@
 use crate::api::contracts::connections::{
-    ConnectToBluetoothRequest, ConnectToBluetoothResponse, ConnectToSerialPortRequest,
-    ConnectToSerialPortResponse, ConnectToTcpPortRequest, ConnectToTcpPortResponse,
-    DropAllDeviceConnectionsRequest, DropAllDeviceConnectionsResponse, DropDeviceConnectionRequest,
-    DropDeviceConnectionResponse, GetAllBluetoothRequest, GetAllBluetoothResponse,
-    GetAllSerialPortsRequest, GetAllSerialPortsResponse, RequestAutoconnectPortRequest,
-    RequestAutoconnectPortResponse,
+    ConnectToBluetoothRequest, ConnectToBluetoothResponse, ConnectToReplayRequest,
+    ConnectToReplayResponse, ConnectToSerialPortRequest, ConnectToSerialPortResponse,
+    ConnectToTcpPortRequest, ConnectToTcpPortResponse, DropAllDeviceConnectionsRequest,
+    DropAllDeviceConnectionsResponse, DropDeviceConnectionRequest, DropDeviceConnectionResponse,
+    GetAllBluetoothRequest, GetAllBluetoothResponse, GetAllSerialPortsRequest,
+    GetAllSerialPortsResponse, RequestAutoconnectPortRequest, RequestAutoconnectPortResponse,
 };
 use crate::domains::connections::{
-    handle_connect_to_bluetooth, handle_connect_to_serial_port, handle_connect_to_tcp_port,
-    handle_drop_all_device_connections, handle_drop_device_connection, handle_get_all_bluetooth,
-    handle_get_all_serial_ports, handle_request_autoconnect_port,
+    handle_connect_to_bluetooth, handle_connect_to_replay, handle_connect_to_serial_port,
+    handle_connect_to_tcp_port, handle_drop_all_device_connections, handle_drop_device_connection,
+    handle_get_all_bluetooth, handle_get_all_serial_ports, handle_request_autoconnect_port,
 };
 use crate::ipc::CommandError;
 use crate::state;
@@ -22,7 +22,7 @@ pub async fn request_autoconnect_port(
     autoconnect_state: tauri::State<'_, state::autoconnect::AutoConnectState>,
 ) -> Result<RequestAutoconnectPortResponse, CommandError> {
     debug!("Called request_autoconnect_port command");
-    let response = handle_request_autoconnect_port(request, autoconnect_state).await?;
+    let response = handle_request_autoconnect_port(request, &autoconnect_state).await?;
     Ok(response)
 }
 
@@ -47,7 +47,7 @@ pub fn get_all_serial_ports(
 #[tauri::command]
 pub async fn connect_to_bluetooth(
     request: ConnectToBluetoothRequest,
-    app_handle: tauri::AppHandle,
+    events: tauri::State<'_, state::events::EventsState>,
     mesh_devices: tauri::State<'_, state::mesh_devices::MeshDevicesState>,
     radio_connections: tauri::State<'_, state::radio_connections::RadioConnectionsState>,
     mesh_graph: tauri::State<'_, state::graph::GraphState>,
@@ -55,10 +55,10 @@ pub async fn connect_to_bluetooth(
     debug!("Called connect_to_bluetooth command");
     let response = handle_connect_to_bluetooth(
         request,
-        app_handle,
-        mesh_devices,
-        radio_connections,
-        mesh_graph,
+        &events,
+        &mesh_devices,
+        &radio_connections,
+        &mesh_graph,
     )
     .await?;
     Ok(response)
@@ -67,7 +67,7 @@ pub async fn connect_to_bluetooth(
 #[tauri::command]
 pub async fn connect_to_serial_port(
     request: ConnectToSerialPortRequest,
-    app_handle: tauri::AppHandle,
+    events: tauri::State<'_, state::events::EventsState>,
     mesh_devices: tauri::State<'_, state::mesh_devices::MeshDevicesState>,
     radio_connections: tauri::State<'_, state::radio_connections::RadioConnectionsState>,
     mesh_graph: tauri::State<'_, state::graph::GraphState>,
@@ -75,10 +75,10 @@ pub async fn connect_to_serial_port(
     debug!("Called connect_to_serial_port command");
     let response = handle_connect_to_serial_port(
         request,
-        app_handle,
-        mesh_devices,
-        radio_connections,
-        mesh_graph,
+        &events,
+        &mesh_devices,
+        &radio_connections,
+        &mesh_graph,
     )
     .await?;
     Ok(response)
@@ -87,7 +87,7 @@ pub async fn connect_to_serial_port(
 #[tauri::command]
 pub async fn connect_to_tcp_port(
     request: ConnectToTcpPortRequest,
-    app_handle: tauri::AppHandle,
+    events: tauri::State<'_, state::events::EventsState>,
     mesh_devices: tauri::State<'_, state::mesh_devices::MeshDevicesState>,
     radio_connections: tauri::State<'_, state::radio_connections::RadioConnectionsState>,
     mesh_graph: tauri::State<'_, state::graph::GraphState>,
@@ -95,23 +95,38 @@ pub async fn connect_to_tcp_port(
     debug!("Called connect_to_tcp_port command");
     let response = handle_connect_to_tcp_port(
         request,
-        app_handle,
-        mesh_devices,
-        radio_connections,
-        mesh_graph,
+        &events,
+        &mesh_devices,
+        &radio_connections,
+        &mesh_graph,
     )
     .await?;
     Ok(response)
 }
 
+#[tauri::command]
+pub async fn connect_to_replay(
+    request: ConnectToReplayRequest,
+    events: tauri::State<'_, state::events::EventsState>,
+    mesh_devices: tauri::State<'_, state::mesh_devices::MeshDevicesState>,
+    mesh_graph: tauri::State<'_, state::graph::GraphState>,
+) -> Result<ConnectToReplayResponse, CommandError> {
+    debug!("Called connect_to_replay command");
+    let response = handle_connect_to_replay(request, &events, &mesh_devices, &mesh_graph).await?;
+    Ok(response)
+}
+
 #[tauri::command]
 pub async fn drop_device_connection(
     request: DropDeviceConnectionRequest,
     mesh_devices: tauri::State<'_, state::mesh_devices::MeshDevicesState>,
     radio_connections: tauri::State<'_, state::radio_connections::RadioConnectionsState>,
+    mesh_graph: tauri::State<'_, state::graph::GraphState>,
 ) -> Result<DropDeviceConnectionResponse, CommandError> {
     debug!("Called drop_device_connection command");
-    let response = handle_drop_device_connection(request, mesh_devices, radio_connections).await?;
+    let response =
+        handle_drop_device_connection(request, &mesh_devices, &radio_connections, &mesh_graph)
+            .await?;
     Ok(response)
 }
 
@@ -120,9 +135,11 @@ pub async fn drop_all_device_connections(
     request: DropAllDeviceConnectionsRequest,
     mesh_devices: tauri::State<'_, state::mesh_devices::MeshDevicesState>,
     radio_connections: tauri::State<'_, state::radio_connections::RadioConnectionsState>,
+    mesh_graph: tauri::State<'_, state::graph::GraphState>,
 ) -> Result<DropAllDeviceConnectionsResponse, CommandError> {
     debug!("Called drop_all_device_connections command");
     let response =
-        handle_drop_all_device_connections(request, mesh_devices, radio_connections).await?;
+        handle_drop_all_device_connections(request, &mesh_devices, &radio_connections, &mesh_graph)
+            .await?;
     Ok(response)
 }
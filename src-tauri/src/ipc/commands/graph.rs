@@ -1,9 +1,18 @@
 use crate::api::contracts::graph::{
-    GetGraphStateRequest, GetGraphStateResponse, InitializeTimeoutHandlerRequest,
-    InitializeTimeoutHandlerResponse, StopTimeoutHandlerRequest, StopTimeoutHandlerResponse,
+    ExportGraphRequest, ExportGraphResponse, GetBestPathRequest, GetBestPathResponse,
+    GetGraphSnapshotRequest, GetGraphSnapshotResponse, GetGraphStateRequest, GetGraphStateResponse,
+    GetObservedTopologyRequest, GetObservedTopologyResponse, GetRouteQualityRequest,
+    GetRouteQualityResponse, GetTopologyDiffRequest, GetTopologyDiffResponse,
+    InitializeTimeoutHandlerRequest, InitializeTimeoutHandlerResponse, ListGraphSnapshotsRequest,
+    ListGraphSnapshotsResponse, RunGraphAnalysisRequest, RunGraphAnalysisResponse,
+    SetInferNeighborNodesRequest, SetInferNeighborNodesResponse, StopTimeoutHandlerRequest,
+    StopTimeoutHandlerResponse,
 };
 use crate::domains::graph::{
-    handle_get_graph_state, handle_initialize_timeout_handler, handle_stop_timeout_handler,
+    handle_export_graph, handle_get_best_path, handle_get_graph_snapshot, handle_get_graph_state,
+    handle_get_observed_topology, handle_get_route_quality, handle_get_topology_diff,
+    handle_initialize_timeout_handler, handle_list_graph_snapshots, handle_run_graph_analysis,
+    handle_set_infer_neighbor_nodes, handle_stop_timeout_handler,
 };
 use crate::ipc::CommandError;
 use crate::state;
@@ -16,18 +25,18 @@ pub async fn get_graph_state(
     mesh_graph: tauri::State<'_, state::graph::GraphState>,
 ) -> Result<GetGraphStateResponse, CommandError> {
     debug!("Called get_graph_state command");
-    let response = handle_get_graph_state(request, mesh_graph).await?;
+    let response = handle_get_graph_state(request, &mesh_graph).await?;
     Ok(response)
 }
 
 #[tauri::command]
 pub async fn initialize_timeout_handler(
     request: InitializeTimeoutHandlerRequest,
-    app_handle: tauri::AppHandle,
+    events: tauri::State<'_, state::events::EventsState>,
     mesh_graph_state: tauri::State<'_, state::graph::GraphState>,
 ) -> Result<InitializeTimeoutHandlerResponse, CommandError> {
     debug!("Called initialize_timeout_handler command");
-    let response = handle_initialize_timeout_handler(request, app_handle, mesh_graph_state).await?;
+    let response = handle_initialize_timeout_handler(request, &events, &mesh_graph_state).await?;
     Ok(response)
 }
 
@@ -37,6 +46,96 @@ pub async fn stop_timeout_handler(
     mesh_graph: tauri::State<'_, state::graph::GraphState>,
 ) -> Result<StopTimeoutHandlerResponse, CommandError> {
     debug!("Called stop_timeout_handler command");
-    let response = handle_stop_timeout_handler(request, mesh_graph).await?;
+    let response = handle_stop_timeout_handler(request, &mesh_graph).await?;
+    Ok(response)
+}
+
+#[tauri::command]
+pub async fn run_graph_analysis(
+    request: RunGraphAnalysisRequest,
+    mesh_graph: tauri::State<'_, state::graph::GraphState>,
+) -> Result<RunGraphAnalysisResponse, CommandError> {
+    debug!("Called run_graph_analysis command");
+    let response = handle_run_graph_analysis(request, &mesh_graph).await?;
+    Ok(response)
+}
+
+#[tauri::command]
+pub async fn get_best_path(
+    request: GetBestPathRequest,
+    mesh_graph: tauri::State<'_, state::graph::GraphState>,
+) -> Result<GetBestPathResponse, CommandError> {
+    debug!("Called get_best_path command");
+    let response = handle_get_best_path(request, &mesh_graph).await?;
+    Ok(response)
+}
+
+#[tauri::command]
+pub async fn get_route_quality(
+    request: GetRouteQualityRequest,
+    mesh_graph: tauri::State<'_, state::graph::GraphState>,
+) -> Result<GetRouteQualityResponse, CommandError> {
+    debug!("Called get_route_quality command");
+    let response = handle_get_route_quality(request, &mesh_graph).await?;
+    Ok(response)
+}
+
+#[tauri::command]
+pub async fn export_graph(
+    request: ExportGraphRequest,
+    mesh_graph: tauri::State<'_, state::graph::GraphState>,
+) -> Result<ExportGraphResponse, CommandError> {
+    debug!("Called export_graph command");
+    let response = handle_export_graph(request, &mesh_graph).await?;
+    Ok(response)
+}
+
+#[tauri::command]
+pub async fn list_graph_snapshots(
+    request: ListGraphSnapshotsRequest,
+    mesh_graph: tauri::State<'_, state::graph::GraphState>,
+) -> Result<ListGraphSnapshotsResponse, CommandError> {
+    debug!("Called list_graph_snapshots command");
+    let response = handle_list_graph_snapshots(request, &mesh_graph).await?;
+    Ok(response)
+}
+
+#[tauri::command]
+pub async fn get_graph_snapshot(
+    request: GetGraphSnapshotRequest,
+    mesh_graph: tauri::State<'_, state::graph::GraphState>,
+) -> Result<GetGraphSnapshotResponse, CommandError> {
+    debug!("Called get_graph_snapshot command");
+    let response = handle_get_graph_snapshot(request, &mesh_graph).await?;
+    Ok(response)
+}
+
+#[tauri::command]
+pub async fn get_topology_diff(
+    request: GetTopologyDiffRequest,
+    mesh_graph: tauri::State<'_, state::graph::GraphState>,
+) -> Result<GetTopologyDiffResponse, CommandError> {
+    debug!("Called get_topology_diff command");
+    let response = handle_get_topology_diff(request, &mesh_graph).await?;
+    Ok(response)
+}
+
+#[tauri::command]
+pub async fn get_observed_topology(
+    request: GetObservedTopologyRequest,
+    mesh_graph: tauri::State<'_, state::graph::GraphState>,
+) -> Result<GetObservedTopologyResponse, CommandError> {
+    debug!("Called get_observed_topology command");
+    let response = handle_get_observed_topology(request, &mesh_graph).await?;
+    Ok(response)
+}
+
+#[tauri::command]
+pub async fn set_infer_neighbor_nodes(
+    request: SetInferNeighborNodesRequest,
+    mesh_graph: tauri::State<'_, state::graph::GraphState>,
+) -> Result<SetInferNeighborNodesResponse, CommandError> {
+    debug!("Called set_infer_neighbor_nodes command");
+    let response = handle_set_infer_neighbor_nodes(request, &mesh_graph).await?;
     Ok(response)
 }
@@ -1,8 +1,12 @@
 use crate::api::contracts::mesh::{
-    DeleteWaypointRequest, DeleteWaypointResponse, SendTextRequest, SendTextResponse,
-    SendWaypointRequest, SendWaypointResponse,
+    DeleteWaypointRequest, DeleteWaypointResponse, RequestStoreForwardHistoryRequest,
+    RequestStoreForwardHistoryResponse, SendTextRequest, SendTextResponse, SendTracerouteRequest,
+    SendTracerouteResponse, SendWaypointRequest, SendWaypointResponse,
+};
+use crate::domains::mesh::{
+    handle_delete_waypoint, handle_request_store_forward_history, handle_send_text,
+    handle_send_traceroute, handle_send_waypoint,
 };
-use crate::domains::mesh::{handle_delete_waypoint, handle_send_text, handle_send_waypoint};
 use crate::ipc::CommandError;
 use crate::state;
 
@@ -11,35 +15,54 @@ use log::debug;
 #[tauri::command]
 pub async fn send_text(
     request: SendTextRequest,
-    app_handle: tauri::AppHandle,
     mesh_devices: tauri::State<'_, state::mesh_devices::MeshDevicesState>,
     radio_connections: tauri::State<'_, state::radio_connections::RadioConnectionsState>,
 ) -> Result<SendTextResponse, CommandError> {
     debug!("Called send_text command",);
-    let response = handle_send_text(request, app_handle, mesh_devices, radio_connections).await?;
+    let response = handle_send_text(request, &mesh_devices, &radio_connections).await?;
     Ok(response)
 }
 
 #[tauri::command]
 pub async fn send_waypoint(
     request: SendWaypointRequest,
-    app_handle: tauri::AppHandle,
     mesh_devices: tauri::State<'_, state::mesh_devices::MeshDevicesState>,
     radio_connections: tauri::State<'_, state::radio_connections::RadioConnectionsState>,
 ) -> Result<SendWaypointResponse, CommandError> {
     debug!("Called send_waypoint command");
-    let response =
-        handle_send_waypoint(request, app_handle, mesh_devices, radio_connections).await?;
+    let response = handle_send_waypoint(request, &mesh_devices, &radio_connections).await?;
     Ok(response)
 }
 
 #[tauri::command]
 pub async fn delete_waypoint(
     request: DeleteWaypointRequest,
-    app_handle: tauri::AppHandle,
     mesh_devices: tauri::State<'_, state::mesh_devices::MeshDevicesState>,
 ) -> Result<DeleteWaypointResponse, CommandError> {
     debug!("Called delete_waypoint command");
-    let response = handle_delete_waypoint(request, app_handle, mesh_devices).await?;
+    let response = handle_delete_waypoint(request, &mesh_devices).await?;
+    Ok(response)
+}
+
+#[tauri::command]
+pub async fn send_traceroute(
+    request: SendTracerouteRequest,
+    mesh_devices: tauri::State<'_, state::mesh_devices::MeshDevicesState>,
+    radio_connections: tauri::State<'_, state::radio_connections::RadioConnectionsState>,
+) -> Result<SendTracerouteResponse, CommandError> {
+    debug!("Called send_traceroute command");
+    let response = handle_send_traceroute(request, &mesh_devices, &radio_connections).await?;
+    Ok(response)
+}
+
+#[tauri::command]
+pub async fn request_store_forward_history(
+    request: RequestStoreForwardHistoryRequest,
+    mesh_devices: tauri::State<'_, state::mesh_devices::MeshDevicesState>,
+    radio_connections: tauri::State<'_, state::radio_connections::RadioConnectionsState>,
+) -> Result<RequestStoreForwardHistoryResponse, CommandError> {
+    debug!("Called request_store_forward_history command");
+    let response =
+        handle_request_store_forward_history(request, &mesh_devices, &radio_connections).await?;
     Ok(response)
 }
@@ -0,0 +1,38 @@
+use crate::api::contracts::mqtt::{
+    StartMqttBridgeRequest, StartMqttBridgeResponse, StopMqttBridgeRequest, StopMqttBridgeResponse,
+};
+use crate::domains::mqtt::{handle_start_mqtt_bridge, handle_stop_mqtt_bridge};
+use crate::ipc::CommandError;
+use crate::state;
+
+use log::debug;
+
+#[tauri::command]
+pub async fn start_mqtt_bridge(
+    request: StartMqttBridgeRequest,
+    events: tauri::State<'_, state::events::EventsState>,
+    mesh_devices: tauri::State<'_, state::mesh_devices::MeshDevicesState>,
+    radio_connections: tauri::State<'_, state::radio_connections::RadioConnectionsState>,
+    mqtt_bridge: tauri::State<'_, state::mqtt::MqttBridgeState>,
+) -> Result<StartMqttBridgeResponse, CommandError> {
+    debug!("Called start_mqtt_bridge command");
+    let response = handle_start_mqtt_bridge(
+        request,
+        &events,
+        &mesh_devices,
+        &radio_connections,
+        &mqtt_bridge,
+    )
+    .await?;
+    Ok(response)
+}
+
+#[tauri::command]
+pub async fn stop_mqtt_bridge(
+    request: StopMqttBridgeRequest,
+    mqtt_bridge: tauri::State<'_, state::mqtt::MqttBridgeState>,
+) -> Result<StopMqttBridgeResponse, CommandError> {
+    debug!("Called stop_mqtt_bridge command");
+    let response = handle_stop_mqtt_bridge(request, &mqtt_bridge).await?;
+    Ok(response)
+}
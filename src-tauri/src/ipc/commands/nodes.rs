@@ -0,0 +1,28 @@
+use crate::api::contracts::nodes::{
+    ExportNodesRequest, ExportNodesResponse, ImportNodeRosterRequest, ImportNodeRosterResponse,
+};
+use crate::domains::nodes::{handle_export_nodes, handle_import_node_roster};
+use crate::ipc::CommandError;
+use crate::state;
+
+use log::debug;
+
+#[tauri::command]
+pub async fn export_nodes(
+    request: ExportNodesRequest,
+    mesh_devices: tauri::State<'_, state::mesh_devices::MeshDevicesState>,
+) -> Result<ExportNodesResponse, CommandError> {
+    debug!("Called export_nodes command");
+    let response = handle_export_nodes(request, &mesh_devices).await?;
+    Ok(response)
+}
+
+#[tauri::command]
+pub async fn import_node_roster(
+    request: ImportNodeRosterRequest,
+    mesh_devices: tauri::State<'_, state::mesh_devices::MeshDevicesState>,
+) -> Result<ImportNodeRosterResponse, CommandError> {
+    debug!("Called import_node_roster command");
+    let response = handle_import_node_roster(request, &mesh_devices).await?;
+    Ok(response)
+}
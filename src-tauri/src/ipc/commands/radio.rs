@@ -22,7 +22,7 @@ pub async fn update_device_config(
     radio_connections: tauri::State<'_, state::radio_connections::RadioConnectionsState>,
 ) -> Result<UpdateDeviceConfigResponse, CommandError> {
     debug!("Called update_device_config command");
-    let response = handle_update_device_config(request, mesh_devices, radio_connections).await?;
+    let response = handle_update_device_config(request, &mesh_devices, &radio_connections).await?;
     Ok(response)
 }
 
@@ -33,7 +33,7 @@ pub async fn update_device_user(
     radio_connections: tauri::State<'_, state::radio_connections::RadioConnectionsState>,
 ) -> Result<UpdateDeviceUserResponse, CommandError> {
     debug!("Called update_device_user command");
-    let response = handle_update_device_user(request, mesh_devices, radio_connections).await?;
+    let response = handle_update_device_user(request, &mesh_devices, &radio_connections).await?;
     Ok(response)
 }
 
@@ -46,7 +46,7 @@ pub async fn start_configuration_transaction(
 ) -> Result<StartConfigurationTransactionResponse, CommandError> {
     debug!("Called start_configuration_transaction command");
     let response =
-        handle_start_configuration_transaction(request, mesh_devices, radio_connections).await?;
+        handle_start_configuration_transaction(request, &mesh_devices, &radio_connections).await?;
     Ok(response)
 }
 
@@ -59,20 +59,18 @@ pub async fn commit_configuration_transaction(
 ) -> Result<CommitConfigurationTransactionResponse, CommandError> {
     debug!("Called commit_configuration_transaction command");
     let response =
-        handle_commit_configuration_transaction(request, mesh_devices, radio_connections).await?;
+        handle_commit_configuration_transaction(request, &mesh_devices, &radio_connections).await?;
     Ok(response)
 }
 
 #[tauri::command]
 pub async fn update_device_config_bulk(
     request: UpdateDeviceConfigBulkRequest,
-    app_handle: tauri::AppHandle,
     mesh_devices: tauri::State<'_, state::mesh_devices::MeshDevicesState>,
     radio_connections: tauri::State<'_, state::radio_connections::RadioConnectionsState>,
 ) -> Result<UpdateDeviceConfigBulkResponse, CommandError> {
     debug!("Called update_device_config_bulk command");
     let response =
-        handle_update_device_config_bulk(request, app_handle, mesh_devices, radio_connections)
-            .await?;
+        handle_update_device_config_bulk(request, &mesh_devices, &radio_connections).await?;
     Ok(response)
 }
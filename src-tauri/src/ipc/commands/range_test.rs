@@ -0,0 +1,41 @@
+use crate::api::contracts::range_test::{
+    ClearRangeTestRequest, ClearRangeTestResponse, ExportRangeTestRequest, ExportRangeTestResponse,
+    GetRangeTestRecordsRequest, GetRangeTestRecordsResponse,
+};
+use crate::domains::range_test::{
+    handle_clear_range_test, handle_export_range_test, handle_get_range_test_records,
+};
+use crate::ipc::CommandError;
+use crate::state;
+
+use log::debug;
+
+#[tauri::command]
+pub async fn get_range_test_records(
+    request: GetRangeTestRecordsRequest,
+    mesh_devices: tauri::State<'_, state::mesh_devices::MeshDevicesState>,
+) -> Result<GetRangeTestRecordsResponse, CommandError> {
+    debug!("Called get_range_test_records command");
+    let response = handle_get_range_test_records(request, &mesh_devices).await?;
+    Ok(response)
+}
+
+#[tauri::command]
+pub async fn export_range_test(
+    request: ExportRangeTestRequest,
+    mesh_devices: tauri::State<'_, state::mesh_devices::MeshDevicesState>,
+) -> Result<ExportRangeTestResponse, CommandError> {
+    debug!("Called export_range_test command");
+    let response = handle_export_range_test(request, &mesh_devices).await?;
+    Ok(response)
+}
+
+#[tauri::command]
+pub async fn clear_range_test(
+    request: ClearRangeTestRequest,
+    mesh_devices: tauri::State<'_, state::mesh_devices::MeshDevicesState>,
+) -> Result<ClearRangeTestResponse, CommandError> {
+    debug!("Called clear_range_test command");
+    let response = handle_clear_range_test(request, &mesh_devices).await?;
+    Ok(response)
+}
@@ -0,0 +1,31 @@
+use crate::api::contracts::telemetry::{
+    GetNodeTelemetryHistoryRequest, GetNodeTelemetryHistoryResponse, SetTelemetryRetentionRequest,
+    SetTelemetryRetentionResponse,
+};
+use crate::domains::telemetry::{
+    handle_get_node_telemetry_history, handle_set_telemetry_retention,
+};
+use crate::ipc::CommandError;
+use crate::state;
+
+use log::debug;
+
+#[tauri::command]
+pub async fn get_node_telemetry_history(
+    request: GetNodeTelemetryHistoryRequest,
+    mesh_devices: tauri::State<'_, state::mesh_devices::MeshDevicesState>,
+) -> Result<GetNodeTelemetryHistoryResponse, CommandError> {
+    debug!("Called get_node_telemetry_history command");
+    let response = handle_get_node_telemetry_history(request, &mesh_devices).await?;
+    Ok(response)
+}
+
+#[tauri::command]
+pub async fn set_telemetry_retention(
+    request: SetTelemetryRetentionRequest,
+    mesh_devices: tauri::State<'_, state::mesh_devices::MeshDevicesState>,
+) -> Result<SetTelemetryRetentionResponse, CommandError> {
+    debug!("Called set_telemetry_retention command");
+    let response = handle_set_telemetry_retention(request, &mesh_devices).await?;
+    Ok(response)
+}
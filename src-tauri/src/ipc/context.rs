@@ -0,0 +1,13 @@
+use crate::state;
+
+/// Handles to all shared application state, used to serve commands
+/// outside of Tauri's `invoke` system (e.g., over a local socket)
+#[derive(Clone)]
+pub struct IpcContext {
+    pub events: state::events::EventsState,
+    pub autoconnect: state::autoconnect::AutoConnectState,
+    pub mesh_devices: state::mesh_devices::MeshDevicesState,
+    pub radio_connections: state::radio_connections::RadioConnectionsState,
+    pub mesh_graph: state::graph::GraphState,
+    pub mqtt_bridge: state::mqtt::MqttBridgeState,
+}
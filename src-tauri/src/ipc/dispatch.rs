@@ -0,0 +1,338 @@
+use log::debug;
+
+use crate::domains::alerts::{
+    handle_add_alert_rule, handle_list_alert_rules, handle_remove_alert_rule,
+};
+use crate::domains::capture::{handle_start_packet_capture, handle_stop_packet_capture};
+use crate::domains::connections::{
+    handle_connect_to_bluetooth, handle_connect_to_replay, handle_connect_to_serial_port,
+    handle_connect_to_tcp_port, handle_drop_all_device_connections, handle_drop_device_connection,
+    handle_get_all_bluetooth, handle_get_all_serial_ports, handle_request_autoconnect_port,
+};
+use crate::domains::graph::{
+    handle_export_graph, handle_get_best_path, handle_get_graph_snapshot, handle_get_graph_state,
+    handle_get_observed_topology, handle_get_route_quality, handle_get_topology_diff,
+    handle_initialize_timeout_handler, handle_list_graph_snapshots, handle_run_graph_analysis,
+    handle_set_infer_neighbor_nodes, handle_stop_timeout_handler,
+};
+use crate::domains::mesh::{
+    handle_delete_waypoint, handle_request_store_forward_history, handle_send_text,
+    handle_send_traceroute, handle_send_waypoint,
+};
+use crate::domains::mqtt::{handle_start_mqtt_bridge, handle_stop_mqtt_bridge};
+use crate::domains::nodes::{handle_export_nodes, handle_import_node_roster};
+use crate::domains::radio::{
+    handle_commit_configuration_transaction, handle_start_configuration_transaction,
+    handle_update_device_config, handle_update_device_config_bulk, handle_update_device_user,
+};
+use crate::domains::range_test::{
+    handle_clear_range_test, handle_export_range_test, handle_get_range_test_records,
+};
+use crate::domains::telemetry::{
+    handle_get_node_telemetry_history, handle_set_telemetry_retention,
+};
+
+use super::context::IpcContext;
+use super::CommandError;
+
+/// Commands that read or write files at paths chosen by the caller. They
+/// are only run for the local app, as otherwise any holder of an API token
+/// could read or overwrite any file the app's user has access to.
+pub const LOCAL_ONLY_COMMANDS: [&str; 6] = [
+    "connect_to_replay",
+    "start_packet_capture",
+    "export_range_test",
+    "export_nodes",
+    "import_node_roster",
+    "export_graph",
+];
+
+/// Why a dispatched command didn't produce a response
+#[derive(Clone, Debug)]
+pub enum DispatchError {
+    /// No command has the passed name
+    UnknownCommand(CommandError),
+    /// The command can't be run through the remote APIs
+    Forbidden(CommandError),
+    /// The request doesn't match the command's request contract
+    InvalidRequest(CommandError),
+    /// The command's handler failed
+    Failed(CommandError),
+}
+
+impl From<DispatchError> for CommandError {
+    fn from(error: DispatchError) -> Self {
+        match error {
+            DispatchError::UnknownCommand(error)
+            | DispatchError::Forbidden(error)
+            | DispatchError::InvalidRequest(error)
+            | DispatchError::Failed(error) => error,
+        }
+    }
+}
+
+/// Deserializes a request, runs the passed handler on it and serializes its response
+macro_rules! route {
+    ($request:expr, |$parsed:ident| $handler:expr) => {{
+        let $parsed = serde_json::from_value($request)
+            .map_err(|e| DispatchError::InvalidRequest(format!("Invalid request: {}", e).into()))?;
+        let response = $handler
+            .await
+            .map_err(|e| DispatchError::Failed(CommandError::from(e)))?;
+        serde_json::to_value(response).map_err(|e| DispatchError::Failed(e.to_string().into()))?
+    }};
+}
+
+/// Runs the command with the passed name for a client of the socket or
+/// HTTP API, mirroring the commands registered with Tauri's `invoke`
+/// handler apart from the `LOCAL_ONLY_COMMANDS`
+pub async fn dispatch_command(
+    context: &IpcContext,
+    command: &str,
+    request: serde_json::Value,
+) -> Result<serde_json::Value, DispatchError> {
+    debug!("Dispatching command \"{}\"", command);
+
+    if LOCAL_ONLY_COMMANDS.contains(&command) {
+        return Err(DispatchError::Forbidden(
+            format!("Command \"{}\" can only be run from the app", command).into(),
+        ));
+    }
+
+    // Requests without fields may be omitted entirely by callers
+    let request = match request {
+        serde_json::Value::Null => serde_json::Value::Object(Default::default()),
+        request => request,
+    };
+
+    let response = match command {
+        // Connections
+        "request_autoconnect_port" => route!(request, |r| handle_request_autoconnect_port(
+            r,
+            &context.autoconnect
+        )),
+        "get_all_bluetooth" => route!(request, |r| handle_get_all_bluetooth(r)),
+        "get_all_serial_ports" => route!(request, |r| async { handle_get_all_serial_ports(r) }),
+        "connect_to_bluetooth" => route!(request, |r| handle_connect_to_bluetooth(
+            r,
+            &context.events,
+            &context.mesh_devices,
+            &context.radio_connections,
+            &context.mesh_graph
+        )),
+        "connect_to_serial_port" => route!(request, |r| handle_connect_to_serial_port(
+            r,
+            &context.events,
+            &context.mesh_devices,
+            &context.radio_connections,
+            &context.mesh_graph
+        )),
+        "connect_to_tcp_port" => route!(request, |r| handle_connect_to_tcp_port(
+            r,
+            &context.events,
+            &context.mesh_devices,
+            &context.radio_connections,
+            &context.mesh_graph
+        )),
+        "connect_to_replay" => route!(request, |r| handle_connect_to_replay(
+            r,
+            &context.events,
+            &context.mesh_devices,
+            &context.mesh_graph
+        )),
+        "drop_device_connection" => route!(request, |r| handle_drop_device_connection(
+            r,
+            &context.mesh_devices,
+            &context.radio_connections,
+            &context.mesh_graph
+        )),
+        "drop_all_device_connections" => {
+            route!(request, |r| handle_drop_all_device_connections(
+                r,
+                &context.mesh_devices,
+                &context.radio_connections,
+                &context.mesh_graph
+            ))
+        }
+
+        // Capture
+        "start_packet_capture" => route!(request, |r| handle_start_packet_capture(
+            r,
+            &context.mesh_devices
+        )),
+        "stop_packet_capture" => route!(request, |r| handle_stop_packet_capture(
+            r,
+            &context.mesh_devices
+        )),
+
+        // Mesh
+        "send_text" => route!(request, |r| handle_send_text(
+            r,
+            &context.mesh_devices,
+            &context.radio_connections
+        )),
+        "send_waypoint" => route!(request, |r| handle_send_waypoint(
+            r,
+            &context.mesh_devices,
+            &context.radio_connections
+        )),
+        "delete_waypoint" => route!(request, |r| handle_delete_waypoint(
+            r,
+            &context.mesh_devices
+        )),
+        "send_traceroute" => route!(request, |r| handle_send_traceroute(
+            r,
+            &context.mesh_devices,
+            &context.radio_connections
+        )),
+        "request_store_forward_history" => {
+            route!(request, |r| handle_request_store_forward_history(
+                r,
+                &context.mesh_devices,
+                &context.radio_connections
+            ))
+        }
+
+        // Range test
+        "get_range_test_records" => route!(request, |r| handle_get_range_test_records(
+            r,
+            &context.mesh_devices
+        )),
+        "export_range_test" => route!(request, |r| handle_export_range_test(
+            r,
+            &context.mesh_devices
+        )),
+        "clear_range_test" => route!(request, |r| handle_clear_range_test(
+            r,
+            &context.mesh_devices
+        )),
+
+        // Telemetry
+        "get_node_telemetry_history" => {
+            route!(request, |r| handle_get_node_telemetry_history(
+                r,
+                &context.mesh_devices
+            ))
+        }
+        "set_telemetry_retention" => route!(request, |r| handle_set_telemetry_retention(
+            r,
+            &context.mesh_devices
+        )),
+
+        // Alerts
+        "add_alert_rule" => route!(request, |r| handle_add_alert_rule(r, &context.mesh_devices)),
+        "remove_alert_rule" => route!(request, |r| handle_remove_alert_rule(
+            r,
+            &context.mesh_devices
+        )),
+        "list_alert_rules" => route!(request, |r| handle_list_alert_rules(
+            r,
+            &context.mesh_devices
+        )),
+
+        // Nodes
+        "export_nodes" => route!(request, |r| handle_export_nodes(r, &context.mesh_devices)),
+        "import_node_roster" => route!(request, |r| handle_import_node_roster(
+            r,
+            &context.mesh_devices
+        )),
+
+        // Radio
+        "update_device_config" => route!(request, |r| handle_update_device_config(
+            r,
+            &context.mesh_devices,
+            &context.radio_connections
+        )),
+        "update_device_user" => route!(request, |r| handle_update_device_user(
+            r,
+            &context.mesh_devices,
+            &context.radio_connections
+        )),
+        "start_configuration_transaction" => {
+            route!(request, |r| handle_start_configuration_transaction(
+                r,
+                &context.mesh_devices,
+                &context.radio_connections
+            ))
+        }
+        "commit_configuration_transaction" => {
+            route!(request, |r| handle_commit_configuration_transaction(
+                r,
+                &context.mesh_devices,
+                &context.radio_connections
+            ))
+        }
+        "update_device_config_bulk" => route!(request, |r| handle_update_device_config_bulk(
+            r,
+            &context.mesh_devices,
+            &context.radio_connections
+        )),
+
+        // Graph
+        "get_graph_state" => route!(request, |r| handle_get_graph_state(r, &context.mesh_graph)),
+        "initialize_timeout_handler" => route!(request, |r| handle_initialize_timeout_handler(
+            r,
+            &context.events,
+            &context.mesh_graph
+        )),
+        "stop_timeout_handler" => {
+            route!(request, |r| handle_stop_timeout_handler(
+                r,
+                &context.mesh_graph
+            ))
+        }
+        "run_graph_analysis" => {
+            route!(request, |r| handle_run_graph_analysis(
+                r,
+                &context.mesh_graph
+            ))
+        }
+        "get_best_path" => route!(request, |r| handle_get_best_path(r, &context.mesh_graph)),
+        "get_route_quality" => route!(request, |r| handle_get_route_quality(
+            r,
+            &context.mesh_graph
+        )),
+        "export_graph" => route!(request, |r| handle_export_graph(r, &context.mesh_graph)),
+        "list_graph_snapshots" => route!(request, |r| handle_list_graph_snapshots(
+            r,
+            &context.mesh_graph
+        )),
+        "get_graph_snapshot" => route!(request, |r| handle_get_graph_snapshot(
+            r,
+            &context.mesh_graph
+        )),
+        "get_topology_diff" => route!(request, |r| handle_get_topology_diff(
+            r,
+            &context.mesh_graph
+        )),
+        "get_observed_topology" => route!(request, |r| handle_get_observed_topology(
+            r,
+            &context.mesh_graph
+        )),
+        "set_infer_neighbor_nodes" => route!(request, |r| handle_set_infer_neighbor_nodes(
+            r,
+            &context.mesh_graph
+        )),
+
+        // MQTT
+        "start_mqtt_bridge" => route!(request, |r| handle_start_mqtt_bridge(
+            r,
+            &context.events,
+            &context.mesh_devices,
+            &context.radio_connections,
+            &context.mqtt_bridge
+        )),
+        "stop_mqtt_bridge" => route!(request, |r| handle_stop_mqtt_bridge(
+            r,
+            &context.mqtt_bridge
+        )),
+
+        _ => {
+            return Err(DispatchError::UnknownCommand(
+                format!("Unknown command \"{}\"", command).into(),
+            ))
+        }
+    };
+
+    Ok(response)
+}
@@ -1,36 +1,197 @@
-use crate::{device, graph::ds::graph::MeshGraph};
+use std::sync::Arc;
+
+use crate::{
+    alerts::TelemetryAlert,
+    device,
+    graph::ds::{delta::GraphDelta, graph::MeshGraph},
+};
 use log::{debug, trace};
+use serde::Serialize;
 use tauri::Emitter;
+use tauri_plugin_notification::NotificationExt;
+use tokio::sync::broadcast;
 
 use super::ConfigurationStatus;
 
-pub fn dispatch_updated_device<R: tauri::Runtime>(
-    handle: &tauri::AppHandle<R>,
+#[derive(Clone, Debug, thiserror::Error)]
+pub enum EventDispatchError {
+    #[error("failed to serialize event payload: {0}")]
+    Serialization(String),
+    #[error("failed to emit event: {0}")]
+    Emit(String),
+    #[error("failed to show notification: {0}")]
+    Notify(String),
+}
+
+/// A sink for events that are pushed to clients, decoupling the packet and
+/// domain layers from the transport (Tauri webview, local socket, etc.)
+pub trait EventDispatcher: Send + Sync {
+    fn emit_event(&self, event: &str, payload: serde_json::Value)
+        -> Result<(), EventDispatchError>;
+
+    /// Raises a desktop notification. Dispatchers without a desktop to
+    /// notify ignore notifications.
+    fn notify(&self, _title: &str, _body: &str) -> Result<(), EventDispatchError> {
+        Ok(())
+    }
+}
+
+impl<T: EventDispatcher + ?Sized> EventDispatcher for Arc<T> {
+    fn emit_event(
+        &self,
+        event: &str,
+        payload: serde_json::Value,
+    ) -> Result<(), EventDispatchError> {
+        (**self).emit_event(event, payload)
+    }
+
+    fn notify(&self, title: &str, body: &str) -> Result<(), EventDispatchError> {
+        (**self).notify(title, body)
+    }
+}
+
+impl<R: tauri::Runtime> EventDispatcher for tauri::AppHandle<R> {
+    fn emit_event(
+        &self,
+        event: &str,
+        payload: serde_json::Value,
+    ) -> Result<(), EventDispatchError> {
+        self.emit(event, payload)
+            .map_err(|e| EventDispatchError::Emit(e.to_string()))
+    }
+
+    fn notify(&self, title: &str, body: &str) -> Result<(), EventDispatchError> {
+        self.notification()
+            .builder()
+            .title(title)
+            .body(body)
+            .show()
+            .map_err(|e| EventDispatchError::Notify(e.to_string()))
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DispatchedEvent {
+    pub event: String,
+    pub payload: serde_json::Value,
+}
+
+/// Broadcasts events to any number of in-process subscribers,
+/// used when running without a Tauri webview
+#[derive(Clone)]
+pub struct BroadcastEventDispatcher {
+    sender: broadcast::Sender<DispatchedEvent>,
+}
+
+impl BroadcastEventDispatcher {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self { sender }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<DispatchedEvent> {
+        self.sender.subscribe()
+    }
+}
+
+impl EventDispatcher for BroadcastEventDispatcher {
+    fn emit_event(
+        &self,
+        event: &str,
+        payload: serde_json::Value,
+    ) -> Result<(), EventDispatchError> {
+        // Sending only fails when there are no subscribers, which isn't an error
+        let _ = self.sender.send(DispatchedEvent {
+            event: event.into(),
+            payload,
+        });
+
+        Ok(())
+    }
+}
+
+/// Emits every event to each of a set of dispatchers, e.g., to both
+/// the Tauri webview and external API clients
+pub struct FanOutEventDispatcher {
+    dispatchers: Vec<Arc<dyn EventDispatcher>>,
+}
+
+impl FanOutEventDispatcher {
+    pub fn new(dispatchers: Vec<Arc<dyn EventDispatcher>>) -> Self {
+        Self { dispatchers }
+    }
+}
+
+impl EventDispatcher for FanOutEventDispatcher {
+    fn emit_event(
+        &self,
+        event: &str,
+        payload: serde_json::Value,
+    ) -> Result<(), EventDispatchError> {
+        // A failing dispatcher shouldn't prevent the others from receiving the event
+        let mut result = Ok(());
+
+        for dispatcher in self.dispatchers.iter() {
+            if let Err(e) = dispatcher.emit_event(event, payload.clone()) {
+                result = Err(e);
+            }
+        }
+
+        result
+    }
+
+    fn notify(&self, title: &str, body: &str) -> Result<(), EventDispatchError> {
+        let mut result = Ok(());
+
+        for dispatcher in self.dispatchers.iter() {
+            if let Err(e) = dispatcher.notify(title, body) {
+                result = Err(e);
+            }
+        }
+
+        result
+    }
+}
+
+fn emit_serialized<D: EventDispatcher + ?Sized, S: Serialize>(
+    handle: &D,
+    event: &str,
+    payload: S,
+) -> Result<(), EventDispatchError> {
+    let payload = serde_json::to_value(payload)
+        .map_err(|e| EventDispatchError::Serialization(e.to_string()))?;
+
+    handle.emit_event(event, payload)
+}
+
+pub fn dispatch_updated_device<D: EventDispatcher + ?Sized>(
+    handle: &D,
     device: &device::MeshDevice,
-) -> tauri::Result<()> {
+) -> Result<(), EventDispatchError> {
     debug!("Dispatching updated device");
 
-    handle.emit("device_update", device)?;
+    emit_serialized(handle, "device_update", device)?;
 
     trace!("Dispatched updated device");
 
     Ok(())
 }
 
-pub fn dispatch_configuration_status<R: tauri::Runtime>(
-    handle: &tauri::AppHandle<R>,
+pub fn dispatch_configuration_status<D: EventDispatcher + ?Sized>(
+    handle: &D,
     status: ConfigurationStatus,
-) -> tauri::Result<()> {
+) -> Result<(), EventDispatchError> {
     debug!("Dispatching configuration status");
 
-    handle.emit("configuration_status", status)?;
+    emit_serialized(handle, "configuration_status", status)?;
 
     Ok(())
 }
 
-pub fn dispatch_rebooting_event<R: tauri::Runtime>(
-    handle: &tauri::AppHandle<R>,
-) -> tauri::Result<()> {
+pub fn dispatch_rebooting_event<D: EventDispatcher + ?Sized>(
+    handle: &D,
+) -> Result<(), EventDispatchError> {
     debug!("Dispatching rebooting event");
 
     let current_time_sec = std::time::SystemTime::now()
@@ -38,18 +199,59 @@ pub fn dispatch_rebooting_event<R: tauri::Runtime>(
         .expect("Time went backwards")
         .as_secs();
 
-    handle.emit("reboot", current_time_sec)?;
+    emit_serialized(handle, "reboot", current_time_sec)?;
 
     Ok(())
 }
 
-pub fn dispatch_updated_graph<R: tauri::Runtime>(
-    handle: &tauri::AppHandle<R>,
+pub fn dispatch_updated_graph<D: EventDispatcher + ?Sized>(
+    handle: &D,
     graph: MeshGraph,
-) -> tauri::Result<()> {
+) -> Result<(), EventDispatchError> {
     debug!("Dispatching updated graph");
 
-    handle.emit("graph_update", graph)?;
+    emit_serialized(handle, "graph_update", graph)?;
 
     Ok(())
 }
+
+/// Emits changes made to the graph, skipping the event if nothing changed.
+/// The full graph is only dispatched for the initial sync.
+pub fn dispatch_graph_deltas<D: EventDispatcher + ?Sized>(
+    handle: &D,
+    deltas: Vec<GraphDelta>,
+) -> Result<(), EventDispatchError> {
+    if deltas.is_empty() {
+        return Ok(());
+    }
+
+    debug!("Dispatching {} graph deltas", deltas.len());
+
+    emit_serialized(handle, "graph_delta", deltas)?;
+
+    Ok(())
+}
+
+pub fn dispatch_telemetry_alert<D: EventDispatcher + ?Sized>(
+    handle: &D,
+    alert: &TelemetryAlert,
+) -> Result<(), EventDispatchError> {
+    debug!(
+        "Dispatching {:?} telemetry alert for rule {} on node {}",
+        alert.status, alert.rule_id, alert.node_num
+    );
+
+    emit_serialized(handle, "telemetry_alert", alert)?;
+
+    Ok(())
+}
+
+pub fn dispatch_notification<D: EventDispatcher + ?Sized>(
+    handle: &D,
+    title: &str,
+    body: &str,
+) -> Result<(), EventDispatchError> {
+    debug!("Dispatching notification \"{}\"", title);
+
+    handle.notify(title, body)
+}
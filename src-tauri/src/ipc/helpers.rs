@@ -1,17 +1,22 @@
+use std::sync::Arc;
 use std::time::Duration;
 
 use log::{trace, warn};
 use meshtastic::packet::PacketRouter;
 use meshtastic::protobufs;
+use tauri::async_runtime::JoinHandle;
 use tokio::sync::mpsc::UnboundedReceiver;
 
+use crate::device::helpers::get_channel_topic_name;
 use crate::device::SerialDeviceStatus;
-use crate::ipc::events::dispatch_configuration_status;
+use crate::ipc::events::{dispatch_configuration_status, EventDispatcher};
 use crate::ipc::ConfigurationStatus;
+use crate::mqtt::proxy::sync_mqtt_proxy;
+use crate::packet_api::bus::{PacketBus, ReceivedPacket};
 use crate::state::{self, DeviceKey};
 
 pub fn spawn_configuration_timeout_handler(
-    handle: tauri::AppHandle,
+    events: Arc<dyn EventDispatcher>,
     connected_devices_inner: state::mesh_devices::MeshDevicesStateInner,
     device_key: DeviceKey,
     timeout: Duration,
@@ -49,7 +54,7 @@ pub fn spawn_configuration_timeout_handler(
         warn!("Device configuration timed out, telling UI to disconnect device");
 
         dispatch_configuration_status(
-            &handle,
+            &events,
             ConfigurationStatus {
                 device_key,
                 successful: false,
@@ -64,11 +69,22 @@ pub fn spawn_configuration_timeout_handler(
     });
 }
 
+/// Why a decoded packet handler stopped routing packets
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodedHandlerExit {
+    /// The stream to the radio closed
+    StreamClosed,
+    /// The radio reported that it rebooted, so it needs to be reconfigured
+    Rebooted,
+}
+
 pub fn spawn_decoded_handler(
     mut decoded_listener: UnboundedReceiver<protobufs::FromRadio>,
     connected_devices_arc: state::mesh_devices::MeshDevicesStateInner,
     device_key: DeviceKey,
-) {
+    packet_bus: PacketBus,
+    radio_connections_arc: state::radio_connections::RadioConnectionsStateInner,
+) -> JoinHandle<DecodedHandlerExit> {
     tauri::async_runtime::spawn(async move {
         while let Some(packet) = decoded_listener.recv().await {
             trace!("Received packet from device: {:?}", packet);
@@ -85,13 +101,47 @@ pub fn spawn_decoded_handler(
                 }
             };
 
-            match packet_api.handle_packet_from_radio(packet) {
-                Ok(result) => result,
-                Err(err) => {
-                    warn!("{}", err);
-                    continue;
-                }
+            let channel_name = match packet.payload_variant.as_ref() {
+                Some(protobufs::from_radio::PayloadVariant::Packet(mesh_packet)) => Some(
+                    get_channel_topic_name(&packet_api.device, &mesh_packet.channel),
+                ),
+                _ => None,
+            };
+
+            packet_api.record_capture(&packet);
+
+            let rebooted = matches!(
+                packet.payload_variant,
+                Some(protobufs::from_radio::PayloadVariant::Rebooted(_))
+            );
+
+            let received_packet = ReceivedPacket {
+                device_key: device_key.clone(),
+                my_node_num: packet_api.device.my_node_info.my_node_num,
+                channel_name,
+                packet: packet.clone(),
             };
+
+            // Subscribers receive packets regardless of whether this client supports them
+            if let Err(err) = packet_api.handle_packet_from_radio(packet) {
+                warn!("{}", err);
+            }
+
+            // Configuration packets may enable or disable the MQTT client proxy
+            sync_mqtt_proxy(
+                packet_api,
+                connected_devices_arc.clone(),
+                radio_connections_arc.clone(),
+            );
+
+            packet_bus.publish(received_packet);
+
+            if rebooted {
+                packet_api.device.set_status(SerialDeviceStatus::Restarting);
+                return DecodedHandlerExit::Rebooted;
+            }
         }
-    });
+
+        DecodedHandlerExit::StreamClosed
+    })
 }
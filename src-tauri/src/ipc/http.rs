@@ -0,0 +1,359 @@
+use std::collections::HashMap;
+
+use axum::body::Bytes;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path, Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use log::{debug, info, warn};
+use tokio::net::TcpListener;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use super::auth::ApiAuth;
+use super::context::IpcContext;
+use super::dispatch::{dispatch_command, DispatchError};
+use super::events::{BroadcastEventDispatcher, DispatchedEvent};
+use super::CommandError;
+
+#[derive(Clone)]
+struct HttpState {
+    context: IpcContext,
+    events: BroadcastEventDispatcher,
+    auth: ApiAuth,
+}
+
+/// Serves every command as `POST /api/<command>` with the command's request
+/// contract as a JSON body, and streams all dispatched events as JSON text
+/// frames over a WebSocket at `GET /events`. Every request must carry the
+/// API token as a bearer token, which WebSocket clients that can't set
+/// headers may pass as the `token` query parameter instead. Commands that
+/// take file paths aren't served, see `dispatch::LOCAL_ONLY_COMMANDS`.
+pub async fn serve_http(
+    address: &str,
+    context: IpcContext,
+    events: BroadcastEventDispatcher,
+    auth: ApiAuth,
+) -> std::io::Result<()> {
+    let listener = TcpListener::bind(address).await?;
+    info!("Serving HTTP API on {}", listener.local_addr()?);
+
+    axum::serve(listener, router(context, events, auth)).await
+}
+
+fn router(context: IpcContext, events: BroadcastEventDispatcher, auth: ApiAuth) -> Router {
+    Router::new()
+        .route("/api/:command", post(handle_command))
+        .route("/events", get(handle_events_upgrade))
+        .with_state(HttpState {
+            context,
+            events,
+            auth,
+        })
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response {
+    (status, Json(CommandError::from(message))).into_response()
+}
+
+/// Rejects requests from web pages on origins that aren't allowed, which
+/// could otherwise use a browser to reach a locally served API
+fn check_origin(auth: &ApiAuth, headers: &HeaderMap) -> Result<(), Response> {
+    let origin = headers
+        .get(header::ORIGIN)
+        .map(|origin| origin.to_str().unwrap_or_default());
+
+    if auth.is_allowed_origin(origin) {
+        Ok(())
+    } else {
+        warn!("Rejected HTTP request from origin {:?}", origin);
+        Err(error_response(StatusCode::FORBIDDEN, "Origin not allowed"))
+    }
+}
+
+fn check_token(
+    auth: &ApiAuth,
+    headers: &HeaderMap,
+    query_token: Option<&str>,
+) -> Result<(), Response> {
+    let authorization = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|authorization| authorization.to_str().ok());
+
+    if auth.is_authorized(authorization) || query_token.is_some_and(|token| auth.is_token(token)) {
+        Ok(())
+    } else {
+        Err(error_response(
+            StatusCode::UNAUTHORIZED,
+            "Not authenticated",
+        ))
+    }
+}
+
+fn is_json(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .and_then(|content_type| content_type.split(';').next())
+        .is_some_and(|mime| mime.trim().eq_ignore_ascii_case("application/json"))
+}
+
+async fn handle_command(
+    State(state): State<HttpState>,
+    Path(command): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    debug!("Received HTTP request for command \"{}\"", command);
+
+    if let Err(response) =
+        check_origin(&state.auth, &headers).and_then(|_| check_token(&state.auth, &headers, None))
+    {
+        return response;
+    }
+
+    if !is_json(&headers) {
+        return error_response(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "Requests must be sent as application/json",
+        );
+    }
+
+    // Requests without fields may be sent without a body
+    let request = if body.is_empty() {
+        serde_json::Value::Null
+    } else {
+        match serde_json::from_slice(&body) {
+            Ok(request) => request,
+            Err(e) => {
+                let error = CommandError::from(format!("Malformed request: {}", e));
+                return (StatusCode::BAD_REQUEST, Json(error)).into_response();
+            }
+        }
+    };
+
+    match dispatch_command(&state.context, &command, request).await {
+        Ok(response) => Json(response).into_response(),
+        Err(DispatchError::UnknownCommand(error)) => {
+            (StatusCode::NOT_FOUND, Json(error)).into_response()
+        }
+        Err(DispatchError::Forbidden(error)) => {
+            (StatusCode::FORBIDDEN, Json(error)).into_response()
+        }
+        Err(DispatchError::InvalidRequest(error)) => {
+            (StatusCode::BAD_REQUEST, Json(error)).into_response()
+        }
+        Err(DispatchError::Failed(error)) => {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(error)).into_response()
+        }
+    }
+}
+
+async fn handle_events_upgrade(
+    State(state): State<HttpState>,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    let query_token = query.get("token").map(String::as_str);
+
+    if let Err(response) = check_origin(&state.auth, &headers)
+        .and_then(|_| check_token(&state.auth, &headers, query_token))
+    {
+        return response;
+    }
+
+    let event_listener = state.events.subscribe();
+    upgrade.on_upgrade(move |socket| stream_events(socket, event_listener))
+}
+
+async fn stream_events(
+    mut socket: WebSocket,
+    mut event_listener: broadcast::Receiver<DispatchedEvent>,
+) {
+    debug!("WebSocket client subscribed to events");
+
+    loop {
+        tokio::select! {
+            event = event_listener.recv() => match event {
+                Ok(event) => {
+                    let encoded = match serde_json::to_string(&event) {
+                        Ok(encoded) => encoded,
+                        Err(e) => {
+                            warn!("Failed to serialize event \"{}\": {}", event.event, e);
+                            continue;
+                        }
+                    };
+
+                    if socket.send(Message::Text(encoded)).await.is_err() {
+                        break;
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("WebSocket client lagged, skipped {} events", skipped);
+                }
+                Err(RecvError::Closed) => break,
+            },
+            // Clients only receive events, so incoming messages other than closes are ignored
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+
+    debug!("WebSocket client unsubscribed from events");
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::state;
+
+    const TOKEN: &str = "secret";
+
+    /// Serves the API on a free local port, returning its base URL
+    async fn serve() -> String {
+        let events = BroadcastEventDispatcher::new(16);
+        let context = IpcContext {
+            events: state::events::EventsState::new(Arc::new(events.clone())),
+            autoconnect: state::autoconnect::AutoConnectState::new(),
+            mesh_devices: state::mesh_devices::MeshDevicesState::new(None, None),
+            radio_connections: state::radio_connections::RadioConnectionsState::new(),
+            mesh_graph: state::graph::GraphState::new(),
+            mqtt_bridge: state::mqtt::MqttBridgeState::new(),
+        };
+        let auth =
+            ApiAuth::new(TOKEN.into()).with_allowed_origins(vec!["http://localhost:5173".into()]);
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, router(context, events, auth))
+                .await
+                .unwrap();
+        });
+
+        format!("http://{}", address)
+    }
+
+    async fn post_command(
+        base_url: &str,
+        command: &str,
+        body: &str,
+        headers: &[(&str, &str)],
+    ) -> reqwest::StatusCode {
+        let mut request = reqwest::Client::new()
+            .post(format!("{}/api/{}", base_url, command))
+            .body(body.to_string());
+
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+
+        request.send().await.unwrap().status()
+    }
+
+    const AUTHORIZED_JSON: [(&str, &str); 2] = [
+        ("Authorization", "Bearer secret"),
+        ("Content-Type", "application/json"),
+    ];
+
+    #[tokio::test]
+    async fn requires_token_json_and_allowed_origin() {
+        let base_url = serve().await;
+
+        let status = post_command(&base_url, "get_graph_state", "{}", &AUTHORIZED_JSON).await;
+        assert_eq!(status, reqwest::StatusCode::OK);
+
+        let status = post_command(
+            &base_url,
+            "get_graph_state",
+            "{}",
+            &[("Content-Type", "application/json")],
+        )
+        .await;
+        assert_eq!(status, reqwest::StatusCode::UNAUTHORIZED);
+
+        let status = post_command(
+            &base_url,
+            "get_graph_state",
+            "{}",
+            &[
+                ("Authorization", "Bearer secret"),
+                ("Content-Type", "text/plain"),
+            ],
+        )
+        .await;
+        assert_eq!(status, reqwest::StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let mut from_other_site = AUTHORIZED_JSON.to_vec();
+        from_other_site.push(("Origin", "https://example.com"));
+        let status = post_command(&base_url, "get_graph_state", "{}", &from_other_site).await;
+        assert_eq!(status, reqwest::StatusCode::FORBIDDEN);
+
+        let mut from_allowed_site = AUTHORIZED_JSON.to_vec();
+        from_allowed_site.push(("Origin", "http://localhost:5173"));
+        let status = post_command(&base_url, "get_graph_state", "{}", &from_allowed_site).await;
+        assert_eq!(status, reqwest::StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn maps_dispatch_errors_to_status_codes() {
+        let base_url = serve().await;
+
+        let status = post_command(&base_url, "not_a_command", "{}", &AUTHORIZED_JSON).await;
+        assert_eq!(status, reqwest::StatusCode::NOT_FOUND);
+
+        let status = post_command(&base_url, "clear_range_test", "{}", &AUTHORIZED_JSON).await;
+        assert_eq!(status, reqwest::StatusCode::BAD_REQUEST);
+
+        let status = post_command(
+            &base_url,
+            "clear_range_test",
+            r#"{"deviceKey": "/dev/ttyUSB0"}"#,
+            &AUTHORIZED_JSON,
+        )
+        .await;
+        assert_eq!(status, reqwest::StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[tokio::test]
+    async fn rejects_commands_with_caller_chosen_paths() {
+        let base_url = serve().await;
+        let file_path = std::env::temp_dir().join(format!("remote-export-{}", std::process::id()));
+        let body = serde_json::json!({ "format": "dot", "filePath": file_path }).to_string();
+
+        let status = post_command(&base_url, "export_graph", &body, &AUTHORIZED_JSON).await;
+        assert_eq!(status, reqwest::StatusCode::FORBIDDEN);
+        assert!(!file_path.exists());
+    }
+
+    #[tokio::test]
+    async fn requires_token_for_event_stream() {
+        let base_url = serve().await;
+        let client = reqwest::Client::new();
+
+        let upgrade = |url: String| {
+            client
+                .get(url)
+                .header("Connection", "upgrade")
+                .header("Upgrade", "websocket")
+                .header("Sec-WebSocket-Version", "13")
+                .header("Sec-WebSocket-Key", "dGhlIHNhbXBsZSBub25jZQ==")
+                .send()
+        };
+
+        let response = upgrade(format!("{}/events", base_url)).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+
+        let response = upgrade(format!("{}/events?token={}", base_url, TOKEN))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::SWITCHING_PROTOCOLS);
+    }
+}
@@ -4,9 +4,43 @@ use meshtastic::ts::specta::{self, Type};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+pub mod auth;
 pub mod commands;
+pub mod context;
+pub mod dispatch;
 pub mod events;
 pub mod helpers;
+#[cfg(feature = "http-api")]
+pub mod http;
+pub mod socket;
+
+/// Serves the HTTP API in the background, failing if the client was built
+/// without the "http-api" feature
+#[cfg(feature = "http-api")]
+pub fn spawn_http_api(
+    address: String,
+    context: context::IpcContext,
+    dispatcher: events::BroadcastEventDispatcher,
+    auth: auth::ApiAuth,
+) -> Result<(), String> {
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = http::serve_http(&address, context, dispatcher, auth).await {
+            log::error!("HTTP API stopped: {}", e);
+        }
+    });
+
+    Ok(())
+}
+
+#[cfg(not(feature = "http-api"))]
+pub fn spawn_http_api(
+    _address: String,
+    _context: context::IpcContext,
+    _dispatcher: events::BroadcastEventDispatcher,
+    _auth: auth::ApiAuth,
+) -> Result<(), String> {
+    Err("The HTTP API requires building with the \"http-api\" feature".into())
+}
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize, thiserror::Error)]
 #[serde(rename_all = "camelCase")]
@@ -39,9 +73,14 @@ impl From<&str> for CommandError {
 #[derive(Clone, Debug, Default, Serialize, Deserialize, Type)]
 #[serde(rename_all = "camelCase")]
 pub struct APMincutStringResults {
-    ap_result: Vec<u32>,
-    mincut_result: Vec<(u32, u32)>,
-    diffcen_result: HashMap<u32, HashMap<u32, HashMap<u32, f64>>>,
+    /// Nodes whose failure would split the mesh
+    pub ap_result: Vec<u32>,
+    /// Smallest set of links whose failure would split the mesh
+    pub mincut_result: Vec<(u32, u32)>,
+    /// Diffusion centrality keyed by node, then horizon, then link probability in percent
+    pub diffcen_result: HashMap<u32, HashMap<u32, HashMap<u32, f64>>>,
+    /// Normalized betweenness centrality of each node
+    pub betweenness_result: HashMap<u32, f64>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, Type)]
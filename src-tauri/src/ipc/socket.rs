@@ -0,0 +1,232 @@
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::mpsc;
+
+use super::auth::ApiAuth;
+use super::context::IpcContext;
+use super::dispatch::dispatch_command;
+use super::events::{BroadcastEventDispatcher, DispatchedEvent};
+use super::CommandError;
+
+/// Command a socket client must send first, with the API token as `token`
+pub const AUTHENTICATE_COMMAND: &str = "authenticate";
+
+/// A command sent by a socket client as a single line of JSON
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SocketRequest {
+    /// Caller-defined identifier echoed back in the response
+    pub id: Option<u64>,
+    pub command: String,
+    #[serde(default)]
+    pub request: serde_json::Value,
+}
+
+/// A message sent to socket clients as a single line of JSON
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum SocketMessage {
+    Response {
+        id: Option<u64>,
+        result: serde_json::Value,
+    },
+    Error {
+        id: Option<u64>,
+        error: CommandError,
+    },
+    Event(DispatchedEvent),
+}
+
+/// Number of messages queued for a socket client before requests and
+/// events wait for it to catch up
+const OUTGOING_MESSAGE_CAPACITY: usize = 256;
+
+/// Serves commands and events as newline-delimited JSON over TCP.
+/// Clients must first authenticate by sending the API token as
+/// `{"command": "authenticate", "request": {"token": "..."}}`,
+/// after which they receive all dispatched events.
+pub async fn serve_socket(
+    address: &str,
+    context: IpcContext,
+    events: BroadcastEventDispatcher,
+    auth: ApiAuth,
+) -> std::io::Result<()> {
+    let listener = TcpListener::bind(address).await?;
+    info!("Listening for socket clients on {}", listener.local_addr()?);
+
+    loop {
+        let (stream, peer_address) = listener.accept().await?;
+        debug!("Accepted socket client {}", peer_address);
+
+        let context = context.clone();
+        let events = events.clone();
+        let auth = auth.clone();
+
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = handle_socket_client(stream, context, events, auth).await {
+                warn!(
+                    "Socket client {} disconnected with error: {}",
+                    peer_address, e
+                );
+            } else {
+                debug!("Socket client {} disconnected", peer_address);
+            }
+        });
+    }
+}
+
+async fn handle_socket_client(
+    stream: TcpStream,
+    context: IpcContext,
+    events: BroadcastEventDispatcher,
+    auth: ApiAuth,
+) -> std::io::Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    if !authenticate_socket_client(&mut lines, &mut writer, &auth).await? {
+        return Ok(());
+    }
+
+    // Requests are handled concurrently, so responses and events are all
+    // queued to a single writer to keep messages from interleaving
+    let (messages, outgoing) = mpsc::channel(OUTGOING_MESSAGE_CAPACITY);
+    let event_forwarder =
+        tauri::async_runtime::spawn(forward_events(events.subscribe(), messages.clone()));
+
+    let result = relay_socket_messages(lines, writer, context, messages, outgoing).await;
+
+    event_forwarder.abort();
+    result
+}
+
+/// Handles each request of an authenticated client as its own task, and
+/// writes their responses along with events as they become available
+async fn relay_socket_messages(
+    mut lines: Lines<BufReader<OwnedReadHalf>>,
+    mut writer: OwnedWriteHalf,
+    context: IpcContext,
+    messages: mpsc::Sender<SocketMessage>,
+    mut outgoing: mpsc::Receiver<SocketMessage>,
+) -> std::io::Result<()> {
+    loop {
+        tokio::select! {
+            line = lines.next_line() => match line? {
+                Some(line) if line.trim().is_empty() => {}
+                Some(line) => {
+                    let context = context.clone();
+                    let messages = messages.clone();
+
+                    tauri::async_runtime::spawn(async move {
+                        let message = handle_socket_request(&context, &line).await;
+
+                        // The client may have disconnected while the request was handled
+                        let _ = messages.send(message).await;
+                    });
+                }
+                None => return Ok(()),
+            },
+            Some(message) = outgoing.recv() => write_message(&mut writer, &message).await?,
+        }
+    }
+}
+
+/// Waits for the client's first request, which must authenticate it.
+/// Returns whether the client was authenticated.
+async fn authenticate_socket_client(
+    lines: &mut Lines<BufReader<OwnedReadHalf>>,
+    writer: &mut OwnedWriteHalf,
+    auth: &ApiAuth,
+) -> std::io::Result<bool> {
+    let line = loop {
+        match lines.next_line().await? {
+            Some(line) if line.trim().is_empty() => continue,
+            Some(line) => break line,
+            None => return Ok(false),
+        }
+    };
+
+    let request = serde_json::from_str::<SocketRequest>(&line).ok();
+    let id = request.as_ref().and_then(|request| request.id);
+
+    let authenticated = request.is_some_and(|request| {
+        request.command == AUTHENTICATE_COMMAND
+            && request
+                .request
+                .get("token")
+                .and_then(|token| token.as_str())
+                .is_some_and(|token| auth.is_token(token))
+    });
+
+    let message = if authenticated {
+        SocketMessage::Response {
+            id,
+            result: serde_json::Value::Null,
+        }
+    } else {
+        warn!("Rejected socket client that didn't authenticate");
+        SocketMessage::Error {
+            id,
+            error: "Not authenticated".into(),
+        }
+    };
+
+    write_message(writer, &message).await?;
+    Ok(authenticated)
+}
+
+async fn forward_events(
+    mut event_listener: broadcast::Receiver<DispatchedEvent>,
+    messages: mpsc::Sender<SocketMessage>,
+) {
+    loop {
+        match event_listener.recv().await {
+            Ok(event) => {
+                if messages.send(SocketMessage::Event(event)).await.is_err() {
+                    return;
+                }
+            }
+            Err(RecvError::Lagged(skipped)) => {
+                warn!("Socket client lagged, skipped {} events", skipped);
+            }
+            Err(RecvError::Closed) => return,
+        }
+    }
+}
+
+async fn write_message(
+    writer: &mut OwnedWriteHalf,
+    message: &SocketMessage,
+) -> std::io::Result<()> {
+    let mut encoded = serde_json::to_vec(message)?;
+    encoded.push(b'\n');
+    writer.write_all(&encoded).await
+}
+
+async fn handle_socket_request(context: &IpcContext, line: &str) -> SocketMessage {
+    let SocketRequest {
+        id,
+        command,
+        request,
+    } = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(e) => {
+            return SocketMessage::Error {
+                id: None,
+                error: format!("Malformed request: {}", e).into(),
+            }
+        }
+    };
+
+    match dispatch_command(context, &command, request).await {
+        Ok(result) => SocketMessage::Response { id, result },
+        Err(error) => SocketMessage::Error {
+            id,
+            error: error.into(),
+        },
+    }
+}
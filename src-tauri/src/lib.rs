@@ -3,16 +3,25 @@
     windows_subsystem = "windows"
 )]
 
+mod alerts;
 mod api;
+mod capture;
 mod cli;
+mod daemon;
 mod device;
 mod domains;
+mod export;
 mod graph;
 mod ipc;
+mod mqtt;
 mod packet_api;
 mod state;
+mod storage;
 
-use log::{info, LevelFilter};
+use std::sync::Arc;
+
+use ipc::events::{BroadcastEventDispatcher, EventDispatcher, FanOutEventDispatcher};
+use log::{info, warn, LevelFilter};
 use specta::{
     export::ts_with_cfg,
     ts::{BigIntExportBehavior, ExportConfiguration, ModuleExportBehavior, TsExportError},
@@ -30,6 +39,20 @@ fn export_ts_types(file_path: &str) -> Result<(), TsExportError> {
 
 const LOG_LEVEL: LevelFilter = LevelFilter::Debug;
 
+/// Directory within the app data directory that device histories are stored in
+pub(crate) const DEVICE_HISTORY_DIR: &str = "history";
+
+/// File a generated API token is written to, in the app data directory
+const API_TOKEN_FILE_NAME: &str = "api-token";
+
+/// Number of events buffered for each HTTP API client before it is considered lagging
+const HTTP_EVENT_BUFFER_CAPACITY: usize = 1024;
+
+/// Runs the client headlessly, see `daemon::run`
+pub fn run_daemon() {
+    daemon::run();
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
@@ -60,21 +83,89 @@ pub fn run() {
             // #[cfg(debug_assertions)]
             // export_ts_types("../src/bindings/index.ts")?;
 
-            let initial_mesh_devices_state = state::mesh_devices::MeshDevicesState::new();
-            let initial_radio_connections_state =
-                state::radio_connections::RadioConnectionsState::new();
+            let history_dir = app
+                .path()
+                .app_data_dir()
+                .map(|dir| dir.join(DEVICE_HISTORY_DIR))
+                .map_err(|e| warn!("Failed to resolve device history directory: {}", e))
+                .ok();
+
             let mut inital_autoconnect_state = state::autoconnect::AutoConnectState::new();
-            let initial_graph_state = state::graph::GraphState::new();
+            let mut http_address = None;
+            let mut api_token = None;
 
-            match cli::handle_cli_matches(app, &mut inital_autoconnect_state) {
+            match cli::handle_cli_matches(
+                app,
+                &mut inital_autoconnect_state,
+                &mut http_address,
+                &mut api_token,
+            ) {
                 Ok(_) => {}
                 Err(err) => panic!("Failed to parse CLI args:\n{}", err),
             }
 
+            let webview_dispatcher: Arc<dyn EventDispatcher> = Arc::new(app.app_handle().clone());
+            let http_dispatcher = http_address
+                .as_ref()
+                .map(|_| BroadcastEventDispatcher::new(HTTP_EVENT_BUFFER_CAPACITY));
+
+            // Events are mirrored to HTTP API clients when the API is enabled
+            let initial_events_state = match http_dispatcher.clone() {
+                Some(http_dispatcher) => {
+                    state::events::EventsState::new(Arc::new(FanOutEventDispatcher::new(vec![
+                        webview_dispatcher,
+                        Arc::new(http_dispatcher),
+                    ])))
+                }
+                None => state::events::EventsState::new(webview_dispatcher),
+            };
+            let initial_mesh_devices_state =
+                state::mesh_devices::MeshDevicesState::new(history_dir, None);
+            let initial_radio_connections_state =
+                state::radio_connections::RadioConnectionsState::new();
+            let initial_graph_state = state::graph::GraphState::new();
+            let initial_mqtt_bridge_state = state::mqtt::MqttBridgeState::new();
+
+            if let (Some(address), Some(dispatcher)) = (http_address, http_dispatcher) {
+                let context = ipc::context::IpcContext {
+                    events: initial_events_state.clone(),
+                    autoconnect: inital_autoconnect_state.clone(),
+                    mesh_devices: initial_mesh_devices_state.clone(),
+                    radio_connections: initial_radio_connections_state.clone(),
+                    mesh_graph: initial_graph_state.clone(),
+                    mqtt_bridge: initial_mqtt_bridge_state.clone(),
+                };
+
+                let auth = ipc::auth::ApiAuth::from_config(api_token);
+
+                if auth.generated_token().is_some() {
+                    match app.path().app_data_dir() {
+                        Ok(dir) => {
+                            let token_path = dir.join(API_TOKEN_FILE_NAME);
+
+                            match auth.write_token_file(&token_path) {
+                                Ok(_) => info!(
+                                    "No API token configured, wrote generated token to {:?}",
+                                    token_path
+                                ),
+                                Err(e) => warn!("Failed to write generated API token: {}", e),
+                            }
+                        }
+                        Err(e) => warn!("Failed to resolve API token directory: {}", e),
+                    }
+                }
+
+                if let Err(e) = ipc::spawn_http_api(address.clone(), context, dispatcher, auth) {
+                    warn!("Not serving HTTP API on {}: {}", address, e);
+                }
+            }
+
+            app.app_handle().manage(initial_events_state);
             app.app_handle().manage(initial_mesh_devices_state);
             app.app_handle().manage(initial_radio_connections_state);
             app.app_handle().manage(inital_autoconnect_state); // Needs to be set after being mutated by CLI parser
             app.app_handle().manage(initial_graph_state);
+            app.app_handle().manage(initial_mqtt_bridge_state);
 
             Ok(())
         })
@@ -85,11 +176,26 @@ pub fn run() {
             ipc::commands::connections::connect_to_bluetooth,
             ipc::commands::connections::connect_to_serial_port,
             ipc::commands::connections::connect_to_tcp_port,
+            ipc::commands::connections::connect_to_replay,
             ipc::commands::connections::drop_device_connection,
             ipc::commands::connections::drop_all_device_connections,
+            ipc::commands::capture::start_packet_capture,
+            ipc::commands::capture::stop_packet_capture,
             ipc::commands::mesh::send_text,
             ipc::commands::mesh::send_waypoint,
             ipc::commands::mesh::delete_waypoint,
+            ipc::commands::mesh::send_traceroute,
+            ipc::commands::mesh::request_store_forward_history,
+            ipc::commands::range_test::get_range_test_records,
+            ipc::commands::range_test::export_range_test,
+            ipc::commands::range_test::clear_range_test,
+            ipc::commands::telemetry::get_node_telemetry_history,
+            ipc::commands::telemetry::set_telemetry_retention,
+            ipc::commands::alerts::add_alert_rule,
+            ipc::commands::alerts::remove_alert_rule,
+            ipc::commands::alerts::list_alert_rules,
+            ipc::commands::nodes::export_nodes,
+            ipc::commands::nodes::import_node_roster,
             ipc::commands::radio::update_device_config,
             ipc::commands::radio::update_device_user,
             ipc::commands::radio::start_configuration_transaction,
@@ -98,7 +204,29 @@ pub fn run() {
             ipc::commands::graph::get_graph_state,
             ipc::commands::graph::initialize_timeout_handler,
             ipc::commands::graph::stop_timeout_handler,
+            ipc::commands::graph::run_graph_analysis,
+            ipc::commands::graph::get_best_path,
+            ipc::commands::graph::get_route_quality,
+            ipc::commands::graph::export_graph,
+            ipc::commands::graph::list_graph_snapshots,
+            ipc::commands::graph::get_graph_snapshot,
+            ipc::commands::graph::get_topology_diff,
+            ipc::commands::graph::get_observed_topology,
+            ipc::commands::graph::set_infer_neighbor_nodes,
+            ipc::commands::mqtt::start_mqtt_bridge,
+            ipc::commands::mqtt::stop_mqtt_bridge,
         ])
         .run(tauri::generate_context!())
         .expect("Error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    /// Regenerates the frontend bindings from the types deriving `Type`.
+    /// Run with `cargo test export_bindings -- --ignored`.
+    #[test]
+    #[ignore]
+    fn export_bindings() {
+        super::export_ts_types("../src/bindings/index.ts").expect("Failed to export bindings");
+    }
+}
@@ -0,0 +1,291 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use log::{debug, info, trace, warn};
+use meshtastic::packet::PacketDestination;
+use meshtastic::protobufs;
+use meshtastic::types::{MeshChannel, NodeId};
+use rumqttc::{AsyncClient, Event, MqttOptions, Packet, QoS};
+use tauri::async_runtime::JoinHandle;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::Instant;
+
+use crate::api::primitives::mqtt::MqttBridgeConfig;
+use crate::device::helpers::generate_rand_id;
+use crate::ipc::events;
+use crate::packet_api::bus::{PacketBus, ReceivedPacket};
+use crate::state::{self, DeviceKey};
+
+use super::json::{encode_packet, parse_downlink, DownlinkCommand};
+use super::topics::{json_downlink_topic_filter, json_uplink_topic};
+
+const MQTT_KEEP_ALIVE_SECONDS: u64 = 30;
+const MQTT_RECONNECT_DELAY_SECONDS: u64 = 5;
+
+/// Number of outgoing requests buffered before publishing fails
+const MQTT_REQUEST_CAPACITY: usize = 128;
+
+/// Spawns a task that publishes every decoded packet received by a connected
+/// radio to the configured broker, and sends downlink messages through the
+/// radio they are addressed from. Reconnects to the broker until aborted.
+pub fn spawn_mqtt_bridge(
+    config: MqttBridgeConfig,
+    packets: PacketBus,
+    mesh_devices: state::mesh_devices::MeshDevicesState,
+    radio_connections: state::radio_connections::RadioConnectionsState,
+) -> JoinHandle<()> {
+    tauri::async_runtime::spawn(async move {
+        run_mqtt_bridge(config, packets, mesh_devices, radio_connections).await;
+    })
+}
+
+async fn run_mqtt_bridge(
+    config: MqttBridgeConfig,
+    packets: PacketBus,
+    mesh_devices: state::mesh_devices::MeshDevicesState,
+    radio_connections: state::radio_connections::RadioConnectionsState,
+) {
+    let client_id = config
+        .client_id
+        .clone()
+        .unwrap_or_else(|| format!("meshtastic-nmc-{:08x}", generate_rand_id::<u32>()));
+
+    let mut options = MqttOptions::new(client_id, config.host.clone(), config.port);
+    options.set_keep_alive(Duration::from_secs(MQTT_KEEP_ALIVE_SECONDS));
+
+    if let Some(username) = config.username.clone() {
+        options.set_credentials(username, config.password.clone().unwrap_or_default());
+    }
+
+    let (client, mut event_loop) = AsyncClient::new(options, MQTT_REQUEST_CAPACITY);
+    let mut packet_listener = packets.subscribe();
+
+    // Radios that downlink messages can be sent from, keyed by node number.
+    // Learned from received packets so that the device state doesn't need
+    // to be searched for every message.
+    let mut gateways: HashMap<u32, DeviceKey> = HashMap::new();
+
+    // Packets keep being published, and queued by the client, while waiting
+    // to reconnect, so that the bus doesn't lag behind
+    let mut reconnect_at: Option<Instant> = None;
+
+    info!(
+        "Starting MQTT bridge to {}:{} under \"{}\"",
+        config.host, config.port, config.root_topic
+    );
+
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep_until(reconnect_at.unwrap_or_else(Instant::now)),
+                if reconnect_at.is_some() =>
+            {
+                reconnect_at = None;
+            }
+            event = event_loop.poll(), if reconnect_at.is_none() => match event {
+                Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                    info!("Connected to MQTT broker");
+
+                    // Subscriptions don't persist across clean sessions
+                    if config.downlink_enabled {
+                        let topic = json_downlink_topic_filter(&config.root_topic);
+
+                        if let Err(e) = client.try_subscribe(topic, QoS::AtLeastOnce) {
+                            warn!("Failed to subscribe to MQTT downlink topic: {}", e);
+                        }
+                    }
+                }
+                Ok(Event::Incoming(Packet::Publish(publish))) => {
+                    debug!("Received MQTT downlink message on \"{}\"", publish.topic);
+
+                    if let Err(e) = handle_downlink(
+                        &publish.payload,
+                        &gateways,
+                        &mesh_devices,
+                        &radio_connections,
+                    )
+                    .await
+                    {
+                        warn!("Failed to handle MQTT downlink message: {}", e);
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    warn!(
+                        "MQTT connection error, retrying in {} seconds: {}",
+                        MQTT_RECONNECT_DELAY_SECONDS, e
+                    );
+                    reconnect_at =
+                        Some(Instant::now() + Duration::from_secs(MQTT_RECONNECT_DELAY_SECONDS));
+                }
+            },
+            packet = packet_listener.recv() => match packet {
+                Ok(packet) => {
+                    gateways.insert(packet.my_node_num, packet.device_key.clone());
+                    publish_packet(&client, &config, packet);
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("MQTT bridge lagged, skipped {} packets", skipped);
+                }
+                Err(RecvError::Closed) => break,
+            },
+        }
+    }
+
+    info!("MQTT bridge stopped");
+}
+
+fn publish_packet(client: &AsyncClient, config: &MqttBridgeConfig, received: ReceivedPacket) {
+    let mesh_packet = match received.packet.payload_variant {
+        Some(protobufs::from_radio::PayloadVariant::Packet(mesh_packet)) => mesh_packet,
+        _ => return,
+    };
+
+    let message = match encode_packet(&mesh_packet, received.my_node_num) {
+        Some(message) => message,
+        None => return,
+    };
+
+    let channel_name = received
+        .channel_name
+        .unwrap_or_else(|| mesh_packet.channel.to_string());
+
+    let topic = json_uplink_topic(&config.root_topic, &channel_name, received.my_node_num);
+    trace!("Publishing packet to \"{}\": {}", topic, message);
+
+    // Publishing without waiting keeps the event loop from stalling on a slow broker
+    if let Err(e) = client.try_publish(topic, QoS::AtMostOnce, false, message.to_string()) {
+        warn!("Failed to publish packet to MQTT broker: {}", e);
+    }
+}
+
+async fn handle_downlink(
+    payload: &[u8],
+    gateways: &HashMap<u32, DeviceKey>,
+    mesh_devices: &state::mesh_devices::MeshDevicesState,
+    radio_connections: &state::radio_connections::RadioConnectionsState,
+) -> Result<(), String> {
+    match parse_downlink(payload)? {
+        DownlinkCommand::SendText {
+            from,
+            to,
+            channel,
+            text,
+        } => {
+            let device_key = gateways
+                .get(&from)
+                .ok_or(format!("No connected radio with node number {}", from))?;
+
+            let mut devices_guard = mesh_devices.inner.lock().await;
+            let packet_api = devices_guard
+                .get_mut(device_key)
+                .ok_or("Device not connected")?;
+
+            let mut connections_guard = radio_connections.inner.lock().await;
+            let connection = connections_guard
+                .get_mut(device_key)
+                .ok_or("Radio connection not initialized")?;
+
+            let destination = match to {
+                Some(to) if to != u32::MAX => PacketDestination::Node(NodeId::new(to)),
+                _ => PacketDestination::Broadcast,
+            };
+
+            connection
+                .send_text(
+                    packet_api,
+                    text,
+                    destination,
+                    true,
+                    MeshChannel::new(channel).map_err(|e| e.to_string())?,
+                )
+                .await
+                .map_err(|e| e.to_string())?;
+
+            events::dispatch_updated_device(&packet_api.events, &packet_api.device)
+                .map_err(|e| e.to_string())?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Requires a broker listening on localhost:1883, e.g. `mosquitto -v`
+    #[tokio::test]
+    #[ignore]
+    async fn publishes_packets_to_local_broker() {
+        let config = MqttBridgeConfig {
+            host: "localhost".into(),
+            port: 1883,
+            username: None,
+            password: None,
+            client_id: None,
+            root_topic: "msh/TEST".into(),
+            downlink_enabled: false,
+        };
+
+        let (subscriber, mut subscriber_loop) =
+            AsyncClient::new(MqttOptions::new("nmc-bridge-test", "localhost", 1883), 16);
+        subscriber
+            .subscribe("msh/TEST/2/json/#", QoS::AtLeastOnce)
+            .await
+            .unwrap();
+
+        // Wait for the subscription to be acknowledged
+        while !matches!(
+            subscriber_loop.poll().await.unwrap(),
+            Event::Incoming(Packet::SubAck(_))
+        ) {}
+
+        let packets = PacketBus::new(16);
+        let bridge = spawn_mqtt_bridge(
+            config,
+            packets.clone(),
+            state::mesh_devices::MeshDevicesState::new(None, None),
+            state::radio_connections::RadioConnectionsState::new(),
+        );
+
+        // Give the bridge time to connect before publishing
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        packets.publish(ReceivedPacket {
+            device_key: "test".into(),
+            my_node_num: 0x22,
+            channel_name: Some("LongFast".into()),
+            packet: protobufs::FromRadio {
+                payload_variant: Some(protobufs::from_radio::PayloadVariant::Packet(
+                    protobufs::MeshPacket {
+                        from: 0x11,
+                        to: u32::MAX,
+                        payload_variant: Some(protobufs::mesh_packet::PayloadVariant::Decoded(
+                            protobufs::Data {
+                                portnum: protobufs::PortNum::TextMessageApp as i32,
+                                payload: b"hello".to_vec(),
+                                ..Default::default()
+                            },
+                        )),
+                        ..Default::default()
+                    },
+                )),
+                ..Default::default()
+            },
+        });
+
+        let publish = loop {
+            if let Event::Incoming(Packet::Publish(publish)) = subscriber_loop.poll().await.unwrap()
+            {
+                break publish;
+            }
+        };
+
+        assert_eq!(publish.topic, "msh/TEST/2/json/LongFast/!00000022");
+
+        let message: serde_json::Value = serde_json::from_slice(&publish.payload).unwrap();
+        assert_eq!(message["payload"]["text"], "hello");
+
+        bridge.abort();
+    }
+}
@@ -0,0 +1,277 @@
+use meshtastic::protobufs;
+use meshtastic::Message;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::device::helpers::{format_node_id, get_current_time_u32};
+
+/// Firmware message type for downlink text messages
+const DOWNLINK_SEND_TEXT: &str = "sendtext";
+
+/// Encodes a decoded mesh packet into the JSON payload published by the
+/// firmware's MQTT module. Returns `None` for encrypted packets and for
+/// ports the firmware doesn't publish as JSON.
+pub fn encode_packet(packet: &protobufs::MeshPacket, gateway_node_num: u32) -> Option<Value> {
+    let data = match packet.payload_variant.as_ref()? {
+        protobufs::mesh_packet::PayloadVariant::Decoded(data) => data,
+        protobufs::mesh_packet::PayloadVariant::Encrypted(_) => return None,
+    };
+
+    let (packet_type, payload) = encode_payload(data)?;
+
+    let timestamp = if packet.rx_time != 0 {
+        packet.rx_time
+    } else {
+        get_current_time_u32()
+    };
+
+    let mut message = json!({
+        "id": packet.id,
+        "channel": packet.channel,
+        "from": packet.from,
+        "to": packet.to,
+        "sender": format_node_id(gateway_node_num),
+        "timestamp": timestamp,
+        "type": packet_type,
+        "payload": strip_nulls(payload),
+    });
+
+    // Packets sent by the gateway itself have no reception metadata
+    if packet.rx_time != 0 {
+        message["rssi"] = json!(packet.rx_rssi);
+        message["snr"] = json!(packet.rx_snr);
+    }
+
+    if packet.hop_start != 0 {
+        message["hops_away"] = json!(packet.hop_start.saturating_sub(packet.hop_limit));
+    }
+
+    Some(message)
+}
+
+fn encode_payload(data: &protobufs::Data) -> Option<(&'static str, Value)> {
+    let encoded = match data.portnum() {
+        protobufs::PortNum::TextMessageApp => (
+            "text",
+            json!({ "text": String::from_utf8_lossy(&data.payload) }),
+        ),
+        protobufs::PortNum::PositionApp => {
+            let position = protobufs::Position::decode(data.payload.as_slice()).ok()?;
+
+            (
+                "position",
+                json!({
+                    "latitude_i": position.latitude_i,
+                    "longitude_i": position.longitude_i,
+                    "altitude": position.altitude,
+                    "time": position.time,
+                    "precision_bits": position.precision_bits,
+                    "sats_in_view": position.sats_in_view,
+                }),
+            )
+        }
+        protobufs::PortNum::NodeinfoApp => {
+            let user = protobufs::User::decode(data.payload.as_slice()).ok()?;
+
+            (
+                "nodeinfo",
+                json!({
+                    "id": user.id,
+                    "longname": user.long_name,
+                    "shortname": user.short_name,
+                    "hardware": user.hw_model,
+                    "role": user.role,
+                }),
+            )
+        }
+        protobufs::PortNum::TelemetryApp => {
+            let telemetry = protobufs::Telemetry::decode(data.payload.as_slice()).ok()?;
+            ("telemetry", encode_telemetry(telemetry.variant?)?)
+        }
+        protobufs::PortNum::NeighborinfoApp => {
+            let neighbor_info = protobufs::NeighborInfo::decode(data.payload.as_slice()).ok()?;
+
+            let neighbors: Vec<Value> = neighbor_info
+                .neighbors
+                .iter()
+                .map(|n| json!({ "node_id": n.node_id, "snr": n.snr }))
+                .collect();
+
+            (
+                "neighborinfo",
+                json!({
+                    "node_id": neighbor_info.node_id,
+                    "node_broadcast_interval_secs": neighbor_info.node_broadcast_interval_secs,
+                    "last_sent_by_id": neighbor_info.last_sent_by_id,
+                    "neighbors_count": neighbors.len(),
+                    "neighbors": neighbors,
+                }),
+            )
+        }
+        protobufs::PortNum::TracerouteApp => {
+            let route_discovery =
+                protobufs::RouteDiscovery::decode(data.payload.as_slice()).ok()?;
+
+            ("traceroute", json!({ "route": route_discovery.route }))
+        }
+        _ => return None,
+    };
+
+    Some(encoded)
+}
+
+fn encode_telemetry(variant: protobufs::telemetry::Variant) -> Option<Value> {
+    let payload = match variant {
+        protobufs::telemetry::Variant::DeviceMetrics(metrics) => json!({
+            "battery_level": metrics.battery_level,
+            "voltage": metrics.voltage,
+            "channel_utilization": metrics.channel_utilization,
+            "air_util_tx": metrics.air_util_tx,
+            "uptime_seconds": metrics.uptime_seconds,
+        }),
+        protobufs::telemetry::Variant::EnvironmentMetrics(metrics) => json!({
+            "temperature": metrics.temperature,
+            "relative_humidity": metrics.relative_humidity,
+            "barometric_pressure": metrics.barometric_pressure,
+            "gas_resistance": metrics.gas_resistance,
+            "voltage": metrics.voltage,
+            "current": metrics.current,
+        }),
+        _ => return None,
+    };
+
+    Some(payload)
+}
+
+/// Drops unset optional fields, which the firmware omits rather than sending as null
+fn strip_nulls(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            Value::Object(map.into_iter().filter(|(_, v)| !v.is_null()).collect())
+        }
+        value => value,
+    }
+}
+
+/// A JSON message published to the downlink topic, following the firmware's format
+#[derive(Clone, Debug, Deserialize)]
+pub struct DownlinkMessage {
+    /// Node number of the radio that should send the message
+    pub from: u32,
+    pub to: Option<u32>,
+    #[serde(default)]
+    pub channel: u32,
+    #[serde(rename = "type")]
+    pub message_type: String,
+    pub payload: Value,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DownlinkCommand {
+    SendText {
+        from: u32,
+        to: Option<u32>,
+        channel: u32,
+        text: String,
+    },
+}
+
+pub fn parse_downlink(payload: &[u8]) -> Result<DownlinkCommand, String> {
+    let message: DownlinkMessage = serde_json::from_slice(payload)
+        .map_err(|e| format!("Malformed downlink message: {}", e))?;
+
+    match message.message_type.as_str() {
+        DOWNLINK_SEND_TEXT => {
+            let text = message
+                .payload
+                .as_str()
+                .ok_or("Downlink text payload must be a string")?;
+
+            Ok(DownlinkCommand::SendText {
+                from: message.from,
+                to: message.to,
+                channel: message.channel,
+                text: text.into(),
+            })
+        }
+        other => Err(format!("Unsupported downlink message type \"{}\"", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decoded_packet(portnum: protobufs::PortNum, payload: Vec<u8>) -> protobufs::MeshPacket {
+        protobufs::MeshPacket {
+            id: 7,
+            from: 0x11,
+            to: u32::MAX,
+            channel: 0,
+            rx_time: 1_700_000_000,
+            rx_snr: 6.5,
+            rx_rssi: -80,
+            hop_start: 3,
+            hop_limit: 1,
+            payload_variant: Some(protobufs::mesh_packet::PayloadVariant::Decoded(
+                protobufs::Data {
+                    portnum: portnum as i32,
+                    payload,
+                    ..Default::default()
+                },
+            )),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn encodes_text_packets() {
+        let packet = decoded_packet(protobufs::PortNum::TextMessageApp, b"hello".to_vec());
+        let message = encode_packet(&packet, 0x22).expect("Expected encoded packet");
+
+        assert_eq!(message["type"], "text");
+        assert_eq!(message["sender"], "!00000022");
+        assert_eq!(message["payload"]["text"], "hello");
+        assert_eq!(message["hops_away"], 2);
+        assert_eq!(message["rssi"], -80);
+    }
+
+    #[test]
+    fn omits_unset_position_fields() {
+        let position = protobufs::Position {
+            latitude_i: Some(27_030_000),
+            longitude_i: Some(-71_000_000),
+            ..Default::default()
+        };
+
+        let packet = decoded_packet(protobufs::PortNum::PositionApp, position.encode_to_vec());
+        let message = encode_packet(&packet, 0x22).expect("Expected encoded packet");
+
+        assert_eq!(message["payload"]["latitude_i"], 27_030_000);
+        assert!(message["payload"].get("altitude").is_none());
+    }
+
+    #[test]
+    fn skips_unsupported_ports() {
+        let packet = decoded_packet(protobufs::PortNum::AdminApp, vec![]);
+        assert!(encode_packet(&packet, 0x22).is_none());
+    }
+
+    #[test]
+    fn parses_downlink_text() {
+        let command =
+            parse_downlink(br#"{"from": 34, "type": "sendtext", "payload": "hi"}"#).unwrap();
+
+        assert_eq!(
+            command,
+            DownlinkCommand::SendText {
+                from: 34,
+                to: None,
+                channel: 0,
+                text: "hi".into(),
+            }
+        );
+
+        assert!(parse_downlink(br#"{"from": 34, "type": "sendposition", "payload": {}}"#).is_err());
+    }
+}
@@ -0,0 +1,13 @@
+//! Bridges decoded mesh traffic to an MQTT broker using the JSON topic and
+//! payload conventions of the Meshtastic firmware's MQTT module.
+//!
+//! Packets are published to `<root>/2/json/<channel>/<gateway id>`, and when
+//! downlink is enabled, JSON messages published to `<root>/2/json/mqtt/` are
+//! sent through the gateway radio. To try it against a local broker, run
+//! `mosquitto -v` and `mosquitto_sub -t 'msh/#' -v`, then start the bridge
+//! with `host` set to `localhost`.
+
+pub mod bridge;
+pub mod json;
+pub mod proxy;
+pub mod topics;
@@ -0,0 +1,437 @@
+use std::time::Duration;
+
+use log::{debug, info, trace, warn};
+use meshtastic::protobufs;
+use rumqttc::{AsyncClient, Event, MqttOptions, Packet, QoS, Transport};
+use tauri::async_runtime::JoinHandle;
+use tokio::sync::mpsc;
+
+use crate::device::helpers::{generate_rand_id, get_channel_topic_name};
+use crate::device::{MeshDevice, MqttProxyStatus, SerialDeviceStatus};
+use crate::ipc::events;
+use crate::packet_api::MeshPacketApi;
+use crate::state::{self, DeviceKey};
+
+/// Broker, credentials and root topic the firmware uses when none are configured
+const DEFAULT_PROXY_BROKER_HOST: &str = "mqtt.meshtastic.org";
+const DEFAULT_PROXY_USERNAME: &str = "meshdev";
+const DEFAULT_PROXY_PASSWORD: &str = "large4cats";
+const DEFAULT_PROXY_ROOT_TOPIC: &str = "msh";
+
+const MQTT_PORT: u16 = 1883;
+const MQTT_TLS_PORT: u16 = 8883;
+
+const MQTT_KEEP_ALIVE_SECONDS: u64 = 30;
+const MQTT_RECONNECT_DELAY_SECONDS: u64 = 5;
+const MQTT_REQUEST_CAPACITY: usize = 128;
+
+/// Relays a radio's MQTT traffic when it is configured to use the client as
+/// its MQTT proxy. Messages from the radio are published to the broker from
+/// its module config, and messages on its downlink topics are sent back to it.
+pub struct MqttClientProxy {
+    uplink: mpsc::UnboundedSender<protobufs::MqttClientProxyMessage>,
+    task: JoinHandle<()>,
+    settings: ProxySettings,
+}
+
+/// What the proxy connected with, so that it can be restarted when the
+/// radio's broker, credentials or downlink channels change
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ProxySettings {
+    address: String,
+    username: String,
+    password: String,
+    tls_enabled: bool,
+    subscriptions: Vec<String>,
+}
+
+impl ProxySettings {
+    fn from_device(device: &MeshDevice) -> Self {
+        let config = device.module_config.mqtt.clone().unwrap_or_default();
+
+        Self {
+            subscriptions: proxy_subscriptions(device, &config),
+            address: config.address,
+            username: config.username,
+            password: config.password,
+            tls_enabled: config.tls_enabled,
+        }
+    }
+}
+
+impl MqttClientProxy {
+    pub fn forward_to_broker(
+        &self,
+        message: protobufs::MqttClientProxyMessage,
+    ) -> Result<(), String> {
+        self.uplink
+            .send(message)
+            .map_err(|_| "MQTT client proxy is not running".to_string())
+    }
+}
+
+impl Drop for MqttClientProxy {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Whether the radio expects the client to relay its MQTT traffic
+pub fn proxy_requested(device: &MeshDevice) -> bool {
+    device
+        .module_config
+        .mqtt
+        .as_ref()
+        .is_some_and(|mqtt| mqtt.enabled && mqtt.proxy_to_client_enabled)
+}
+
+/// Starts, stops or restarts the MQTT proxy of a device to match its module
+/// config. Waits until the device has finished configuring so that its
+/// channels are known.
+pub fn sync_mqtt_proxy(
+    packet_api: &mut MeshPacketApi,
+    mesh_devices: state::mesh_devices::MeshDevicesStateInner,
+    radio_connections: state::radio_connections::RadioConnectionsStateInner,
+) {
+    if packet_api.device.status != SerialDeviceStatus::Connected {
+        return;
+    }
+
+    match (
+        proxy_requested(&packet_api.device),
+        packet_api.mqtt_proxy.is_some(),
+    ) {
+        (true, false) => start_mqtt_proxy(packet_api, mesh_devices, radio_connections),
+        (true, true) => {
+            let settings = ProxySettings::from_device(&packet_api.device);
+
+            if packet_api
+                .mqtt_proxy
+                .as_ref()
+                .is_some_and(|proxy| proxy.settings != settings)
+            {
+                info!(
+                    "MQTT client proxy settings for \"{}\" changed, restarting proxy",
+                    packet_api.device_key
+                );
+                packet_api.mqtt_proxy = None;
+                start_mqtt_proxy(packet_api, mesh_devices, radio_connections);
+            }
+        }
+        (false, true) => {
+            info!(
+                "Stopping MQTT client proxy for \"{}\"",
+                packet_api.device_key
+            );
+            packet_api.mqtt_proxy = None;
+            update_proxy_status(packet_api, MqttProxyStatus::Disabled);
+        }
+        (false, false) => {}
+    }
+}
+
+fn start_mqtt_proxy(
+    packet_api: &mut MeshPacketApi,
+    mesh_devices: state::mesh_devices::MeshDevicesStateInner,
+    radio_connections: state::radio_connections::RadioConnectionsStateInner,
+) {
+    let config = packet_api
+        .device
+        .module_config
+        .mqtt
+        .clone()
+        .unwrap_or_default();
+
+    let client_id = format!("meshtastic-nmc-proxy-{:08x}", generate_rand_id::<u32>());
+    let options = proxy_broker_options(&config, client_id);
+    let settings = ProxySettings::from_device(&packet_api.device);
+
+    let (host, port) = options.broker_address();
+    info!(
+        "Starting MQTT client proxy for \"{}\" to {}:{}",
+        packet_api.device_key, host, port
+    );
+
+    let (uplink, uplink_listener) = mpsc::unbounded_channel();
+
+    let task = tauri::async_runtime::spawn(run_mqtt_proxy(
+        options,
+        settings.subscriptions.clone(),
+        uplink_listener,
+        packet_api.device_key.clone(),
+        mesh_devices,
+        radio_connections,
+    ));
+
+    packet_api.mqtt_proxy = Some(MqttClientProxy {
+        uplink,
+        task,
+        settings,
+    });
+    update_proxy_status(packet_api, MqttProxyStatus::Connecting);
+}
+
+async fn run_mqtt_proxy(
+    options: MqttOptions,
+    subscriptions: Vec<String>,
+    mut uplink_listener: mpsc::UnboundedReceiver<protobufs::MqttClientProxyMessage>,
+    device_key: DeviceKey,
+    mesh_devices: state::mesh_devices::MeshDevicesStateInner,
+    radio_connections: state::radio_connections::RadioConnectionsStateInner,
+) {
+    let (client, mut event_loop) = AsyncClient::new(options, MQTT_REQUEST_CAPACITY);
+
+    loop {
+        tokio::select! {
+            event = event_loop.poll() => match event {
+                Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                    info!("MQTT client proxy for \"{}\" connected to broker", device_key);
+
+                    // Subscriptions don't persist across clean sessions
+                    for topic in subscriptions.iter() {
+                        if let Err(e) = client.try_subscribe(topic.clone(), QoS::AtLeastOnce) {
+                            warn!("Failed to subscribe to \"{}\": {}", topic, e);
+                        }
+                    }
+
+                    set_proxy_status(&mesh_devices, &device_key, MqttProxyStatus::Connected).await;
+                }
+                Ok(Event::Incoming(Packet::Publish(publish))) => {
+                    trace!("Relaying MQTT message on \"{}\" to radio", publish.topic);
+
+                    let message = protobufs::MqttClientProxyMessage {
+                        topic: publish.topic.clone(),
+                        retained: publish.retain,
+                        payload_variant: Some(
+                            protobufs::mqtt_client_proxy_message::PayloadVariant::Data(
+                                publish.payload.to_vec(),
+                            ),
+                        ),
+                    };
+
+                    if let Err(e) = forward_to_radio(&radio_connections, &device_key, message).await {
+                        warn!("Failed to relay MQTT message to radio: {}", e);
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    warn!(
+                        "MQTT client proxy connection error, retrying in {} seconds: {}",
+                        MQTT_RECONNECT_DELAY_SECONDS, e
+                    );
+                    set_proxy_status(&mesh_devices, &device_key, MqttProxyStatus::Disconnected)
+                        .await;
+                    tokio::time::sleep(Duration::from_secs(MQTT_RECONNECT_DELAY_SECONDS)).await;
+                }
+            },
+            message = uplink_listener.recv() => match message {
+                Some(message) => publish_to_broker(&client, message),
+                None => break,
+            },
+        }
+    }
+}
+
+fn publish_to_broker(client: &AsyncClient, message: protobufs::MqttClientProxyMessage) {
+    trace!("Relaying MQTT message on \"{}\" to broker", message.topic);
+
+    let payload = match message.payload_variant {
+        Some(protobufs::mqtt_client_proxy_message::PayloadVariant::Data(data)) => data,
+        Some(protobufs::mqtt_client_proxy_message::PayloadVariant::Text(text)) => text.into_bytes(),
+        None => return,
+    };
+
+    if let Err(e) = client.try_publish(message.topic, QoS::AtLeastOnce, message.retained, payload) {
+        warn!("Failed to relay MQTT message to broker: {}", e);
+    }
+}
+
+async fn forward_to_radio(
+    radio_connections: &state::radio_connections::RadioConnectionsStateInner,
+    device_key: &DeviceKey,
+    message: protobufs::MqttClientProxyMessage,
+) -> Result<(), String> {
+    let mut connections_guard = radio_connections.lock().await;
+    let connection = connections_guard
+        .get_mut(device_key)
+        .ok_or("Radio connection not initialized")?;
+
+    connection
+        .send_to_radio_packet(Some(
+            protobufs::to_radio::PayloadVariant::MqttClientProxyMessage(message),
+        ))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+async fn set_proxy_status(
+    mesh_devices: &state::mesh_devices::MeshDevicesStateInner,
+    device_key: &DeviceKey,
+    status: MqttProxyStatus,
+) {
+    let mut devices_guard = mesh_devices.lock().await;
+
+    if let Some(packet_api) = devices_guard.get_mut(device_key) {
+        update_proxy_status(packet_api, status);
+    }
+}
+
+fn update_proxy_status(packet_api: &mut MeshPacketApi, status: MqttProxyStatus) {
+    if packet_api.device.mqtt_proxy_status == status {
+        return;
+    }
+
+    debug!("Setting MQTT client proxy status to {:?}", status);
+    packet_api.device.mqtt_proxy_status = status;
+
+    if let Err(e) = events::dispatch_updated_device(&packet_api.events, &packet_api.device) {
+        warn!("Failed to dispatch MQTT client proxy status: {}", e);
+    }
+}
+
+/// Resolves the broker address from the module config the way the firmware does
+fn proxy_broker_address(config: &protobufs::module_config::MqttConfig) -> (String, u16) {
+    let default_port = if config.tls_enabled {
+        MQTT_TLS_PORT
+    } else {
+        MQTT_PORT
+    };
+
+    if config.address.is_empty() {
+        return (DEFAULT_PROXY_BROKER_HOST.into(), default_port);
+    }
+
+    match config.address.rsplit_once(':') {
+        Some((host, port)) => match port.parse::<u16>() {
+            Ok(port) => (host.into(), port),
+            Err(_) => {
+                warn!("Invalid port in MQTT address \"{}\"", config.address);
+                (host.into(), default_port)
+            }
+        },
+        None => (config.address.clone(), default_port),
+    }
+}
+
+fn proxy_broker_options(
+    config: &protobufs::module_config::MqttConfig,
+    client_id: String,
+) -> MqttOptions {
+    let (host, port) = proxy_broker_address(config);
+
+    let mut options = MqttOptions::new(client_id, host, port);
+    options.set_keep_alive(Duration::from_secs(MQTT_KEEP_ALIVE_SECONDS));
+
+    if config.address.is_empty() && config.username.is_empty() {
+        options.set_credentials(DEFAULT_PROXY_USERNAME, DEFAULT_PROXY_PASSWORD);
+    } else if !config.username.is_empty() {
+        options.set_credentials(config.username.clone(), config.password.clone());
+    }
+
+    if config.tls_enabled {
+        options.set_transport(Transport::tls_with_default_config());
+    }
+
+    options
+}
+
+/// Topics the firmware would subscribe to itself, one per channel with downlink enabled
+fn proxy_subscriptions(
+    device: &MeshDevice,
+    config: &protobufs::module_config::MqttConfig,
+) -> Vec<String> {
+    let root_topic = proxy_root_topic(device, config);
+
+    device
+        .channels
+        .iter()
+        .filter(|(_, channel)| {
+            channel
+                .config
+                .settings
+                .as_ref()
+                .is_some_and(|settings| settings.downlink_enabled)
+        })
+        .map(|(index, _)| {
+            format!(
+                "{}/2/e/{}/+",
+                root_topic,
+                get_channel_topic_name(device, index)
+            )
+        })
+        .collect()
+}
+
+/// The firmware appends the LoRa region to the default root topic
+fn proxy_root_topic(device: &MeshDevice, config: &protobufs::module_config::MqttConfig) -> String {
+    if !config.root.is_empty() && config.root != DEFAULT_PROXY_ROOT_TOPIC {
+        return config.root.clone();
+    }
+
+    match device.config.lora.as_ref() {
+        Some(lora) => format!(
+            "{}/{}",
+            DEFAULT_PROXY_ROOT_TOPIC,
+            lora.region().as_str_name()
+        ),
+        None => DEFAULT_PROXY_ROOT_TOPIC.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn broker_address_defaults_match_firmware() {
+        let config = protobufs::module_config::MqttConfig::default();
+        assert_eq!(
+            proxy_broker_address(&config),
+            (DEFAULT_PROXY_BROKER_HOST.into(), MQTT_PORT)
+        );
+
+        let config = protobufs::module_config::MqttConfig {
+            address: "broker.local:1884".into(),
+            ..Default::default()
+        };
+        assert_eq!(proxy_broker_address(&config), ("broker.local".into(), 1884));
+
+        let config = protobufs::module_config::MqttConfig {
+            address: "broker.local".into(),
+            tls_enabled: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            proxy_broker_address(&config),
+            ("broker.local".into(), MQTT_TLS_PORT)
+        );
+    }
+
+    #[test]
+    fn settings_change_with_broker_credentials() {
+        let mut device = MeshDevice::new();
+        device.module_config.mqtt = Some(protobufs::module_config::MqttConfig {
+            address: "broker.local".into(),
+            username: "user".into(),
+            ..Default::default()
+        });
+        let settings = ProxySettings::from_device(&device);
+
+        if let Some(mqtt) = device.module_config.mqtt.as_mut() {
+            mqtt.password = "changed".into();
+        }
+
+        assert_ne!(ProxySettings::from_device(&device), settings);
+    }
+
+    #[test]
+    fn custom_root_topic_is_used_as_is() {
+        let device = MeshDevice::new();
+        let config = protobufs::module_config::MqttConfig {
+            root: "mesh/custom".into(),
+            ..Default::default()
+        };
+
+        assert_eq!(proxy_root_topic(&device, &config), "mesh/custom");
+    }
+}
@@ -0,0 +1,29 @@
+use crate::device::helpers::format_node_id;
+
+/// Topic that JSON encoded packets received on a channel are published to
+pub fn json_uplink_topic(root_topic: &str, channel_name: &str, gateway_node_num: u32) -> String {
+    format!(
+        "{}/2/json/{}/{}",
+        root_topic,
+        channel_name,
+        format_node_id(gateway_node_num)
+    )
+}
+
+/// Topic filter matching JSON messages that should be sent through a radio
+pub fn json_downlink_topic_filter(root_topic: &str) -> String {
+    format!("{}/2/json/mqtt/#", root_topic)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uplink_topic_follows_firmware_conventions() {
+        assert_eq!(
+            json_uplink_topic("msh/US", "LongFast", 0xdeadbeef),
+            "msh/US/2/json/LongFast/!deadbeef"
+        );
+    }
+}
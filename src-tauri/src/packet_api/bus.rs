@@ -0,0 +1,40 @@
+use meshtastic::protobufs;
+use tokio::sync::broadcast;
+
+use crate::state::DeviceKey;
+
+/// A packet received from a connected radio, along with the context needed
+/// to interpret it without access to the device state
+#[derive(Clone, Debug)]
+pub struct ReceivedPacket {
+    pub device_key: DeviceKey,
+    pub my_node_num: u32,
+
+    /// Name of the channel the packet was received on, if it is a mesh packet
+    pub channel_name: Option<String>,
+
+    pub packet: protobufs::FromRadio,
+}
+
+/// Broadcasts every packet received from connected radios to in-process
+/// subscribers (e.g., the MQTT bridge)
+#[derive(Clone)]
+pub struct PacketBus {
+    sender: broadcast::Sender<ReceivedPacket>,
+}
+
+impl PacketBus {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self { sender }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ReceivedPacket> {
+        self.sender.subscribe()
+    }
+
+    pub fn publish(&self, packet: ReceivedPacket) {
+        // Sending only fails when there are no subscribers, which isn't an error
+        let _ = self.sender.send(packet);
+    }
+}
@@ -5,10 +5,11 @@ use crate::{
     device::{helpers::get_current_time_u32, MeshChannel, SerialDeviceStatus},
     ipc::{events, ConfigurationStatus},
     packet_api::{handlers::DeviceUpdateError, MeshPacketApi},
+    storage::NodeSeries,
 };
 
-pub fn handle_channel_packet<R: tauri::Runtime>(
-    packet_api: &mut MeshPacketApi<R>,
+pub fn handle_channel_packet(
+    packet_api: &mut MeshPacketApi,
 
     channel: protobufs::Channel,
 ) -> Result<(), DeviceUpdateError> {
@@ -18,44 +19,44 @@ pub fn handle_channel_packet<R: tauri::Runtime>(
         messages: vec![],
     });
 
-    events::dispatch_updated_device(&packet_api.app_handle, &packet_api.device)
+    events::dispatch_updated_device(&packet_api.events, &packet_api.device)
         .map_err(|e| DeviceUpdateError::EventDispatchFailure(e.to_string()))?;
 
     Ok(())
 }
 
-pub fn handle_config_packet<R: tauri::Runtime>(
-    packet_api: &mut MeshPacketApi<R>,
+pub fn handle_config_packet(
+    packet_api: &mut MeshPacketApi,
 
     config: protobufs::Config,
 ) -> Result<(), DeviceUpdateError> {
     packet_api.device.set_config(config);
 
-    events::dispatch_updated_device(&packet_api.app_handle, &packet_api.device)
+    events::dispatch_updated_device(&packet_api.events, &packet_api.device)
         .map_err(|e| DeviceUpdateError::EventDispatchFailure(e.to_string()))?;
 
     Ok(())
 }
 
-pub fn handle_module_config_packet<R: tauri::Runtime>(
-    packet_api: &mut MeshPacketApi<R>,
+pub fn handle_module_config_packet(
+    packet_api: &mut MeshPacketApi,
 
     module_config: protobufs::ModuleConfig,
 ) -> Result<(), DeviceUpdateError> {
     packet_api.device.set_module_config(module_config);
 
-    events::dispatch_updated_device(&packet_api.app_handle, &packet_api.device)
+    events::dispatch_updated_device(&packet_api.events, &packet_api.device)
         .map_err(|e| DeviceUpdateError::EventDispatchFailure(e.to_string()))?;
 
     Ok(())
 }
 
-pub fn handle_config_complete_packet<R: tauri::Runtime>(
-    packet_api: &mut MeshPacketApi<R>,
+pub fn handle_config_complete_packet(
+    packet_api: &mut MeshPacketApi,
 ) -> Result<(), DeviceUpdateError> {
     packet_api.device.set_status(SerialDeviceStatus::Configured);
 
-    events::dispatch_updated_device(&packet_api.app_handle, &packet_api.device)
+    events::dispatch_updated_device(&packet_api.events, &packet_api.device)
         .map_err(|e| DeviceUpdateError::EventDispatchFailure(e.to_string()))?;
 
     if packet_api.device.status == SerialDeviceStatus::Configured {
@@ -65,7 +66,7 @@ pub fn handle_config_complete_packet<R: tauri::Runtime>(
         );
 
         events::dispatch_configuration_status(
-            &packet_api.app_handle,
+            &packet_api.events,
             ConfigurationStatus {
                 device_key: packet_api.device_key.clone(),
                 successful: true,
@@ -74,46 +75,91 @@ pub fn handle_config_complete_packet<R: tauri::Runtime>(
         )
         .map_err(|e| DeviceUpdateError::EventDispatchFailure(e.to_string()))?;
 
+        // Send the whole graph once so that clients can apply later deltas to it
+        let mut graph = packet_api
+            .get_locked_graph()
+            .map_err(|e| DeviceUpdateError::GeneralFailure(e.to_string()))?;
+
+        graph.take_deltas();
+
+        events::dispatch_updated_graph(&packet_api.events, graph.clone())
+            .map_err(|e| DeviceUpdateError::EventDispatchFailure(e.to_string()))?;
+
+        drop(graph);
+
         packet_api.device.set_status(SerialDeviceStatus::Connected);
     }
 
     Ok(())
 }
 
-pub fn handle_my_node_info_packet<R: tauri::Runtime>(
-    packet_api: &mut MeshPacketApi<R>,
+pub fn handle_my_node_info_packet(
+    packet_api: &mut MeshPacketApi,
 
     my_node_info: protobufs::MyNodeInfo,
 ) -> Result<(), DeviceUpdateError> {
     packet_api.device.set_my_node_info(my_node_info);
 
-    events::dispatch_updated_device(&packet_api.app_handle, &packet_api.device)
+    // The radio's node number is needed to locate its history,
+    // so history can only be loaded once it is known
+    if packet_api.history.is_none() {
+        packet_api.open_history();
+    }
+
+    events::dispatch_updated_device(&packet_api.events, &packet_api.device)
         .map_err(|e| DeviceUpdateError::EventDispatchFailure(e.to_string()))?;
 
     Ok(())
 }
 
-pub fn handle_node_info_packet<R: tauri::Runtime>(
-    packet_api: &mut MeshPacketApi<R>,
+pub fn handle_node_info_packet(
+    packet_api: &mut MeshPacketApi,
     node_info: protobufs::NodeInfo,
 ) -> Result<(), DeviceUpdateError> {
+    let mut series = vec![];
+    if node_info.device_metrics.is_some() {
+        series.push(NodeSeries::DeviceMetrics);
+    }
+    if node_info.position.is_some() {
+        series.push(NodeSeries::Position);
+    }
+
     packet_api.device.add_node_info(node_info.clone());
+    packet_api.record_node_history(node_info.num, &series);
 
     let mut graph = packet_api
         .get_locked_graph()
         .map_err(|e| DeviceUpdateError::GeneralFailure(e.to_string()))?;
 
-    graph.update_from_node_info(node_info);
+    graph.update_from_node_info(&packet_api.device_key, node_info);
 
-    events::dispatch_updated_device(&packet_api.app_handle, &packet_api.device)
+    events::dispatch_updated_device(&packet_api.events, &packet_api.device)
         .map_err(|e| DeviceUpdateError::EventDispatchFailure(e.to_string()))?;
 
-    events::dispatch_updated_graph(&packet_api.app_handle, graph.clone())
+    events::dispatch_graph_deltas(&packet_api.events, graph.take_deltas())
         .map_err(|e| DeviceUpdateError::EventDispatchFailure(e.to_string()))?;
 
     Ok(())
 }
 
+pub fn handle_mqtt_client_proxy_message_packet(
+    packet_api: &mut MeshPacketApi,
+
+    message: protobufs::MqttClientProxyMessage,
+) -> Result<(), DeviceUpdateError> {
+    let proxy = packet_api.mqtt_proxy.as_ref().ok_or_else(|| {
+        DeviceUpdateError::GeneralFailure(
+            "Received MQTT client proxy message without a running proxy".into(),
+        )
+    })?;
+
+    proxy
+        .forward_to_broker(message)
+        .map_err(DeviceUpdateError::GeneralFailure)?;
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     // * Integration test converage within `mod.rs`
@@ -1,36 +1,42 @@
-use log::debug;
+use log::{debug, trace, warn};
 use meshtastic::protobufs;
 use tauri_plugin_notification::Notification;
 
 use crate::{
+    alerts::AlertStatus,
     device::{
-        helpers::{get_channel_name, get_node_user_name},
+        helpers::{
+            get_channel_name, get_current_time_u32, get_node_user_name, parse_range_test_sequence,
+        },
         ChannelMessageState, NeighborInfoPacket, NormalizedWaypoint, PositionPacket,
-        TelemetryPacket, TextPacket, UserPacket, WaypointPacket,
+        TelemetryPacket, TextPacket, TracerouteResult, UserPacket, WaypointPacket,
     },
     ipc::events,
     packet_api::{handlers::DeviceUpdateError, MeshPacketApi},
+    storage::{HistoryRecord, NodeSeries},
 };
 use meshtastic::Message;
 
-pub fn handle_user_mesh_packet<R: tauri::Runtime>(
-    packet_api: &mut MeshPacketApi<R>,
+pub fn handle_user_mesh_packet(
+    packet_api: &mut MeshPacketApi,
     packet: protobufs::MeshPacket,
     data: protobufs::Data,
 ) -> Result<(), DeviceUpdateError> {
     let data = protobufs::User::decode(data.payload.as_slice())
         .map_err(|e| DeviceUpdateError::DecodeFailure(e.to_string()))?;
 
+    let node_num = packet.from;
     packet_api.device.add_user(UserPacket { packet, data });
+    packet_api.record_node_history(node_num, &[]);
 
-    events::dispatch_updated_device(&packet_api.app_handle, &packet_api.device)
+    events::dispatch_updated_device(&packet_api.events, &packet_api.device)
         .map_err(|e| DeviceUpdateError::EventDispatchFailure(e.to_string()))?;
 
     Ok(())
 }
 
-pub fn handle_position_mesh_packet<R: tauri::Runtime>(
-    packet_api: &mut MeshPacketApi<R>,
+pub fn handle_position_mesh_packet(
+    packet_api: &mut MeshPacketApi,
     packet: protobufs::MeshPacket,
     data: protobufs::Data,
 ) -> Result<(), DeviceUpdateError> {
@@ -41,24 +47,25 @@ pub fn handle_position_mesh_packet<R: tauri::Runtime>(
         packet: packet.clone(),
         data: data.clone(),
     });
+    packet_api.record_node_history(packet.from, &[NodeSeries::Position]);
 
     let mut graph = packet_api
         .get_locked_graph()
         .map_err(|e| DeviceUpdateError::GeneralFailure(e.to_string()))?;
 
-    graph.update_from_position(packet, data);
+    graph.update_from_position(&packet_api.device_key, packet, data);
 
-    events::dispatch_updated_device(&packet_api.app_handle, &packet_api.device)
+    events::dispatch_updated_device(&packet_api.events, &packet_api.device)
         .map_err(|e| DeviceUpdateError::EventDispatchFailure(e.to_string()))?;
 
-    events::dispatch_updated_graph(&packet_api.app_handle, graph.clone())
+    events::dispatch_graph_deltas(&packet_api.events, graph.take_deltas())
         .map_err(|e| DeviceUpdateError::EventDispatchFailure(e.to_string()))?;
 
     Ok(())
 }
 
-pub fn handle_routing_mesh_packet<R: tauri::Runtime>(
-    packet_api: &mut MeshPacketApi<R>,
+pub fn handle_routing_mesh_packet(
+    packet_api: &mut MeshPacketApi,
     packet: protobufs::MeshPacket,
     data: protobufs::Data,
 ) -> Result<(), DeviceUpdateError> {
@@ -114,7 +121,9 @@ pub fn handle_routing_mesh_packet<R: tauri::Runtime>(
                         }
                     }
 
-                    events::dispatch_updated_device(&packet_api.app_handle, &packet_api.device)
+                    packet_api.record_message_state_history(packet.channel, data.request_id);
+
+                    events::dispatch_updated_device(&packet_api.events, &packet_api.device)
                         .map_err(|e| DeviceUpdateError::EventDispatchFailure(e.to_string()))?;
                 }
             }
@@ -130,26 +139,61 @@ pub fn handle_routing_mesh_packet<R: tauri::Runtime>(
     Ok(())
 }
 
-pub fn handle_telemetry_mesh_packet<R: tauri::Runtime>(
-    packet_api: &mut MeshPacketApi<R>,
+pub fn handle_telemetry_mesh_packet(
+    packet_api: &mut MeshPacketApi,
     packet: protobufs::MeshPacket,
     data: protobufs::Data,
 ) -> Result<(), DeviceUpdateError> {
     let data = protobufs::Telemetry::decode(data.payload.as_slice())
         .map_err(|e| DeviceUpdateError::DecodeFailure(e.to_string()))?;
 
+    let node_num = packet.from;
+    let series = NodeSeries::from_telemetry(&data);
+
+    // Replayed telemetry is historical, so it must not raise alerts stamped with the current time
+    let alerts = if packet_api.is_replay() {
+        vec![]
+    } else {
+        packet_api
+            .alerts
+            .lock()
+            .map_err(|e| DeviceUpdateError::GeneralFailure(e.to_string()))?
+            .evaluate(node_num, &data, get_current_time_u32())
+    };
+
     packet_api
         .device
         .set_device_metrics(TelemetryPacket { packet, data });
+    packet_api.record_node_history(node_num, series.as_slice());
 
-    events::dispatch_updated_device(&packet_api.app_handle, &packet_api.device)
+    events::dispatch_updated_device(&packet_api.events, &packet_api.device)
         .map_err(|e| DeviceUpdateError::EventDispatchFailure(e.to_string()))?;
 
+    for alert in alerts {
+        events::dispatch_telemetry_alert(&packet_api.events, &alert)
+            .map_err(|e| DeviceUpdateError::EventDispatchFailure(e.to_string()))?;
+
+        if alert.status != AlertStatus::Firing {
+            continue;
+        }
+
+        let node_name = get_node_user_name(&mut packet_api.device, &alert.node_num)
+            .unwrap_or_else(|| alert.node_num.to_string());
+
+        let body = format!(
+            "{:?} of {} is {} ({:?} {})",
+            alert.metric, node_name, alert.value, alert.comparison, alert.threshold
+        );
+
+        events::dispatch_notification(&packet_api.events, &alert.rule_name, &body)
+            .map_err(|e| DeviceUpdateError::NotificationDispatchFailure(e.to_string()))?;
+    }
+
     Ok(())
 }
 
-pub fn handle_text_message_mesh_packet<R: tauri::Runtime>(
-    packet_api: &mut MeshPacketApi<R>,
+pub fn handle_text_message_mesh_packet(
+    packet_api: &mut MeshPacketApi,
     packet: protobufs::MeshPacket,
     data: protobufs::Data,
 ) -> Result<(), DeviceUpdateError> {
@@ -160,6 +204,7 @@ pub fn handle_text_message_mesh_packet<R: tauri::Runtime>(
         packet: packet.clone(),
         data: data.clone(),
     });
+    packet_api.record_latest_message_history(packet.channel);
 
     let from_user_name = get_node_user_name(&mut packet_api.device, &packet.from)
         .unwrap_or_else(|| packet.from.to_string());
@@ -168,7 +213,7 @@ pub fn handle_text_message_mesh_packet<R: tauri::Runtime>(
         .unwrap_or_else(|| "Unknown channel".into());
 
     // Always keep updates at bottom in case of failure during functions
-    events::dispatch_updated_device(&packet_api.app_handle, &packet_api.device)
+    events::dispatch_updated_device(&packet_api.events, &packet_api.device)
         .map_err(|e| DeviceUpdateError::EventDispatchFailure(e.to_string()))?;
 
     if packet.from != packet_api.device.my_node_info.my_node_num {
@@ -191,8 +236,8 @@ pub fn handle_text_message_mesh_packet<R: tauri::Runtime>(
     Ok(())
 }
 
-pub fn handle_waypoint_mesh_packet<R: tauri::Runtime>(
-    packet_api: &mut MeshPacketApi<R>,
+pub fn handle_waypoint_mesh_packet(
+    packet_api: &mut MeshPacketApi,
     packet: protobufs::MeshPacket,
     data: protobufs::Data,
 ) -> Result<(), DeviceUpdateError> {
@@ -206,6 +251,10 @@ pub fn handle_waypoint_mesh_packet<R: tauri::Runtime>(
         packet: packet.clone(),
         data: converted_data.clone(),
     });
+    packet_api.record_history(HistoryRecord::Waypoint {
+        waypoint: converted_data.clone(),
+    });
+    packet_api.record_latest_message_history(packet.channel);
 
     let from_user_name = get_node_user_name(&mut packet_api.device, &packet.from)
         .unwrap_or_else(|| packet.from.to_string());
@@ -213,7 +262,7 @@ pub fn handle_waypoint_mesh_packet<R: tauri::Runtime>(
     let channel_name = get_channel_name(&mut packet_api.device, &packet.channel)
         .unwrap_or_else(|| "Unknown channel".into());
 
-    events::dispatch_updated_device(&packet_api.app_handle, &packet_api.device)
+    events::dispatch_updated_device(&packet_api.events, &packet_api.device)
         .map_err(|e| DeviceUpdateError::EventDispatchFailure(e.to_string()))?;
 
     if packet.from != packet_api.device.my_node_info.my_node_num {
@@ -239,29 +288,211 @@ pub fn handle_waypoint_mesh_packet<R: tauri::Runtime>(
     Ok(())
 }
 
-pub fn handle_neighbor_info_mesh_packet<R: tauri::Runtime>(
-    packet_api: &mut MeshPacketApi<R>,
+pub fn handle_neighbor_info_mesh_packet(
+    packet_api: &mut MeshPacketApi,
     packet: protobufs::MeshPacket,
     data: protobufs::Data,
 ) -> Result<(), DeviceUpdateError> {
     let data = protobufs::NeighborInfo::decode(data.payload.as_slice())
         .map_err(|e| DeviceUpdateError::DecodeFailure(e.to_string()))?;
 
-    packet_api.device.add_neighborinfo(NeighborInfoPacket {
+    let neighbor_info = NeighborInfoPacket {
         packet: packet.clone(),
         data: data.clone(),
+    };
+    packet_api.device.add_neighborinfo(neighbor_info.clone());
+    packet_api.record_history(HistoryRecord::NeighborInfo { neighbor_info });
+
+    let mut graph = packet_api
+        .get_locked_graph()
+        .map_err(|e| DeviceUpdateError::GeneralFailure(e.to_string()))?;
+
+    graph.update_from_neighbor_info(&packet_api.device_key, packet, data);
+
+    events::dispatch_updated_device(&packet_api.events, &packet_api.device)
+        .map_err(|e| DeviceUpdateError::EventDispatchFailure(e.to_string()))?;
+
+    events::dispatch_graph_deltas(&packet_api.events, graph.take_deltas())
+        .map_err(|e| DeviceUpdateError::EventDispatchFailure(e.to_string()))?;
+
+    Ok(())
+}
+
+pub fn handle_traceroute_mesh_packet(
+    packet_api: &mut MeshPacketApi,
+    packet: protobufs::MeshPacket,
+    data: protobufs::Data,
+) -> Result<(), DeviceUpdateError> {
+    let route_discovery = protobufs::RouteDiscovery::decode(data.payload.as_slice())
+        .map_err(|e| DeviceUpdateError::DecodeFailure(e.to_string()))?;
+
+    let my_node_num = packet_api.device.my_node_info.my_node_num;
+
+    // Requests are answered by the firmware, only replies to our own requests are recorded
+    if data.request_id == 0 || packet.to != my_node_num {
+        debug!(
+            "Ignoring traceroute packet from {} not addressed to this device",
+            packet.from
+        );
+        return Ok(());
+    }
+
+    let traceroute =
+        TracerouteResult::from_route_discovery(my_node_num, packet.from, route_discovery);
+
+    packet_api.device.add_traceroute(traceroute.clone());
+    packet_api.record_history(HistoryRecord::Traceroute {
+        traceroute: traceroute.clone(),
     });
 
     let mut graph = packet_api
         .get_locked_graph()
         .map_err(|e| DeviceUpdateError::GeneralFailure(e.to_string()))?;
 
-    graph.update_from_neighbor_info(packet, data);
+    graph.update_from_traceroute(&packet_api.device_key, &traceroute);
 
-    events::dispatch_updated_device(&packet_api.app_handle, &packet_api.device)
+    events::dispatch_updated_device(&packet_api.events, &packet_api.device)
         .map_err(|e| DeviceUpdateError::EventDispatchFailure(e.to_string()))?;
 
-    events::dispatch_updated_graph(&packet_api.app_handle, graph.clone())
+    events::dispatch_graph_deltas(&packet_api.events, graph.take_deltas())
+        .map_err(|e| DeviceUpdateError::EventDispatchFailure(e.to_string()))?;
+
+    Ok(())
+}
+
+pub fn handle_range_test_mesh_packet(
+    packet_api: &mut MeshPacketApi,
+    packet: protobufs::MeshPacket,
+    data: protobufs::Data,
+) -> Result<(), DeviceUpdateError> {
+    let payload = String::from_utf8(data.payload)
+        .map_err(|e| DeviceUpdateError::GeneralFailure(e.to_string()))?;
+
+    let sequence = parse_range_test_sequence(&payload).ok_or_else(|| {
+        DeviceUpdateError::DecodeFailure(format!("Invalid range test payload \"{}\"", payload))
+    })?;
+
+    packet_api.device.add_range_test_record(&packet, sequence);
+
+    events::dispatch_updated_device(&packet_api.events, &packet_api.device)
+        .map_err(|e| DeviceUpdateError::EventDispatchFailure(e.to_string()))?;
+
+    Ok(())
+}
+
+pub fn handle_store_forward_mesh_packet(
+    packet_api: &mut MeshPacketApi,
+    packet: protobufs::MeshPacket,
+    data: protobufs::Data,
+) -> Result<(), DeviceUpdateError> {
+    let store_and_forward = protobufs::StoreAndForward::decode(data.payload.as_slice())
+        .map_err(|e| DeviceUpdateError::DecodeFailure(e.to_string()))?;
+
+    let request_response = store_and_forward.rr();
+
+    // Requests from other clients aren't relevant to this device
+    if !is_router_response(request_response) {
+        trace!(
+            "Ignoring store-and-forward {:?} from client {}",
+            request_response,
+            packet.from
+        );
+        return Ok(());
+    }
+
+    // Replayed messages keep the sender, id and receive time of the original
+    // message, so they don't identify the router that replayed them
+    if let Some(protobufs::store_and_forward::Variant::Text(text)) = store_and_forward.variant {
+        let data = String::from_utf8(text)
+            .map_err(|e| DeviceUpdateError::GeneralFailure(e.to_string()))?;
+
+        let message_id = packet.id;
+        let channel = packet.channel;
+
+        if packet_api
+            .device
+            .merge_text_message(TextPacket { packet, data })
+        {
+            packet_api.record_message_history(channel, message_id);
+        }
+
+        events::dispatch_updated_device(&packet_api.events, &packet_api.device)
+            .map_err(|e| DeviceUpdateError::EventDispatchFailure(e.to_string()))?;
+
+        return Ok(());
+    }
+
+    let server = packet_api.device.add_store_forward_server(packet.from);
+
+    match store_and_forward.variant {
+        Some(protobufs::store_and_forward::Variant::Heartbeat(heartbeat)) => {
+            server.last_heartbeat = Some(server.last_heard);
+            server.heartbeat_period = Some(heartbeat.period);
+            server.secondary = heartbeat.secondary != 0;
+        }
+        Some(protobufs::store_and_forward::Variant::Stats(stats)) => {
+            server.stats = Some(stats);
+        }
+        Some(protobufs::store_and_forward::Variant::History(history)) => {
+            debug!(
+                "Store-and-forward router {} replaying {} messages from the last {} minutes",
+                packet.from, history.history_messages, history.window
+            );
+            server.announced_history_messages = history.history_messages;
+        }
+        // Handled above
+        Some(protobufs::store_and_forward::Variant::Text(_)) => {}
+        None => match request_response {
+            protobufs::store_and_forward::RequestResponse::RouterBusy => {
+                warn!("Store-and-forward router {} is busy", packet.from);
+            }
+            protobufs::store_and_forward::RequestResponse::RouterError => {
+                warn!("Store-and-forward router {} reported an error", packet.from);
+            }
+            other => debug!(
+                "Received store-and-forward {:?} from router {}",
+                other, packet.from
+            ),
+        },
+    }
+
+    events::dispatch_updated_device(&packet_api.events, &packet_api.device)
+        .map_err(|e| DeviceUpdateError::EventDispatchFailure(e.to_string()))?;
+
+    Ok(())
+}
+
+fn is_router_response(request_response: protobufs::store_and_forward::RequestResponse) -> bool {
+    use protobufs::store_and_forward::RequestResponse;
+
+    matches!(
+        request_response,
+        RequestResponse::RouterError
+            | RequestResponse::RouterHeartbeat
+            | RequestResponse::RouterPing
+            | RequestResponse::RouterPong
+            | RequestResponse::RouterBusy
+            | RequestResponse::RouterHistory
+            | RequestResponse::RouterStats
+            | RequestResponse::RouterTextDirect
+            | RequestResponse::RouterTextBroadcast
+    )
+}
+
+/// Infers a direct link to this device from the metadata of a received packet
+pub fn handle_received_packet_link(
+    packet_api: &mut MeshPacketApi,
+    packet: &protobufs::MeshPacket,
+) -> Result<(), DeviceUpdateError> {
+    let my_node_num = packet_api.device.my_node_info.my_node_num;
+
+    let mut graph = packet_api
+        .get_locked_graph()
+        .map_err(|e| DeviceUpdateError::GeneralFailure(e.to_string()))?;
+
+    graph.update_from_received_packet(&packet_api.device_key, my_node_num, packet);
+
+    events::dispatch_graph_deltas(&packet_api.events, graph.take_deltas())
         .map_err(|e| DeviceUpdateError::EventDispatchFailure(e.to_string()))?;
 
     Ok(())
@@ -1,35 +1,211 @@
+use std::path::PathBuf;
 use std::sync::{Arc, LockResult, Mutex};
 
+use log::{info, warn};
+use meshtastic::protobufs;
+use tokio_util::sync::CancellationToken;
+
 // use meshtastic::connections::stream_api::{state::Configured, StreamApi};
 
-use crate::{device::MeshDevice, graph::ds::graph::MeshGraph, state::DeviceKey};
+use crate::{
+    alerts::AlertEngine,
+    capture::{CaptureRecord, CaptureWriter, REPLAY_DEVICE_KEY_PREFIX},
+    device::{helpers::get_current_time_millis, MeshDevice},
+    graph::ds::graph::MeshGraph,
+    ipc::events::EventDispatcher,
+    mqtt::proxy::MqttClientProxy,
+    state::DeviceKey,
+    storage::{DeviceHistoryStore, HistoryRecord, NodeSeries},
+};
 
+pub mod bus;
 pub mod handlers;
 pub mod router;
 
-pub struct MeshPacketApi<R: tauri::Runtime = tauri::Wry> {
-    pub app_handle: tauri::AppHandle<R>,
+pub struct MeshPacketApi {
+    pub events: Arc<dyn EventDispatcher>,
     pub device_key: DeviceKey,
     pub device: MeshDevice,
     pub graph_arc: Arc<Mutex<MeshGraph>>,
+    pub history_dir: Option<PathBuf>,
+    pub history: Option<DeviceHistoryStore>,
+    pub mqtt_proxy: Option<MqttClientProxy>,
+    pub capture: Option<CaptureWriter>,
+    pub alerts: Arc<Mutex<AlertEngine>>,
+    /// Cancelled when the connection is dropped, so that the tasks keeping it
+    /// alive don't mistake a later connection with the same key for it
+    pub cancellation: CancellationToken,
 }
 
-impl<R: tauri::Runtime> MeshPacketApi<R> {
+impl MeshPacketApi {
     pub fn new(
-        app_handle: tauri::AppHandle<R>,
+        events: Arc<dyn EventDispatcher>,
         device_key: DeviceKey,
         device: MeshDevice,
         graph_arc: Arc<Mutex<MeshGraph>>,
+        history_dir: Option<PathBuf>,
     ) -> Self {
         Self {
-            app_handle,
+            events,
             device_key,
             device,
             graph_arc,
+            history_dir,
+            history: None,
+            mqtt_proxy: None,
+            capture: None,
+            alerts: Arc::new(Mutex::new(AlertEngine::default())),
+            cancellation: CancellationToken::new(),
         }
     }
 
+    /// Whether packets come from a replayed capture rather than a live radio
+    pub fn is_replay(&self) -> bool {
+        self.device_key.starts_with(REPLAY_DEVICE_KEY_PREFIX)
+    }
+
     pub fn get_locked_graph(&self) -> LockResult<std::sync::MutexGuard<MeshGraph>> {
         self.graph_arc.lock()
     }
+
+    /// Opens the on-disk history for the connected radio and rehydrates the
+    /// device from it. Requires the radio's node number to be known.
+    pub fn open_history(&mut self) {
+        let history_dir = match self.history_dir.as_ref() {
+            Some(dir) => dir,
+            None => return,
+        };
+
+        let store = match DeviceHistoryStore::open(
+            history_dir,
+            &self.device_key,
+            self.device.my_node_info.my_node_num,
+        ) {
+            Ok(store) => store,
+            Err(e) => {
+                warn!("Failed to open device history: {}", e);
+                return;
+            }
+        };
+
+        if let Err(e) = store.rehydrate(&mut self.device) {
+            warn!("Failed to rehydrate device from history: {}", e);
+        }
+
+        info!("Loaded device history from {:?}", store.path());
+
+        self.history = Some(store);
+
+        // Drop records superseded during previous sessions
+        self.compact_history();
+    }
+
+    pub fn record_history(&mut self, record: HistoryRecord) {
+        let store = match self.history.as_mut() {
+            Some(store) => store,
+            None => return,
+        };
+
+        if let Err(e) = store.append(record) {
+            warn!("Failed to append device history record: {}", e);
+            return;
+        }
+
+        if store.needs_compaction() {
+            self.compact_history();
+        }
+    }
+
+    /// Records the attributes of a node along with the latest sample of each
+    /// of the passed series, rather than the node's whole history
+    pub fn record_node_history(&mut self, node_num: u32, series: &[NodeSeries]) {
+        let Some(node) = self.device.nodes.get(&node_num) else {
+            return;
+        };
+
+        let mut records = vec![HistoryRecord::node_attributes(node)];
+        records.extend(
+            series
+                .iter()
+                .filter_map(|series| HistoryRecord::node_sample(node, *series)),
+        );
+
+        for record in records {
+            self.record_history(record);
+        }
+    }
+
+    /// Records the most recently added message on the passed channel
+    pub fn record_latest_message_history(&mut self, channel: u32) {
+        let message = self
+            .device
+            .channels
+            .get(&channel)
+            .and_then(|c| c.messages.last().cloned());
+
+        if let Some(message) = message {
+            self.record_history(HistoryRecord::Message { channel, message });
+        }
+    }
+
+    pub fn record_message_history(&mut self, channel: u32, message_id: u32) {
+        let message = self.device.channels.get(&channel).and_then(|c| {
+            c.messages
+                .iter()
+                .find(|m| m.packet_id() == message_id)
+                .cloned()
+        });
+
+        if let Some(message) = message {
+            self.record_history(HistoryRecord::Message { channel, message });
+        }
+    }
+
+    pub fn record_message_state_history(&mut self, channel: u32, message_id: u32) {
+        let state = self.device.channels.get(&channel).and_then(|c| {
+            c.messages
+                .iter()
+                .find(|m| m.packet_id() == message_id)
+                .map(|m| m.state.clone())
+        });
+
+        if let Some(state) = state {
+            self.record_history(HistoryRecord::MessageState {
+                channel,
+                message_id,
+                state,
+            });
+        }
+    }
+
+    /// Appends a received packet to the active capture, if any. Capturing
+    /// stops if the capture can't be written to.
+    pub fn record_capture(&mut self, packet: &protobufs::FromRadio) {
+        let capture = match self.capture.as_mut() {
+            Some(capture) => capture,
+            None => return,
+        };
+
+        let record = CaptureRecord {
+            timestamp_ms: get_current_time_millis(),
+            packet: packet.clone(),
+        };
+
+        if let Err(e) = capture.write(&record) {
+            warn!(
+                "Failed to write to packet capture {:?}, stopping capture: {}",
+                capture.path(),
+                e
+            );
+            self.capture = None;
+        }
+    }
+
+    fn compact_history(&mut self) {
+        if let Some(store) = self.history.as_mut() {
+            if let Err(e) = store.compact(&self.device) {
+                warn!("Failed to compact device history: {}", e);
+            }
+        }
+    }
 }
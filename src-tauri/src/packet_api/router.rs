@@ -1,4 +1,4 @@
-use log::debug;
+use log::{debug, warn};
 use meshtastic::packet::PacketRouter;
 use meshtastic::protobufs;
 use meshtastic::types::NodeId;
@@ -11,7 +11,7 @@ use super::handlers::{
 };
 use super::MeshPacketApi;
 
-impl<R: tauri::Runtime> PacketRouter<(), DeviceUpdateError> for MeshPacketApi<R> {
+impl PacketRouter<(), DeviceUpdateError> for MeshPacketApi {
     fn source_node_id(&self) -> NodeId {
         NodeId::new(self.device.my_node_info.my_node_num)
     }
@@ -68,16 +68,14 @@ impl<R: tauri::Runtime> PacketRouter<(), DeviceUpdateError> for MeshPacketApi<R>
             }
             protobufs::from_radio::PayloadVariant::Rebooted(_) => {
                 debug!("Device rebooting");
-                events::dispatch_rebooting_event(&self.app_handle)
+                events::dispatch_rebooting_event(&self.events)
                     .map_err(|e| DeviceUpdateError::EventDispatchFailure(e.to_string()))?;
             }
             protobufs::from_radio::PayloadVariant::XmodemPacket(_) => {
                 return Err(DeviceUpdateError::RadioMessageNotSupported("xmodem".into()));
             }
-            protobufs::from_radio::PayloadVariant::MqttClientProxyMessage(_) => {
-                return Err(DeviceUpdateError::RadioMessageNotSupported(
-                    "mqtt client proxy message".into(),
-                ));
+            protobufs::from_radio::PayloadVariant::MqttClientProxyMessage(message) => {
+                from_radio_handlers::handle_mqtt_client_proxy_message_packet(self, message)?;
             }
             protobufs::from_radio::PayloadVariant::FileInfo(_) => {
                 return Err(DeviceUpdateError::RadioMessageNotSupported("file info".into()));
@@ -103,6 +101,16 @@ impl<R: tauri::Runtime> PacketRouter<(), DeviceUpdateError> for MeshPacketApi<R>
             .ok_or("No payload variant")
             .map_err(|e| DeviceUpdateError::GeneralFailure(e.to_string()))?;
 
+        // Every packet, including encrypted and unsupported ones, tells us about its sender
+        self.device.record_node_packet(&packet);
+
+        // Links are inferred from every decoded packet, including unsupported ones
+        if let protobufs::mesh_packet::PayloadVariant::Decoded(_) = variant {
+            if let Err(e) = mesh_packet_handlers::handle_received_packet_link(self, &packet) {
+                warn!("Failed to infer link from packet {}: {}", packet.id, e);
+            }
+        }
+
         match variant {
             protobufs::mesh_packet::PayloadVariant::Decoded(data) => match data.portnum() {
                 protobufs::PortNum::AdminApp => {
@@ -129,7 +137,7 @@ impl<R: tauri::Runtime> PacketRouter<(), DeviceUpdateError> for MeshPacketApi<R>
                     return Err(DeviceUpdateError::PacketNotSupported("admin".into()));
                 }
                 protobufs::PortNum::RangeTestApp => {
-                    return Err(DeviceUpdateError::PacketNotSupported("range test".into()));
+                    mesh_packet_handlers::handle_range_test_mesh_packet(self, packet, data)?;
                 }
                 protobufs::PortNum::RemoteHardwareApp => {
                     return Err(DeviceUpdateError::PacketNotSupported(
@@ -149,9 +157,7 @@ impl<R: tauri::Runtime> PacketRouter<(), DeviceUpdateError> for MeshPacketApi<R>
                     return Err(DeviceUpdateError::PacketNotSupported("simulator".into()));
                 }
                 protobufs::PortNum::StoreForwardApp => {
-                    return Err(DeviceUpdateError::PacketNotSupported(
-                        "store-forward".into(),
-                    ));
+                    mesh_packet_handlers::handle_store_forward_mesh_packet(self, packet, data)?;
                 }
                 protobufs::PortNum::TelemetryApp => {
                     mesh_packet_handlers::handle_telemetry_mesh_packet(self, packet, data)?;
@@ -174,7 +180,7 @@ impl<R: tauri::Runtime> PacketRouter<(), DeviceUpdateError> for MeshPacketApi<R>
                     mesh_packet_handlers::handle_neighbor_info_mesh_packet(self, packet, data)?;
                 }
                 protobufs::PortNum::TracerouteApp => {
-                    return Err(DeviceUpdateError::PacketNotSupported("traceroute".into()));
+                    mesh_packet_handlers::handle_traceroute_mesh_packet(self, packet, data)?;
                 }
                 protobufs::PortNum::DetectionSensorApp => {
                     return Err(DeviceUpdateError::PacketNotSupported(
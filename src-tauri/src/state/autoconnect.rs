@@ -5,7 +5,7 @@ use super::DeviceKey;
 
 pub type AutoConnectStateInner = Arc<async_runtime::Mutex<Option<DeviceKey>>>;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct AutoConnectState {
     pub inner: AutoConnectStateInner,
 }
@@ -0,0 +1,24 @@
+use std::sync::Arc;
+
+use crate::ipc::events::EventDispatcher;
+use crate::packet_api::bus::PacketBus;
+
+/// Number of received packets buffered for each packet bus subscriber
+const PACKET_BUS_CAPACITY: usize = 1024;
+
+pub type EventsStateInner = Arc<dyn EventDispatcher>;
+
+#[derive(Clone)]
+pub struct EventsState {
+    pub inner: EventsStateInner,
+    pub packets: PacketBus,
+}
+
+impl EventsState {
+    pub fn new(dispatcher: EventsStateInner) -> Self {
+        Self {
+            inner: dispatcher,
+            packets: PacketBus::new(PACKET_BUS_CAPACITY),
+        }
+    }
+}
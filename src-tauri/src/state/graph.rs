@@ -4,6 +4,7 @@ use crate::graph::ds::graph::MeshGraph;
 
 pub type GraphStateInner = Arc<Mutex<MeshGraph>>;
 
+#[derive(Clone)]
 pub struct GraphState {
     pub inner: GraphStateInner,
 }
@@ -1,20 +1,35 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
 use tauri::async_runtime;
 
-use crate::packet_api::MeshPacketApi;
+use crate::{alerts::AlertEngine, packet_api::MeshPacketApi};
 
 use super::DeviceKey;
 
 pub type MeshDevicesStateInner = Arc<async_runtime::Mutex<HashMap<DeviceKey, MeshPacketApi>>>;
 
+#[derive(Clone)]
 pub struct MeshDevicesState {
     pub inner: MeshDevicesStateInner,
+    /// Directory device histories are persisted to, if persistence is enabled
+    pub history_dir: Option<PathBuf>,
+    /// Directory every new connection is captured to, if capturing is enabled
+    pub capture_dir: Option<PathBuf>,
+    /// Alert rules evaluated against the telemetry of every connection, kept
+    /// here so that they outlive the connections
+    pub alerts: Arc<Mutex<AlertEngine>>,
 }
 
 impl MeshDevicesState {
-    pub fn new() -> Self {
+    pub fn new(history_dir: Option<PathBuf>, capture_dir: Option<PathBuf>) -> Self {
         Self {
             inner: Arc::new(async_runtime::Mutex::new(HashMap::new())),
+            history_dir,
+            capture_dir,
+            alerts: Arc::new(Mutex::new(AlertEngine::default())),
         }
     }
 }
@@ -1,6 +1,8 @@
 pub mod autoconnect;
+pub mod events;
 pub mod graph;
 pub mod mesh_devices;
+pub mod mqtt;
 pub mod radio_connections;
 
 pub type DeviceKey = String;
@@ -0,0 +1,18 @@
+use std::sync::Arc;
+
+use tauri::async_runtime::{self, JoinHandle};
+
+pub type MqttBridgeStateInner = Arc<async_runtime::Mutex<Option<JoinHandle<()>>>>;
+
+#[derive(Clone)]
+pub struct MqttBridgeState {
+    pub inner: MqttBridgeStateInner,
+}
+
+impl MqttBridgeState {
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(async_runtime::Mutex::new(None)),
+        }
+    }
+}
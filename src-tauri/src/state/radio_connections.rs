@@ -7,6 +7,7 @@ use super::DeviceKey;
 pub type RadioConnectionsStateInner =
     Arc<async_runtime::Mutex<HashMap<DeviceKey, ConnectedStreamApi>>>;
 
+#[derive(Clone)]
 pub struct RadioConnectionsState {
     pub inner: RadioConnectionsStateInner,
 }
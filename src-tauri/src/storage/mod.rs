@@ -0,0 +1,625 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread::{self, JoinHandle};
+
+use log::{debug, trace, warn};
+use serde::{Deserialize, Serialize};
+
+use meshtastic::protobufs;
+
+use crate::device::{
+    roster::NodeAnnotation, ChannelMessageState, ChannelMessageWithState, MeshChannel, MeshDevice,
+    MeshNode, MeshNodeAirQualityMetrics, MeshNodeDeviceMetrics, MeshNodeEnvironmentMetrics,
+    MeshNodeHealthMetrics, MeshNodeLocalStats, MeshNodePowerMetrics, NeighborInfoPacket,
+    NormalizedPosition, NormalizedWaypoint, TracerouteResult,
+};
+use crate::state::DeviceKey;
+
+/// Number of records appended to a history file before it is rewritten
+/// from the in-memory device state
+pub const HISTORY_COMPACTION_THRESHOLD: usize = 5000;
+
+/// A single entry in a device history file. Records are replayed in order
+/// on rehydration, so later records take precedence over earlier ones.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum HistoryRecord {
+    /// A node with all of its samples, as written on compaction
+    Node {
+        node: MeshNode,
+    },
+    /// A node without its samples, which are recorded one at a time
+    NodeAttributes {
+        node: MeshNode,
+    },
+    NodeSample {
+        node_num: u32,
+        sample: NodeSample,
+    },
+    Message {
+        channel: u32,
+        message: ChannelMessageWithState,
+    },
+    MessageState {
+        channel: u32,
+        message_id: u32,
+        state: ChannelMessageState,
+    },
+    Waypoint {
+        waypoint: NormalizedWaypoint,
+    },
+    WaypointDeleted {
+        waypoint_id: u32,
+    },
+    NeighborInfo {
+        neighbor_info: NeighborInfoPacket,
+    },
+    Traceroute {
+        traceroute: TracerouteResult,
+    },
+    /// A local annotation of a node, such as one imported from a roster
+    Annotation {
+        node_num: u32,
+        annotation: NodeAnnotation,
+    },
+}
+
+/// The series of samples kept for each node
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NodeSeries {
+    DeviceMetrics,
+    EnvironmentMetrics,
+    Position,
+    AirQualityMetrics,
+    PowerMetrics,
+    LocalStats,
+    HealthMetrics,
+}
+
+impl NodeSeries {
+    /// The series a telemetry packet is stored in, if any
+    pub fn from_telemetry(telemetry: &protobufs::Telemetry) -> Option<Self> {
+        let series = match telemetry.variant.as_ref()? {
+            protobufs::telemetry::Variant::DeviceMetrics(_) => Self::DeviceMetrics,
+            protobufs::telemetry::Variant::EnvironmentMetrics(_) => Self::EnvironmentMetrics,
+            protobufs::telemetry::Variant::AirQualityMetrics(_) => Self::AirQualityMetrics,
+            protobufs::telemetry::Variant::PowerMetrics(_) => Self::PowerMetrics,
+            protobufs::telemetry::Variant::LocalStats(_) => Self::LocalStats,
+            protobufs::telemetry::Variant::HealthMetrics(_) => Self::HealthMetrics,
+        };
+
+        Some(series)
+    }
+}
+
+/// A sample appended to one of a node's series
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "series", content = "sample")]
+pub enum NodeSample {
+    DeviceMetrics(MeshNodeDeviceMetrics),
+    EnvironmentMetrics(MeshNodeEnvironmentMetrics),
+    Position(NormalizedPosition),
+    AirQualityMetrics(MeshNodeAirQualityMetrics),
+    PowerMetrics(MeshNodePowerMetrics),
+    LocalStats(MeshNodeLocalStats),
+    HealthMetrics(MeshNodeHealthMetrics),
+}
+
+impl HistoryRecord {
+    /// Records the attributes of a node, leaving out its samples so that
+    /// records don't grow with the node's history
+    pub fn node_attributes(node: &MeshNode) -> Self {
+        HistoryRecord::NodeAttributes {
+            node: MeshNode {
+                last_heard: node.last_heard.clone(),
+                user: node.user.clone(),
+                first_seen: node.first_seen,
+                hops_away: node.hops_away,
+                via_mqtt: node.via_mqtt,
+                packet_count: node.packet_count,
+                ..MeshNode::new(node.node_num)
+            },
+        }
+    }
+
+    /// Records the latest sample of one of a node's series
+    pub fn node_sample(node: &MeshNode, series: NodeSeries) -> Option<Self> {
+        let sample = match series {
+            NodeSeries::DeviceMetrics => {
+                NodeSample::DeviceMetrics(node.device_metrics.back()?.clone())
+            }
+            NodeSeries::EnvironmentMetrics => {
+                NodeSample::EnvironmentMetrics(node.environment_metrics.back()?.clone())
+            }
+            NodeSeries::Position => NodeSample::Position(node.position_metrics.back()?.clone()),
+            NodeSeries::AirQualityMetrics => {
+                NodeSample::AirQualityMetrics(node.air_quality_metrics.back()?.clone())
+            }
+            NodeSeries::PowerMetrics => {
+                NodeSample::PowerMetrics(node.power_metrics.back()?.clone())
+            }
+            NodeSeries::LocalStats => NodeSample::LocalStats(node.local_stats.back()?.clone()),
+            NodeSeries::HealthMetrics => {
+                NodeSample::HealthMetrics(node.health_metrics.back()?.clone())
+            }
+        };
+
+        Some(HistoryRecord::NodeSample {
+            node_num: node.node_num,
+            sample,
+        })
+    }
+}
+
+enum WriterCommand {
+    Append(HistoryRecord),
+    Replace(Vec<HistoryRecord>),
+}
+
+/// Append-only, newline-delimited JSON store of the state a device has
+/// accumulated, keyed by the device connection and the radio's node number.
+/// Records are written on a background thread, so that callers holding the
+/// device lock don't wait on the disk.
+pub struct DeviceHistoryStore {
+    path: PathBuf,
+    commands: Option<mpsc::Sender<WriterCommand>>,
+    writer_thread: Option<JoinHandle<()>>,
+    records_since_compaction: usize,
+}
+
+impl DeviceHistoryStore {
+    pub fn open(base_dir: &Path, device_key: &DeviceKey, my_node_num: u32) -> io::Result<Self> {
+        fs::create_dir_all(base_dir)?;
+
+        let path = base_dir.join(history_file_name(device_key, my_node_num));
+        debug!("Opening device history file at {:?}", path);
+
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let (commands, receiver) = mpsc::channel();
+
+        let writer_path = path.clone();
+        let writer_thread = thread::Builder::new()
+            .name("device-history-writer".into())
+            .spawn(move || run_writer(writer_path, file, receiver))?;
+
+        Ok(Self {
+            path,
+            commands: Some(commands),
+            writer_thread: Some(writer_thread),
+            records_since_compaction: 0,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn needs_compaction(&self) -> bool {
+        self.records_since_compaction >= HISTORY_COMPACTION_THRESHOLD
+    }
+
+    pub fn append(&mut self, record: HistoryRecord) -> io::Result<()> {
+        trace!("Appending history record: {:?}", record);
+
+        self.send(WriterCommand::Append(record))?;
+        self.records_since_compaction += 1;
+
+        Ok(())
+    }
+
+    fn send(&self, command: WriterCommand) -> io::Result<()> {
+        self.commands
+            .as_ref()
+            .and_then(|commands| commands.send(command).ok())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::BrokenPipe, "History writer stopped"))
+    }
+
+    /// Replays all records in the history file into the passed device.
+    /// Malformed records (e.g., a partially written final line) are skipped.
+    /// Must be called before any record is appended.
+    pub fn rehydrate(&self, device: &mut MeshDevice) -> io::Result<usize> {
+        let reader = BufReader::new(File::open(&self.path)?);
+        let mut applied = 0;
+
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            match serde_json::from_str::<HistoryRecord>(&line) {
+                Ok(record) => {
+                    apply_record(device, record);
+                    applied += 1;
+                }
+                Err(e) => {
+                    warn!(
+                        "Skipping malformed history record on line {} of {:?}: {}",
+                        index + 1,
+                        self.path,
+                        e
+                    );
+                }
+            }
+        }
+
+        // Samples are recorded as they were received, so the retention
+        // policy is applied once they have all been added back
+        device.set_telemetry_retention(device.telemetry_retention);
+
+        debug!(
+            "Rehydrated {} history records from {:?}",
+            applied, self.path
+        );
+
+        Ok(applied)
+    }
+
+    /// Rewrites the history file so that it only contains the current state
+    /// of the passed device, dropping superseded records.
+    pub fn compact(&mut self, device: &MeshDevice) -> io::Result<()> {
+        debug!("Compacting device history file at {:?}", self.path);
+
+        self.send(WriterCommand::Replace(snapshot_records(device)))?;
+        self.records_since_compaction = 0;
+
+        Ok(())
+    }
+}
+
+impl Drop for DeviceHistoryStore {
+    // Waits for queued records to be written
+    fn drop(&mut self) {
+        self.commands.take();
+
+        if let Some(writer_thread) = self.writer_thread.take() {
+            if writer_thread.join().is_err() {
+                warn!("Device history writer for {:?} panicked", self.path);
+            }
+        }
+    }
+}
+
+/// Writes records until the store is dropped. The file is flushed whenever
+/// there are no more records queued, so bursts of packets share one flush.
+fn run_writer(path: PathBuf, file: File, commands: mpsc::Receiver<WriterCommand>) {
+    let mut writer = BufWriter::new(file);
+
+    while let Ok(command) = commands.recv() {
+        let mut next_command = Some(command);
+
+        while let Some(command) = next_command {
+            if let Err(e) = write_command(&path, &mut writer, command) {
+                warn!("Failed to write device history to {:?}: {}", path, e);
+            }
+
+            next_command = commands.try_recv().ok();
+        }
+
+        if let Err(e) = writer.flush() {
+            warn!("Failed to flush device history to {:?}: {}", path, e);
+        }
+    }
+}
+
+fn write_command(
+    path: &Path,
+    writer: &mut BufWriter<File>,
+    command: WriterCommand,
+) -> io::Result<()> {
+    match command {
+        WriterCommand::Append(record) => write_record(writer, &record),
+        WriterCommand::Replace(records) => {
+            writer.flush()?;
+
+            let temp_path = path.with_extension("jsonl.tmp");
+
+            {
+                let mut temp_writer = BufWriter::new(File::create(&temp_path)?);
+
+                for record in records.iter() {
+                    write_record(&mut temp_writer, record)?;
+                }
+
+                temp_writer.flush()?;
+            }
+
+            fs::rename(&temp_path, path)?;
+
+            let file = OpenOptions::new().append(true).open(path)?;
+            *writer = BufWriter::new(file);
+
+            Ok(())
+        }
+    }
+}
+
+fn write_record(writer: &mut impl Write, record: &HistoryRecord) -> io::Result<()> {
+    serde_json::to_writer(&mut *writer, record)?;
+    writer.write_all(b"\n")
+}
+
+fn history_file_name(device_key: &DeviceKey, my_node_num: u32) -> String {
+    // Device keys are port names, Bluetooth names or socket addresses,
+    // none of which are guaranteed to be valid file names
+    let sanitized_key: String = device_key
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+
+    // Different keys can sanitize to the same name, so the name also
+    // carries a hash of the key itself
+    format!(
+        "{}-{:016x}-{}.jsonl",
+        sanitized_key,
+        stable_hash(device_key),
+        my_node_num
+    )
+}
+
+/// FNV-1a, which unlike the standard library's hasher is guaranteed to
+/// give the same hash across releases
+fn stable_hash(value: &str) -> u64 {
+    value.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+fn apply_record(device: &mut MeshDevice, record: HistoryRecord) {
+    match record {
+        HistoryRecord::Node { node } => {
+            device.nodes.insert(node.node_num, node);
+        }
+        HistoryRecord::NodeAttributes { node } => {
+            let existing = device.node_mut(node.node_num);
+
+            existing.last_heard = node.last_heard;
+            existing.user = node.user;
+            existing.first_seen = node.first_seen;
+            existing.hops_away = node.hops_away;
+            existing.via_mqtt = node.via_mqtt;
+            existing.packet_count = node.packet_count;
+        }
+        HistoryRecord::NodeSample { node_num, sample } => {
+            let node = device.node_mut(node_num);
+
+            match sample {
+                NodeSample::DeviceMetrics(sample) => node.device_metrics.push_back(sample),
+                NodeSample::EnvironmentMetrics(sample) => {
+                    node.environment_metrics.push_back(sample)
+                }
+                NodeSample::Position(sample) => node.position_metrics.push_back(sample),
+                NodeSample::AirQualityMetrics(sample) => node.air_quality_metrics.push_back(sample),
+                NodeSample::PowerMetrics(sample) => node.power_metrics.push_back(sample),
+                NodeSample::LocalStats(sample) => node.local_stats.push_back(sample),
+                NodeSample::HealthMetrics(sample) => node.health_metrics.push_back(sample),
+            }
+        }
+        HistoryRecord::Message { channel, message } => {
+            let mesh_channel = device.channels.entry(channel).or_insert_with(|| {
+                let mut mesh_channel = MeshChannel::default();
+                mesh_channel.config.index = channel as i32;
+                mesh_channel
+            });
+
+            let message_id = message.packet_id();
+
+            match mesh_channel
+                .messages
+                .iter_mut()
+                .find(|m| m.packet_id() == message_id)
+            {
+                Some(existing) => *existing = message,
+                None => mesh_channel.messages.push(message),
+            }
+        }
+        HistoryRecord::MessageState {
+            channel,
+            message_id,
+            state,
+        } => {
+            device.set_message_state(channel, message_id, state);
+        }
+        HistoryRecord::Waypoint { waypoint } => {
+            device.waypoints.insert(waypoint.id, waypoint);
+        }
+        HistoryRecord::WaypointDeleted { waypoint_id } => {
+            device.waypoints.remove(&waypoint_id);
+        }
+        HistoryRecord::NeighborInfo { neighbor_info } => {
+            device
+                .neighbors
+                .insert(neighbor_info.packet.from, neighbor_info);
+        }
+        HistoryRecord::Traceroute { traceroute } => {
+            device
+                .traceroutes
+                .insert(traceroute.destination, traceroute);
+        }
+        HistoryRecord::Annotation {
+            node_num,
+            annotation,
+        } => {
+            device.roster.insert(node_num, annotation);
+        }
+    }
+}
+
+fn snapshot_records(device: &MeshDevice) -> Vec<HistoryRecord> {
+    let mut records = vec![];
+
+    records.extend(
+        device
+            .nodes
+            .values()
+            .map(|node| HistoryRecord::Node { node: node.clone() }),
+    );
+
+    for (channel, mesh_channel) in device.channels.iter() {
+        records.extend(
+            mesh_channel
+                .messages
+                .iter()
+                .map(|message| HistoryRecord::Message {
+                    channel: *channel,
+                    message: message.clone(),
+                }),
+        );
+    }
+
+    records.extend(
+        device
+            .waypoints
+            .values()
+            .map(|waypoint| HistoryRecord::Waypoint {
+                waypoint: waypoint.clone(),
+            }),
+    );
+
+    records.extend(
+        device
+            .neighbors
+            .values()
+            .map(|neighbor_info| HistoryRecord::NeighborInfo {
+                neighbor_info: neighbor_info.clone(),
+            }),
+    );
+
+    records.extend(
+        device
+            .traceroutes
+            .values()
+            .map(|traceroute| HistoryRecord::Traceroute {
+                traceroute: traceroute.clone(),
+            }),
+    );
+
+    records.extend(
+        device
+            .roster
+            .iter()
+            .map(|(node_num, annotation)| HistoryRecord::Annotation {
+                node_num: *node_num,
+                annotation: annotation.clone(),
+            }),
+    );
+
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Recent enough to stay outside of downsampling on rehydration
+    fn position(latitude: f32) -> NodeSample {
+        NodeSample::Position(NormalizedPosition {
+            latitude,
+            timestamp: crate::device::helpers::get_current_time_u32(),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn history_file_name_is_sanitized() {
+        let name = history_file_name(&"/dev/ttyUSB0".to_string(), 1234);
+        assert!(name.starts_with("_dev_ttyUSB0-"));
+        assert!(name.ends_with("-1234.jsonl"));
+
+        // Keys that sanitize to the same name don't share a file
+        assert_ne!(
+            history_file_name(&"192.168.1.5:4403".to_string(), 42),
+            history_file_name(&"192_168_1_5_4403".to_string(), 42)
+        );
+    }
+
+    #[test]
+    fn rehydrates_appended_and_compacted_records() {
+        let dir = std::env::temp_dir().join(format!("history-test-{}", std::process::id()));
+        let device_key = "/dev/ttyUSB0".to_string();
+        let _ = fs::remove_dir_all(&dir);
+
+        let mut node = MeshNode::new(2);
+        node.user = Some(protobufs::User {
+            long_name: "Relay".into(),
+            ..Default::default()
+        });
+
+        {
+            let mut store = DeviceHistoryStore::open(&dir, &device_key, 1).unwrap();
+            store.append(HistoryRecord::node_attributes(&node)).unwrap();
+            store
+                .append(HistoryRecord::NodeSample {
+                    node_num: 2,
+                    sample: position(1.0),
+                })
+                .unwrap();
+        }
+
+        let mut store = DeviceHistoryStore::open(&dir, &device_key, 1).unwrap();
+        let mut device = MeshDevice::new();
+        assert_eq!(store.rehydrate(&mut device).unwrap(), 2);
+
+        store.compact(&device).unwrap();
+        store
+            .append(HistoryRecord::NodeSample {
+                node_num: 2,
+                sample: position(2.0),
+            })
+            .unwrap();
+        let path = store.path().to_path_buf();
+        drop(store);
+
+        // The attributes and first sample were compacted into a single record
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 2);
+
+        let store = DeviceHistoryStore::open(&dir, &device_key, 1).unwrap();
+        let mut device = MeshDevice::new();
+        store.rehydrate(&mut device).unwrap();
+
+        let node = &device.nodes[&2];
+        assert_eq!(node.user.as_ref().unwrap().long_name, "Relay");
+        let latitudes: Vec<f32> = node.position_metrics.iter().map(|p| p.latitude).collect();
+        assert_eq!(latitudes, vec![1.0, 2.0]);
+
+        drop(store);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn keeps_annotations_through_compaction() {
+        let dir = std::env::temp_dir().join(format!("roster-test-{}", std::process::id()));
+        let device_key = "/dev/ttyUSB0".to_string();
+        let _ = fs::remove_dir_all(&dir);
+
+        let annotation = NodeAnnotation {
+            nickname: Some("North".into()),
+            ..Default::default()
+        };
+
+        {
+            let mut store = DeviceHistoryStore::open(&dir, &device_key, 1).unwrap();
+            store
+                .append(HistoryRecord::Annotation {
+                    node_num: 2,
+                    annotation: annotation.clone(),
+                })
+                .unwrap();
+
+            let mut device = MeshDevice::new();
+            device.roster.insert(2, annotation.clone());
+            store.compact(&device).unwrap();
+        }
+
+        let store = DeviceHistoryStore::open(&dir, &device_key, 1).unwrap();
+        let mut device = MeshDevice::new();
+        assert_eq!(store.rehydrate(&mut device).unwrap(), 1);
+        assert_eq!(device.roster.get(&2), Some(&annotation));
+
+        drop(store);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -2,7 +2,7 @@ import { listen } from "@tauri-apps/api/event";
 import { useDispatch } from "react-redux";
 import { warn } from "tauri-plugin-log-api";
 
-import type { MeshGraph } from "@app/types/graph";
+import type { GraphDelta, MeshGraph } from "@app/types/graph";
 import type { app_device_MeshDevice } from "@bindings/index";
 
 import { connectionSliceActions } from "@features/connection/slice";
@@ -106,11 +106,18 @@ export const useCreateGraphUpdateChannel = () => {
   const dispatch = useDispatch();
 
   const createChannel = async () => {
-    const unlisten = await listen<MeshGraph>("graph_update", (event) => {
+    const unlistenUpdate = await listen<MeshGraph>("graph_update", (event) => {
       dispatch(graphSliceActions.setGraph(event.payload));
     });
 
-    return unlisten;
+    const unlistenDelta = await listen<GraphDelta[]>("graph_delta", (event) => {
+      dispatch(graphSliceActions.applyGraphDeltas(event.payload));
+    });
+
+    return () => {
+      unlistenUpdate();
+      unlistenDelta();
+    };
   };
 
   return createChannel;
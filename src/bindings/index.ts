@@ -86,7 +86,7 @@ export type meshtastic_protobufs_DeviceMetadata = { firmwareVersion: string; dev
  */
 export type meshtastic_protobufs_NetworkConnectionStatus = { ipAddress: number; isConnected: boolean; isMqttConnected: boolean; isSyslogConnected: boolean }
 
-export type app_graph_ds_edge_GraphEdge = { snr: number; from: number; to: number; lastHeard: string; timeoutDuration: string }
+export type app_graph_ds_edge_GraphEdge = { snr: number; from: number; to: number; lastHeard: string; timeoutDuration: string; broadcastInterval: string | null; distanceMeters: number | null; bearingDegrees: number | null; rxStatistics: app_graph_ds_link_statistics_LinkStatistics | null }
 
 /**
  * 
@@ -752,7 +752,24 @@ export type meshtastic_protobufs_AdminMessage = { payloadVariant: meshtastic_pro
  */
 export type meshtastic_protobufs_Routing = { variant: meshtastic_protobufs_routing_Variant | null }
 
-export type app_graph_ds_node_GraphNode = { nodeNum: number; lastHeard: string; timeoutDuration: string }
+/**
+ * A single change to the graph, emitted so that clients don't need to
+ * receive the whole graph after every packet
+ */
+export type app_graph_ds_delta_GraphDelta = { type: "nodeUpserted"; node: app_graph_ds_node_GraphNode } | { type: "nodeRemoved"; nodeNum: number } | { type: "edgeUpserted"; from: number; to: number; edge: app_graph_ds_edge_GraphEdge } | { type: "edgeRemoved"; from: number; to: number }
+
+/**
+ * A node in the mesh graph. Nodes are identified by their node number alone,
+ * so that a node's attributes can change without affecting its links.
+ */
+export type app_graph_ds_node_GraphNode = { nodeNum: number; lastHeard: string; timeoutDuration: string; broadcastInterval: string | null; position: app_graph_ds_position_GraphPosition | null; inferred: boolean }
+
+export type app_graph_ds_position_GraphPosition = { latitude: number; longitude: number; altitude: number | null }
+
+/**
+ * Running signal statistics of the packets received over a link
+ */
+export type app_graph_ds_link_statistics_LinkStatistics = { packetCount: number; meanSnr: number; minSnr: number; maxSnr: number; meanRssi: number | null; rssiCount: number }
 
 /**
  * 
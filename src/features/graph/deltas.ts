@@ -0,0 +1,72 @@
+import type { GraphDelta, StableGraph } from "@app/types/graph";
+
+const findNodeIndex = (graph: StableGraph, nodeNum: number) =>
+  graph.nodes.findIndex((node) => node.nodeNum === nodeNum);
+
+const findEdgeIndex = (graph: StableGraph, from: number, to: number) =>
+  graph.edges.findIndex(([source, target]) => source === from && target === to);
+
+/**
+ * Applies a change emitted by the backend to a serialized graph in place.
+ * Edges reference nodes by their index in `nodes`, so indices are shifted
+ * when a node is removed.
+ */
+export const applyGraphDelta = (graph: StableGraph, delta: GraphDelta) => {
+  switch (delta.type) {
+    case "nodeUpserted": {
+      const index = findNodeIndex(graph, delta.node.nodeNum);
+
+      if (index === -1) {
+        graph.nodes.push(delta.node);
+      } else {
+        graph.nodes[index] = delta.node;
+      }
+
+      break;
+    }
+
+    case "nodeRemoved": {
+      const index = findNodeIndex(graph, delta.nodeNum);
+      if (index === -1) break;
+
+      graph.nodes.splice(index, 1);
+      graph.edges = graph.edges
+        .filter(([source, target]) => source !== index && target !== index)
+        .map(([source, target, edge]) => [
+          source > index ? source - 1 : source,
+          target > index ? target - 1 : target,
+          edge,
+        ]);
+
+      break;
+    }
+
+    case "edgeUpserted": {
+      const from = findNodeIndex(graph, delta.from);
+      const to = findNodeIndex(graph, delta.to);
+      if (from === -1 || to === -1) break;
+
+      const index = findEdgeIndex(graph, from, to);
+
+      if (index === -1) {
+        graph.edges.push([from, to, delta.edge]);
+      } else {
+        graph.edges[index] = [from, to, delta.edge];
+      }
+
+      break;
+    }
+
+    case "edgeRemoved": {
+      const from = findNodeIndex(graph, delta.from);
+      const to = findNodeIndex(graph, delta.to);
+      const index = findEdgeIndex(graph, from, to);
+
+      if (index !== -1) {
+        graph.edges.splice(index, 1);
+      }
+
+      break;
+    }
+  }
+};
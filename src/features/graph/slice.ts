@@ -1,6 +1,8 @@
-import type { MeshGraph } from "@app/types/graph";
+import type { GraphDelta, MeshGraph } from "@app/types/graph";
 import { type PayloadAction, createSlice } from "@reduxjs/toolkit";
 
+import { applyGraphDelta } from "@features/graph/deltas";
+
 export type IGraphState = {
   graph: MeshGraph["graph"] | null;
 };
@@ -16,6 +18,14 @@ export const graphSlice = createSlice({
     setGraph: (state, action: PayloadAction<MeshGraph>) => {
       state.graph = action.payload.graph;
     },
+    applyGraphDeltas: (state, action: PayloadAction<GraphDelta[]>) => {
+      // Deltas received before the initial sync are covered by it
+      if (!state.graph) return;
+
+      for (const delta of action.payload) {
+        applyGraphDelta(state.graph, delta);
+      }
+    },
   },
 });
 
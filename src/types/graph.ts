@@ -14,3 +14,8 @@ export interface MeshGraph {
   graph: StableGraph;
   nodesLookup: Record<number, app_graph_ds_node_GraphNode>;
 }
+
+// Re-exported from the Specta bindings of `GraphDelta`, see `export_ts_types`
+export type {
+  app_graph_ds_delta_GraphDelta as GraphDelta,
+} from "@bindings/index";